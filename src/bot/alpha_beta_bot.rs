@@ -0,0 +1,701 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::game::{ChessEngine, Result};
+use crate::game::board::color::Color;
+use crate::game::board::move_kind::MoveKind;
+use crate::game::board::move_struct::Move;
+use crate::game::board::position::Position;
+use crate::game::pieces::piece_kind::PieceKind;
+use crate::tablebase::{Tablebase, Wdl};
+use crate::tablebase::dtm::Dtm;
+
+use super::evaluation::{evaluate, piece_value};
+use super::Bot;
+use super::score::Score;
+use super::search::{SearchControl, SearchLimits, SearchResult, MAX_DEPTH};
+use super::transposition::{Bound, Entry, TranspositionTable};
+
+const MAX_PLY: usize = 64;
+
+/// A root move and its score for the side to move
+pub(crate) type ScoredMove = ((Position, Position), Score);
+const NULL_MOVE_REDUCTION: i16 = 2;
+// Moves searched before late move reductions kick in
+const LATE_MOVE_INDEX: usize = 3;
+// Indexed by the remaining depth
+const FUTILITY_MARGINS: [i32; 3] = [0, 200, 450];
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+
+/// The selective search techniques used by `AlphaBetaBot`, each one can be
+/// turned off to measure its effect
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Pruning {
+    pub(crate) null_move: bool,
+    pub(crate) late_move_reductions: bool,
+    pub(crate) futility: bool,
+    pub(crate) check_extensions: bool,
+    // The quiescence search skips the captures losing material
+    pub(crate) bad_captures: bool,
+}
+
+impl Pruning {
+    pub(crate) const ALL: Self = Self {
+        null_move: true,
+        late_move_reductions: true,
+        futility: true,
+        check_extensions: true,
+        bad_captures: true,
+    };
+
+    pub(crate) const NONE: Self = Self {
+        null_move: false,
+        late_move_reductions: false,
+        futility: false,
+        check_extensions: false,
+        bad_captures: false,
+    };
+}
+
+impl Default for Pruning {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Iterative deepening alpha-beta. With more than one thread, the helper
+/// threads search the same position at staggered depths and only share the
+/// transposition table with the main thread (Lazy SMP): what they find
+/// speeds up the main search, which alone gives the result
+pub(crate) struct AlphaBetaBot {
+    pruning: Pruning,
+    control: SearchControl,
+    table: Arc<TranspositionTable>,
+    threads: usize,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl AlphaBetaBot {
+    pub(crate) fn new() -> Self {
+        Self {
+            pruning: Pruning::default(),
+            control: SearchControl::new(&SearchLimits::default()),
+            table: Arc::new(TranspositionTable::default()),
+            threads: 1,
+            tablebase: None,
+        }
+    }
+
+    pub(crate) fn with_pruning(mut self, pruning: Pruning) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    /// Scores every root move with a full window, depth after depth, for
+    /// the bots that don't always play the best move. Once the limits are
+    /// hit, the moves of the last completed depth are returned, or the moves
+    /// scored so far when not even the first depth was completed
+    pub(crate) fn score_root_moves(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+    ) -> (Vec<ScoredMove>, SearchResult) {
+        let mut chess_game: ChessEngine = chess_game.clone();
+        self.control = SearchControl::new(&limits);
+        let mut scored: Vec<ScoredMove> = Vec::new();
+        let mut depth_reached: i16 = 0;
+
+        for depth in 1..=limits.max_depth() {
+            let mut iteration: Vec<ScoredMove> = Vec::new();
+
+            for (from, to) in ordered_moves(&chess_game).iter().map(|m| (m.from(), m.to())) {
+                chess_game.try_move(Some(from), Some(to));
+                let score: Score =
+                    -self.alpha_beta(&mut chess_game, depth - 1, 1, -Score::INFINITY, Score::INFINITY, true, &mut Vec::new());
+                chess_game.undo_move();
+
+                if self.control.aborted() {
+                    break;
+                }
+                iteration.push(((from, to), score));
+            }
+
+            if self.control.aborted() {
+                if scored.is_empty() {
+                    scored = iteration;
+                }
+                break;
+            }
+            scored = iteration;
+            depth_reached = depth;
+        }
+
+        let best: Option<&ScoredMove> = scored.iter().max_by_key(|(_, score)| *score);
+        let result: SearchResult = self.control.result(
+            best.map_or(Score::ZERO, |(_, score)| *score),
+            best.map(|(best_move, _)| *best_move).into_iter().collect(),
+            depth_reached,
+        );
+
+        (scored, result)
+    }
+
+    /// Searches depth after depth from `first_depth`, until `max_depth` or
+    /// until the search is aborted
+    fn deepen(
+        &mut self,
+        chess_game: &mut ChessEngine,
+        first_depth: i16,
+        max_depth: i16,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut result: SearchResult = self.control.result(Score::ZERO, Vec::new(), 0);
+
+        for depth in first_depth..=max_depth {
+            let (score, pv): (Score, Vec<(Position, Position)>) = self.root(chess_game, depth, result.best_move());
+
+            // An unfinished iteration is only better than nothing
+            if self.control.aborted() {
+                if result.best_move().is_none() {
+                    let pv: Vec<(Position, Position)> = if pv.is_empty() {
+                        ordered_moves(chess_game).first().map(|m| (m.from(), m.to())).into_iter().collect()
+                    } else {
+                        pv
+                    };
+                    result = self.control.result(score, pv, depth);
+                }
+                break;
+            }
+
+            result = self.control.result(score, pv, depth);
+            on_iteration(&result);
+
+            if score.is_mate() && score > Score::ZERO {
+                break;
+            }
+        }
+
+        result
+    }
+
+    /// Searches every root move, the best move of the previous iteration first
+    fn root(
+        &mut self,
+        chess_game: &mut ChessEngine,
+        depth: i16,
+        previous_best: Option<(Position, Position)>,
+    ) -> (Score, Vec<(Position, Position)>) {
+        let mut alpha: Score = -Score::INFINITY;
+        let mut pv: Vec<(Position, Position)> = Vec::new();
+
+        let mut root_moves: Vec<Move> = ordered_moves(chess_game);
+        move_first(&mut root_moves, previous_best);
+
+        for (from, to) in root_moves.iter().map(|m| (m.from(), m.to())) {
+            let mut child_pv: Vec<(Position, Position)> = Vec::new();
+            chess_game.try_move(Some(from), Some(to));
+            let score: Score = -self.alpha_beta(chess_game, depth - 1, 1, -Score::INFINITY, -alpha, true, &mut child_pv);
+            chess_game.undo_move();
+
+            if self.control.aborted() {
+                break;
+            }
+
+            if score > alpha || pv.is_empty() {
+                alpha = score;
+                pv = vec![(from, to)];
+                pv.append(&mut child_pv);
+            }
+        }
+
+        (alpha, pv)
+    }
+
+    /// Fail-soft alpha-beta, returns the score of the current player and
+    /// fills `pv` with the best line when a move raises alpha
+    #[allow(clippy::too_many_arguments)]
+    fn alpha_beta(
+        &mut self,
+        chess_game: &mut ChessEngine,
+        mut depth: i16,
+        ply: usize,
+        mut alpha: Score,
+        beta: Score,
+        null_allowed: bool,
+        pv: &mut Vec<(Position, Position)>,
+    ) -> Score {
+        if !self.control.visit(ply) {
+            return Score::ZERO;
+        }
+
+        match chess_game.result() {
+            Result::Checkmate => return Score::mated_in(ply),
+            Result::Draw | Result::Stalemate => return Score::ZERO,
+            Result::None => {}
+        }
+
+        if let Some(score) = self.tablebase.as_ref().and_then(|tablebase| tablebase_score(tablebase, chess_game, ply)) {
+            return score;
+        }
+
+        let in_check: bool = chess_game.checked_king().is_some();
+        if in_check && self.pruning.check_extensions && ply < MAX_PLY {
+            depth += 1;
+        }
+
+        if depth <= 0 || ply >= MAX_PLY {
+            return self.quiescence(chess_game, ply, alpha, beta);
+        }
+
+        let key: u64 = chess_game.key();
+        let entry: Option<Entry> = self.table.probe(key);
+        if let Some(entry) = entry.filter(|entry| entry.depth() >= depth) {
+            let score: Score = entry.score(ply);
+            let cutoff: bool = match entry.bound() {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if cutoff {
+                pv.extend(entry.best_move());
+                return score;
+            }
+        }
+
+        let current_player: Color = chess_game.current_player();
+        let static_eval: Score = evaluate(chess_game).relative(current_player);
+
+        // Reverse futility pruning: the position is so good that a shallow
+        // search is not going to bring it back under beta
+        if self.pruning.futility && !in_check && depth <= 3 && !beta.is_mate() {
+            let margin: Score = Score::centipawns(REVERSE_FUTILITY_MARGIN * i32::from(depth));
+            if static_eval - margin >= beta {
+                return static_eval;
+            }
+        }
+
+        // Null move pruning: if passing the turn still fails high, a real move
+        // would too. It is unsound in zugzwang, which mostly happens when only
+        // pawns are left, so it is skipped there
+        if self.pruning.null_move
+            && null_allowed
+            && !in_check
+            && depth > NULL_MOVE_REDUCTION
+            && !beta.is_mate()
+            && has_non_pawn_material(chess_game, current_player)
+        {
+            chess_game.make_null_move();
+            let score: Score = -self.alpha_beta(
+                chess_game,
+                depth - 1 - NULL_MOVE_REDUCTION,
+                ply + 1,
+                -beta,
+                -beta + Score::centipawns(1),
+                false,
+                &mut Vec::new(),
+            );
+            chess_game.undo_null_move();
+
+            if score >= beta {
+                return score;
+            }
+        }
+
+        // Futility pruning: quiet moves can't raise a hopeless position above
+        // alpha at the frontier
+        let futile: bool = self.pruning.futility
+            && !in_check
+            && (depth as usize) < FUTILITY_MARGINS.len()
+            && !alpha.is_mate()
+            && static_eval + Score::centipawns(FUTILITY_MARGINS[depth as usize]) <= alpha;
+
+        let original_alpha: Score = alpha;
+        let mut best: Score = -Score::INFINITY;
+        let mut best_move: Option<(Position, Position)> = None;
+        let mut moves: Vec<Move> = ordered_moves(chess_game);
+        move_first(&mut moves, entry.and_then(|entry| entry.best_move()));
+
+        for (index, possible_move) in moves.into_iter().enumerate() {
+            let quiet: bool = is_quiet(&possible_move);
+            chess_game.try_move(Some(possible_move.from()), Some(possible_move.to()));
+            let gives_check: bool = chess_game.checked_king().is_some();
+
+            if futile && quiet && !gives_check {
+                chess_game.undo_move();
+                best = best.max(static_eval);
+                continue;
+            }
+
+            let reduce: bool = self.pruning.late_move_reductions
+                && depth >= 3
+                && index >= LATE_MOVE_INDEX
+                && quiet
+                && !in_check
+                && !gives_check;
+
+            let mut child_pv: Vec<(Position, Position)> = Vec::new();
+            let mut score: Score;
+            if reduce {
+                score = -self.alpha_beta(chess_game, depth - 2, ply + 1, -alpha - Score::centipawns(1), -alpha, true, &mut child_pv);
+                if score > alpha {
+                    child_pv.clear();
+                    score = -self.alpha_beta(chess_game, depth - 1, ply + 1, -beta, -alpha, true, &mut child_pv);
+                }
+            } else {
+                score = -self.alpha_beta(chess_game, depth - 1, ply + 1, -beta, -alpha, true, &mut child_pv);
+            }
+
+            chess_game.undo_move();
+
+            if self.control.aborted() {
+                return Score::ZERO;
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some((possible_move.from(), possible_move.to()));
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push((possible_move.from(), possible_move.to()));
+                pv.append(&mut child_pv);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound: Bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(Entry::new(key, depth, best, bound, best_move, ply));
+
+        best
+    }
+
+    /// Only captures are searched, until the position is quiet
+    fn quiescence(&mut self, chess_game: &mut ChessEngine, ply: usize, mut alpha: Score, beta: Score) -> Score {
+        if !self.control.visit(ply) {
+            return Score::ZERO;
+        }
+
+        match chess_game.result() {
+            Result::Checkmate => return Score::mated_in(ply),
+            Result::Draw | Result::Stalemate => return Score::ZERO,
+            Result::None => {}
+        }
+
+        let stand_pat: Score = evaluate(chess_game).relative(chess_game.current_player());
+        if stand_pat >= beta || ply >= MAX_PLY {
+            return stand_pat;
+        }
+        if stand_pat > alpha {
+            alpha = stand_pat;
+        }
+
+        for capture in ordered_moves(chess_game).into_iter().filter(|m| !is_quiet(m)) {
+            if self.pruning.bad_captures && chess_game.board().see(&capture) < 0 {
+                continue;
+            }
+            chess_game.try_move(Some(capture.from()), Some(capture.to()));
+            let score: Score = -self.quiescence(chess_game, ply + 1, -beta, -alpha);
+            chess_game.undo_move();
+
+            if self.control.aborted() {
+                return Score::ZERO;
+            }
+            if score >= beta {
+                return score;
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        alpha
+    }
+}
+
+impl Default for AlphaBetaBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bot for AlphaBetaBot {
+    fn name(&self) -> &str {
+        "alpha-beta"
+    }
+
+    fn new_game(&mut self) {
+        self.table.clear();
+    }
+
+    fn set_hash_size(&mut self, megabytes: usize) {
+        self.table = Arc::new(TranspositionTable::new(megabytes));
+    }
+
+    fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    fn set_tablebase(&mut self, tablebase: Arc<Tablebase>) {
+        self.tablebase = Some(tablebase);
+    }
+
+    /// Iterative deepening, `on_iteration` is called with the result of every
+    /// depth completed by the main thread
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut chess_game: ChessEngine = chess_game.clone();
+        self.control = SearchControl::new(&limits);
+        // The tables know better than any search
+        let known = |tablebase: &Arc<Tablebase>| {
+            let (best_move, _) = tablebase.best_move(&chess_game)?;
+            Some((best_move, tablebase_score(tablebase, &chess_game, 0)?))
+        };
+        if let Some((best_move, score)) = self.tablebase.as_ref().and_then(known) {
+            let result: SearchResult = self.control.result(score, vec![best_move], 1);
+            on_iteration(&result);
+            return result;
+        }
+        if self.threads <= 1 {
+            return self.deepen(&mut chess_game, 1, limits.max_depth(), on_iteration);
+        }
+
+        let helpers_stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        thread::scope(|scope| {
+            for index in 1..self.threads {
+                let mut helper: Self = Self {
+                    pruning: self.pruning,
+                    control: self.control.helper(Arc::clone(&helpers_stop)),
+                    table: Arc::clone(&self.table),
+                    threads: 1,
+                    tablebase: self.tablebase.clone(),
+                };
+                // Only the board is copied, the history is shared
+                let mut chess_game: ChessEngine = chess_game.fork();
+                // Half of the helpers are one ply ahead of the main thread
+                let first_depth: i16 = 1 + (index % 2) as i16;
+
+                scope.spawn(move || helper.deepen(&mut chess_game, first_depth, MAX_DEPTH, &mut |_| {}));
+            }
+
+            let result: SearchResult = self.deepen(&mut chess_game, 1, limits.max_depth(), on_iteration);
+            helpers_stop.store(true, Ordering::Relaxed);
+            result
+        })
+    }
+}
+
+/// Legal moves, captures first (most valuable victim, then least valuable
+/// attacker). Ties are broken by coordinates to keep the search
+/// deterministic
+pub(super) fn ordered_moves(chess_game: &ChessEngine) -> Vec<Move> {
+    let mut moves: Vec<Move> = chess_game
+        .possible_moves()
+        .values()
+        .flatten()
+        .cloned()
+        .collect();
+
+    moves.sort_by_key(|m| {
+        let attacker: i32 = chess_game
+            .board()
+            .piece(m.from(), Color::Any)
+            .map_or(0, |piece| piece_value(piece).mg());
+        let order: i32 = captured(m).map_or(0, |victim| 10 * piece_value(&victim).mg() - attacker + 1);
+
+        (-order, m.from().row(), m.from().column(), m.to().row(), m.to().column())
+    });
+
+    moves
+}
+
+/// Moves `best` to the front, the rest keeps its order
+fn move_first(moves: &mut [Move], best: Option<(Position, Position)>) {
+    if let Some(index) = moves.iter().position(|m| Some((m.from(), m.to())) == best) {
+        moves[..=index].rotate_right(1);
+    }
+}
+
+/// The score of the side to move in the tablebase: the mates of the DTM
+/// tables, the wins of the WDL ones. The engine has no fifty-move rule to
+/// spoil the cursed wins
+fn tablebase_score(tablebase: &Tablebase, chess_game: &ChessEngine, ply: usize) -> Option<Score> {
+    if let Some(dtm) = tablebase.probe_dtm(chess_game) {
+        return Some(match dtm {
+            Dtm::Win(plies) => Score::mate_in(ply + plies),
+            Dtm::Draw => Score::ZERO,
+            Dtm::Loss(plies) => Score::mated_in(ply + plies),
+        });
+    }
+
+    tablebase.probe_wdl(chess_game).map(|wdl| match wdl {
+        Wdl::Win | Wdl::CursedWin => Score::tablebase_win_in(ply),
+        Wdl::Draw => Score::ZERO,
+        Wdl::BlessedLoss | Wdl::Loss => -Score::tablebase_win_in(ply),
+    })
+}
+
+fn has_non_pawn_material(chess_game: &ChessEngine, color: Color) -> bool {
+    chess_game
+        .board()
+        .pieces(color)
+        .iter()
+        .any(|piece| !matches!(piece, PieceKind::King(_) | PieceKind::Pawn(_)))
+}
+
+pub(super) fn captured(possible_move: &Move) -> Option<PieceKind> {
+    match possible_move.kind() {
+        MoveKind::Attack(attacked) => attacked,
+        MoveKind::EnPassant(attacked) => Some(attacked),
+        _ => None,
+    }
+}
+
+fn is_quiet(possible_move: &Move) -> bool {
+    captured(possible_move).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::Bot;
+    use crate::bot::score::Score;
+    use crate::bot::search::{SearchLimits, SearchResult};
+    use crate::game::ChessEngine;
+    use crate::game::board::Board;
+    use crate::game::board::board_builder::BoardBuilder;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+    use crate::game::pieces::Piece;
+    use crate::game::pieces::king::King;
+    use crate::game::pieces::pawn::Pawn;
+    use crate::game::pieces::piece_kind::PieceKind;
+    use crate::game::pieces::rook::Rook;
+
+    use super::{AlphaBetaBot, Pruning};
+
+    #[test]
+    fn test_alpha_beta_checkmate() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((7isize, 4isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 7isize).into(), Color::Black)))
+            .build();
+        let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+        let expected: (Position, Position) = ((7isize, 4isize).into(), (0isize, 4isize).into());
+
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(3)).best_move();
+
+        assert_eq!(Some(expected), predicted_move);
+    }
+
+    #[rstest]
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", "e1", "e8")]
+    #[case("r3k3/8/8/1N6/8/8/8/7K w - - 0 1", "b5", "c7")]
+    #[case("6k1/6p1/8/3q4/8/8/8/3R2K1 w - - 0 1", "d1", "d5")]
+    #[case("6k1/5ppp/8/8/3n4/8/5PPP/3R2K1 w - - 0 1", "d1", "d4")]
+    #[case("3r2k1/5ppp/8/8/8/8/3Q1PPP/6K1 b - - 0 1", "d8", "d2")]
+    fn test_tactics(
+        #[case]
+        fen: &str,
+        #[case]
+        from: &str,
+        #[case]
+        to: &str,
+        #[values(1, 3)]
+        threads: usize
+    ) -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+        bot.set_threads(threads);
+        let expected: Option<(Position, Position)> = Position::from_notation(from).zip(Position::from_notation(to));
+
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(3)).best_move();
+
+        assert_eq!(expected, predicted_move);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pruning_visits_fewer_nodes() -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/5ppp/8/8/3n4/8/5PPP/3R2K1 w - - 0 1")?;
+        let mut full_width: AlphaBetaBot = AlphaBetaBot::new().with_pruning(Pruning::NONE);
+        let mut selective: AlphaBetaBot = AlphaBetaBot::new();
+
+        let expected: SearchResult = full_width.choose_move(&chess_game, SearchLimits::depth(3));
+        let predicted: SearchResult = selective.choose_move(&chess_game, SearchLimits::depth(3));
+
+        assert_eq!(expected.best_move(), predicted.best_move());
+        assert!(
+            predicted.nodes() < expected.nodes(),
+            "selective: {} nodes, full width: {} nodes",
+            predicted.nodes(),
+            expected.nodes(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_reports_mate_and_pv() -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1")?;
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+        let mut iterations: Vec<SearchResult> = Vec::new();
+
+        let result: SearchResult = bot.search(&chess_game, SearchLimits::depth(3), &mut |info| iterations.push(info.clone()));
+
+        assert_eq!(Score::mate_in(1), result.score());
+        assert_eq!("#1", result.score().to_string());
+        assert_eq!(vec![(Position::from_notation("e1").unwrap(), Position::from_notation("e8").unwrap())], result.pv());
+        assert_eq!(Some(&result), iterations.last());
+        assert!(result.seldepth() >= result.depth() as usize);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_iterations() -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/5ppp/8/8/3n4/8/5PPP/3R2K1 w - - 0 1")?;
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+        let mut depths: Vec<i16> = Vec::new();
+
+        let result: SearchResult = bot.search(&chess_game, SearchLimits::depth(3), &mut |info| depths.push(info.depth()));
+
+        assert_eq!(vec![1, 2, 3], depths);
+        assert_eq!(3, result.pv().len());
+        assert_eq!(result.best_move(), Some((Position::from_notation("d1").unwrap(), Position::from_notation("d4").unwrap())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_movetime() {
+        let chess_game: ChessEngine = ChessEngine::new();
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+
+        let result: SearchResult = bot.choose_move(&chess_game, SearchLimits::movetime(Duration::from_millis(50)));
+
+        assert!(result.best_move().is_some());
+        assert!(result.elapsed() < Duration::from_secs(2), "{result}");
+    }
+}
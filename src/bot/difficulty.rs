@@ -0,0 +1,251 @@
+use std::fmt::{self, Display};
+
+use crate::game::ChessEngine;
+
+use super::alpha_beta_bot::{AlphaBetaBot, ScoredMove};
+use super::Bot;
+use super::rng::Rng;
+use super::score::Score;
+use super::search::{SearchLimits, SearchResult};
+
+/// How strong the bot plays. The Elo ratings are rough estimates, to give
+/// an idea rather than a calibrated rating
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Difficulty {
+    name: &'static str,
+    elo: u32,
+    depth: Option<i16>,
+    nodes: Option<u64>,
+    // The root scores are shifted by up to this many centipawns
+    noise: i32,
+    // In centipawns, the higher it is the more often a worse move is played
+    temperature: f64,
+}
+
+impl Difficulty {
+    const fn new(name: &'static str, elo: u32, depth: Option<i16>, nodes: Option<u64>, noise: i32, temperature: f64) -> Self {
+        Self {
+            name,
+            elo,
+            depth,
+            nodes,
+            noise,
+            temperature,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        LEVELS.into_iter().find(|level| level.name == name)
+    }
+
+    /// The strongest level not above `elo`, the weakest one when they all are
+    pub(crate) fn from_elo(elo: u32) -> Self {
+        LEVELS
+            .into_iter()
+            .rev()
+            .find(|level| level.elo <= elo)
+            .unwrap_or(LEVELS[0])
+    }
+
+    pub(crate) const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub(crate) const fn elo(&self) -> u32 {
+        self.elo
+    }
+
+    /// The next level, back to the weakest after the strongest
+    pub(crate) fn next(&self) -> Self {
+        let index: usize = LEVELS.iter().position(|level| level == self).unwrap_or(0);
+
+        LEVELS[(index + 1) % LEVELS.len()]
+    }
+
+    /// The full strength has no limit, it is the plain alpha-beta bot
+    pub(crate) fn bot(self) -> Box<dyn Bot> {
+        if self == EXPERT {
+            Box::new(AlphaBetaBot::new())
+        } else {
+            Box::new(LimitedBot::new(self, Rng::from_time()))
+        }
+    }
+
+    fn limit(&self, mut limits: SearchLimits) -> SearchLimits {
+        if let Some(depth) = self.depth {
+            let depth: i16 = limits.max_depth().min(depth);
+            limits = limits.with_depth(depth);
+        }
+        if let Some(nodes) = self.nodes {
+            let nodes: u64 = limits.max_nodes().map_or(nodes, |max_nodes| max_nodes.min(nodes));
+            limits = limits.with_nodes(nodes);
+        }
+
+        limits
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (~{} Elo)", self.name, self.elo)
+    }
+}
+
+const EXPERT: Difficulty = Difficulty::new("expert", 2000, None, None, 0, 0.0);
+
+/// The levels, weakest first
+pub(crate) const LEVELS: [Difficulty; 5] = [
+    Difficulty::new("beginner", 800, Some(1), Some(200), 200, 150.0),
+    Difficulty::new("novice", 1100, Some(2), Some(1_000), 100, 75.0),
+    Difficulty::new("intermediate", 1400, Some(2), Some(4_000), 50, 35.0),
+    Difficulty::new("advanced", 1700, Some(3), Some(15_000), 20, 12.0),
+    EXPERT,
+];
+
+/// Scores every root move within the limits of its level, blurs the scores
+/// with noise, and picks a move at random, the better ones being more likely
+/// (softmax over the scores)
+pub(crate) struct LimitedBot {
+    difficulty: Difficulty,
+    search: AlphaBetaBot,
+    rng: Rng,
+}
+
+impl LimitedBot {
+    pub(crate) fn new(difficulty: Difficulty, rng: Rng) -> Self {
+        Self {
+            difficulty,
+            search: AlphaBetaBot::new(),
+            rng,
+        }
+    }
+
+    fn pick(&mut self, scored: &[ScoredMove]) -> Option<ScoredMove> {
+        let noise: i32 = self.difficulty.noise;
+        let blurred: Vec<ScoredMove> = scored
+            .iter()
+            .map(|&(scored_move, score)| {
+                let shift: i32 = self.rng.below(2 * noise as usize + 1) as i32 - noise;
+                (scored_move, score + Score::centipawns(shift))
+            })
+            .collect();
+
+        let best: Score = blurred.iter().map(|(_, score)| *score).max()?;
+        if self.difficulty.temperature <= 0.0 {
+            return blurred.into_iter().find(|(_, score)| *score == best);
+        }
+
+        let weights: Vec<f64> = blurred
+            .iter()
+            .map(|(_, score)| (f64::from((*score - best).value()) / self.difficulty.temperature).exp())
+            .collect();
+        let mut remaining: f64 = self.rng.unit() * weights.iter().sum::<f64>();
+        for (index, weight) in weights.iter().enumerate() {
+            remaining -= weight;
+            if remaining < 0.0 {
+                return Some(blurred[index]);
+            }
+        }
+
+        blurred.last().copied()
+    }
+}
+
+impl Bot for LimitedBot {
+    fn name(&self) -> &str {
+        self.difficulty.name
+    }
+
+    fn new_game(&mut self) {
+        self.search.new_game();
+    }
+
+    fn set_hash_size(&mut self, megabytes: usize) {
+        self.search.set_hash_size(megabytes);
+    }
+
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let (scored, best) = self.search.score_root_moves(chess_game, self.difficulty.limit(limits));
+
+        let result: SearchResult = match self.pick(&scored) {
+            Some((picked, score)) => {
+                SearchResult::new(score, vec![picked], best.depth(), best.seldepth(), best.nodes(), best.elapsed())
+            }
+            None => best,
+        };
+        on_iteration(&result);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::Bot;
+    use crate::bot::rng::Rng;
+    use crate::bot::search::{SearchLimits, SearchResult};
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::{Difficulty, LimitedBot, LEVELS};
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(Some("novice"), Difficulty::from_name("novice").map(|level| level.name()));
+        assert_eq!(None, Difficulty::from_name("grandmaster"));
+        assert_eq!("beginner", Difficulty::from_elo(100).name());
+        assert_eq!("intermediate", Difficulty::from_elo(1500).name());
+        assert_eq!("expert", Difficulty::from_elo(3000).name());
+        assert_eq!("beginner", LEVELS[LEVELS.len() - 1].next().name());
+        assert!(LEVELS.windows(2).all(|pair| pair[0].elo() < pair[1].elo()));
+        assert_eq!("advanced (~1700 Elo)", LEVELS[3].to_string());
+    }
+
+    #[test]
+    fn test_limits() {
+        let beginner: Difficulty = LEVELS[0];
+
+        let limits: SearchLimits = beginner.limit(SearchLimits::depth(5).with_nodes(50));
+
+        assert_eq!(1, limits.max_depth());
+        assert_eq!(Some(50), limits.max_nodes());
+    }
+
+    #[test]
+    fn test_weak_levels_vary() -> Result<()> {
+        let chess_game: ChessEngine = ChessEngine::new();
+        let mut bot: LimitedBot = LimitedBot::new(LEVELS[0], Rng::new(3));
+        let mut moves: HashSet<(Position, Position)> = HashSet::new();
+
+        for _ in 0..10 {
+            let result: SearchResult = bot.choose_move(&chess_game, SearchLimits::default());
+            moves.extend(result.best_move());
+        }
+
+        assert!(moves.len() > 1, "{moves:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_weak_levels_still_take_a_queen() -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/6p1/8/3q4/8/8/8/3R2K1 w - - 0 1")?;
+        let expected: Option<(Position, Position)> = Position::from_notation("d1").zip(Position::from_notation("d5"));
+
+        for level in &LEVELS[1..3] {
+            let mut bot: LimitedBot = LimitedBot::new(*level, Rng::new(11));
+            assert_eq!(expected, bot.choose_move(&chess_game, SearchLimits::default()).best_move(), "{level}");
+        }
+        Ok(())
+    }
+}
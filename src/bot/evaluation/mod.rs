@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use trace::EvalTrace;
+
+use crate::game::{ChessEngine, Result};
+use crate::game::board::Board;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::board::{COLUMNS, ROWS};
+use crate::game::pieces::piece_kind::PieceKind;
+
+use super::score::Score;
+
+mod pst;
+pub(crate) mod trace;
+
+const MAX_PHASE: i32 = 24;
+const BISHOP_PAIR: Phased = Phased::new(30, 50);
+const DOUBLED_PAWN: Phased = Phased::new(-10, -20);
+const ISOLATED_PAWN: Phased = Phased::new(-10, -15);
+const PAWN_SHIELD: Phased = Phased::new(12, 0);
+const KING_ZONE_ATTACK: Phased = Phased::new(-8, -2);
+// Indexed by the rank of the pawn relative to its color (1 is the starting rank)
+const PASSED_PAWN: [Phased; ROWS] = [
+    Phased::new(0, 0),
+    Phased::new(5, 10),
+    Phased::new(5, 15),
+    Phased::new(10, 25),
+    Phased::new(20, 45),
+    Phased::new(35, 75),
+    Phased::new(60, 120),
+    Phased::new(0, 0),
+];
+
+/// A pair of midgame and endgame values, blended together according to the
+/// game phase
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Phased {
+    mg: i32,
+    eg: i32,
+}
+
+impl Phased {
+    pub(crate) const fn new(mg: i32, eg: i32) -> Self {
+        Self {
+            mg,
+            eg,
+        }
+    }
+
+    pub(crate) const fn mg(self) -> i32 {
+        self.mg
+    }
+
+    pub(crate) const fn eg(self) -> i32 {
+        self.eg
+    }
+
+    /// `phase` goes from `MAX_PHASE` (all the pieces on the board) to 0 (only
+    /// kings and pawns left)
+    pub(crate) const fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add<Self> for Phased {
+    type Output = Self;
+
+    fn add(self, value: Self) -> Self::Output {
+        Self::new(self.mg + value.mg, self.eg + value.eg)
+    }
+}
+
+impl AddAssign<Self> for Phased {
+    fn add_assign(&mut self, value: Self) {
+        *self = *self + value;
+    }
+}
+
+impl Sub<Self> for Phased {
+    type Output = Self;
+
+    fn sub(self, value: Self) -> Self::Output {
+        Self::new(self.mg - value.mg, self.eg - value.eg)
+    }
+}
+
+impl Neg for Phased {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Phased {
+    type Output = Self;
+
+    fn mul(self, value: i32) -> Self::Output {
+        Self::new(self.mg * value, self.eg * value)
+    }
+}
+
+/// Static evaluation of the position, from white's point of view
+pub(crate) fn evaluate(chess_game: &ChessEngine) -> Score {
+    match chess_game.result() {
+        Result::Checkmate => return (-Score::MATE).relative(chess_game.current_player()),
+        Result::Stalemate | Result::Draw => return Score::ZERO,
+        Result::None => {}
+    }
+
+    EvalTrace::new(chess_game.board()).score()
+}
+
+/// The terms summed up by the evaluation, each computed for one color
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Term {
+    Material,
+    PieceSquares,
+    Mobility,
+    PawnStructure,
+    KingSafety,
+    BishopPair,
+}
+
+impl Term {
+    pub(crate) const fn values() -> [Self; 6] {
+        [Self::Material, Self::PieceSquares, Self::Mobility, Self::PawnStructure, Self::KingSafety, Self::BishopPair]
+    }
+
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Material => "Material",
+            Self::PieceSquares => "Piece-square tables",
+            Self::Mobility => "Mobility",
+            Self::PawnStructure => "Pawn structure",
+            Self::KingSafety => "King safety",
+            Self::BishopPair => "Bishop pair",
+        }
+    }
+
+    fn compute(self, board: &Board, color: Color) -> Phased {
+        match self {
+            Self::Material => material(board, color),
+            Self::PieceSquares => piece_squares(board, color),
+            Self::Mobility => mobility(board, color),
+            Self::PawnStructure => pawn_structure(board, color),
+            Self::KingSafety => king_safety(board, color),
+            Self::BishopPair => bishop_pair(board, color),
+        }
+    }
+}
+
+pub(crate) fn phase(board: &Board) -> i32 {
+    let phase: i32 = [Color::White, Color::Black]
+        .into_iter()
+        .flat_map(|color| board.pieces(color))
+        .map(|piece| match piece {
+            PieceKind::Knight(_) | PieceKind::Bishop(_) => 1,
+            PieceKind::Rook(_) => 2,
+            PieceKind::Queen(_) => 4,
+            PieceKind::King(_) | PieceKind::Pawn(_) => 0,
+        })
+        .sum();
+
+    phase.min(MAX_PHASE)
+}
+
+pub(crate) const fn piece_value(piece: &PieceKind) -> Phased {
+    match piece {
+        PieceKind::Bishop(_) => Phased::new(365, 297),
+        PieceKind::King(_) => Phased::new(0, 0),
+        PieceKind::Knight(_) => Phased::new(337, 281),
+        PieceKind::Pawn(_) => Phased::new(82, 94),
+        PieceKind::Queen(_) => Phased::new(1025, 936),
+        PieceKind::Rook(_) => Phased::new(477, 512),
+    }
+}
+
+fn material(board: &Board, color: Color) -> Phased {
+    board
+        .pieces(color)
+        .into_iter()
+        .fold(Phased::default(), |total, piece| total + piece_value(piece))
+}
+
+fn piece_squares(board: &Board, color: Color) -> Phased {
+    board
+        .pieces(color)
+        .into_iter()
+        .fold(Phased::default(), |total, piece| total + pst::value(piece))
+}
+
+fn mobility(board: &Board, color: Color) -> Phased {
+    let mut moves_count: HashMap<Position, i32> = HashMap::new();
+    for possible_move in board.possible_moves(color) {
+        *moves_count.entry(possible_move.from()).or_default() += 1;
+    }
+
+    board
+        .pieces(color)
+        .into_iter()
+        .map(|piece| {
+            let count: i32 = moves_count.get(&piece.position()).copied().unwrap_or(0);
+
+            // Bonus per move above (or penalty below) an average mobility
+            match piece {
+                PieceKind::Bishop(_) => Phased::new(5, 5) * (count - 6),
+                PieceKind::Knight(_) => Phased::new(4, 4) * (count - 4),
+                PieceKind::Queen(_) => Phased::new(1, 2) * (count - 13),
+                PieceKind::Rook(_) => Phased::new(2, 4) * (count - 7),
+                PieceKind::King(_) | PieceKind::Pawn(_) => Phased::default(),
+            }
+        })
+        .fold(Phased::default(), Add::add)
+}
+
+fn pawns(board: &Board, color: Color) -> Vec<Position> {
+    board
+        .pieces(color)
+        .into_iter()
+        .filter(|piece| matches!(piece, PieceKind::Pawn(_)))
+        .map(|piece| piece.position())
+        .collect()
+}
+
+fn relative_rank(position: Position, color: Color) -> usize {
+    match color {
+        Color::White => ROWS - 1 - position.row(),
+        Color::Black => position.row(),
+        Color::Any => panic!("A position can not be relative to color \"Any\""),
+    }
+}
+
+fn pawn_structure(board: &Board, color: Color) -> Phased {
+    let own_pawns: Vec<Position> = pawns(board, color);
+    let other_pawns: Vec<Position> = pawns(board, color.other());
+    let mut files: [i32; COLUMNS] = [0; COLUMNS];
+    let mut total: Phased = Phased::default();
+
+    for pawn in &own_pawns {
+        files[pawn.column()] += 1;
+    }
+
+    for count in files {
+        if count > 1 {
+            total += DOUBLED_PAWN * (count - 1);
+        }
+    }
+
+    for pawn in &own_pawns {
+        let column: usize = pawn.column();
+        let left_empty: bool = column == 0 || files[column - 1] == 0;
+        let right_empty: bool = column == COLUMNS - 1 || files[column + 1] == 0;
+
+        if left_empty && right_empty {
+            total += ISOLATED_PAWN;
+        }
+
+        let blocked: bool = other_pawns
+            .iter()
+            .filter(|other| other.column().abs_diff(column) <= 1)
+            .any(|other| relative_rank(*other, color) > relative_rank(*pawn, color));
+
+        if !blocked {
+            total += PASSED_PAWN[relative_rank(*pawn, color)];
+        }
+    }
+
+    total
+}
+
+fn king_safety(board: &Board, color: Color) -> Phased {
+    let Some(king_position) = board.king_position(color) else {
+        return Phased::default();
+    };
+    let forward: isize = match color {
+        Color::White => -1isize,
+        Color::Black => 1isize,
+        Color::Any => panic!("A king of color \"Any\" has no forward direction"),
+    };
+    let mut total: Phased = Phased::default();
+
+    for row_offset in 1..=2isize {
+        for column_offset in -1..=1isize {
+            let position: Position = king_position + (forward * row_offset, column_offset);
+
+            if matches!(board.piece(position, color), Some(PieceKind::Pawn(_))) {
+                total += PAWN_SHIELD;
+            }
+        }
+    }
+
+    let zone_attacks: usize = board
+        .possible_moves(color.other())
+        .iter()
+        .filter(|m| {
+            m.to().row().abs_diff(king_position.row()) <= 1
+                && m.to().column().abs_diff(king_position.column()) <= 1
+        })
+        .count();
+
+    total + KING_ZONE_ATTACK * zone_attacks as i32
+}
+
+fn bishop_pair(board: &Board, color: Color) -> Phased {
+    let bishops: usize = board
+        .pieces(color)
+        .into_iter()
+        .filter(|piece| matches!(piece, PieceKind::Bishop(_)))
+        .count();
+
+    if bishops >= 2 {
+        BISHOP_PAIR
+    } else {
+        Phased::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::score::Score;
+    use crate::game::ChessEngine;
+    use crate::game::board::Board;
+    use crate::game::board::board_builder::BoardBuilder;
+    use crate::game::board::color::Color;
+    use crate::game::pieces::Piece;
+    use crate::game::pieces::bishop::Bishop;
+    use crate::game::pieces::king::King;
+    use crate::game::pieces::knight::Knight;
+    use crate::game::pieces::pawn::Pawn;
+    use crate::game::pieces::piece_kind::PieceKind;
+    use crate::game::pieces::rook::Rook;
+
+    use super::{bishop_pair, evaluate, pawn_structure, Phased, BISHOP_PAIR, DOUBLED_PAWN, ISOLATED_PAWN, PASSED_PAWN};
+
+    #[test]
+    fn test_evaluate_starting_position() {
+        let chess_game: ChessEngine = ChessEngine::new();
+
+        assert_eq!(Score::ZERO, evaluate(&chess_game));
+    }
+
+    #[test]
+    fn test_evaluate_mirrored_position() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((4isize, 3isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((6isize, 5isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 2isize).into(), Color::Black)))
+            .with(PieceKind::Knight(Knight::new((2isize, 2isize).into(), Color::Black)))
+            .build();
+        let mirrored: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((3isize, 3isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((7isize, 2isize).into(), Color::White)))
+            .with(PieceKind::Knight(Knight::new((5isize, 2isize).into(), Color::White)))
+            .build();
+
+        let score: Score = evaluate(&ChessEngine::from_board(board, Color::White));
+        let mirrored_score: Score = evaluate(&ChessEngine::from_board(mirrored, Color::Black));
+
+        assert_eq!(score, -mirrored_score);
+        assert!(score > Score::ZERO, "a rook and a pawn should beat a knight: {score}");
+    }
+
+    #[test]
+    fn test_evaluate_checkmate() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((0isize, 3isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((0isize, 7isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((1isize, 0isize).into(), Color::White)))
+            .build();
+        let chess_game: ChessEngine = ChessEngine::from_board(board, Color::Black);
+
+        assert_eq!(Score::MATE, evaluate(&chess_game));
+    }
+
+    #[test]
+    fn test_pawn_structure_doubled_isolated() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::Pawn(Pawn::new((6isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((5isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 1isize).into(), Color::Black)))
+            .build();
+        let expected: Phased = DOUBLED_PAWN + ISOLATED_PAWN * 2;
+
+        assert_eq!(expected, pawn_structure(&board, Color::White));
+    }
+
+    #[test]
+    fn test_pawn_structure_passed() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::Pawn(Pawn::new((2isize, 4isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 5isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
+            .build();
+        let expected: Phased = PASSED_PAWN[5];
+
+        assert_eq!(expected, pawn_structure(&board, Color::White));
+    }
+
+    #[test]
+    fn test_bishop_pair() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::Bishop(Bishop::new((7isize, 2isize).into(), Color::White)))
+            .with(PieceKind::Bishop(Bishop::new((7isize, 5isize).into(), Color::White)))
+            .with(PieceKind::Bishop(Bishop::new((0isize, 2isize).into(), Color::Black)))
+            .build();
+
+        assert_eq!(BISHOP_PAIR, bishop_pair(&board, Color::White));
+        assert_eq!(Phased::default(), bishop_pair(&board, Color::Black));
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt::{self, Display};
+
+use crate::bot::score::Score;
+use crate::game::board::Board;
+use crate::game::board::color::Color;
+
+use super::{phase, Phased, Term, MAX_PHASE};
+
+/// Breakdown of the static evaluation: every term, for both colors, before
+/// the midgame and endgame values are blended
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct EvalTrace {
+    terms: Vec<(Term, Phased, Phased)>,
+    phase: i32,
+}
+
+impl EvalTrace {
+    pub(crate) fn new(board: &Board) -> Self {
+        let terms: Vec<(Term, Phased, Phased)> = Term::values()
+            .into_iter()
+            .map(|term| (term, term.compute(board, Color::White), term.compute(board, Color::Black)))
+            .collect();
+
+        Self {
+            terms,
+            phase: phase(board),
+        }
+    }
+
+    pub(crate) const fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// The sum of all the terms, from white's point of view
+    pub(crate) fn total(&self) -> Phased {
+        self
+            .terms
+            .iter()
+            .fold(Phased::default(), |total, (_, white, black)| total + *white - *black)
+    }
+
+    pub(crate) fn score(&self) -> Score {
+        Score::centipawns(self.total().taper(self.phase))
+    }
+
+    pub(crate) fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!(
+            "{:<20}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}",
+            "Term", "W mg", "W eg", "B mg", "B eg", "mg", "eg",
+        ));
+
+        for (term, white, black) in &self.terms {
+            let difference: Phased = *white - *black;
+            lines.push(format!(
+                "{:<20}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}",
+                term.name(), white.mg(), white.eg(), black.mg(), black.eg(), difference.mg(), difference.eg(),
+            ));
+        }
+
+        let total: Phased = self.total();
+        lines.push(format!("{:<48}{:>7}{:>7}", "Total", total.mg(), total.eg()));
+        lines.push(format!("Phase: {}/{MAX_PHASE} (midgame weight)", self.phase()));
+        lines.push(format!("Score: {} (white's point of view)", self.score()));
+        lines
+    }
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+#[cfg(test)]
+impl EvalTrace {
+    pub(super) fn term(&self, term: Term, color: Color) -> Phased {
+        let (_, white, black) = self
+            .terms
+            .iter()
+            .find(|(t, _, _)| *t == term)
+            .expect("Every term should be traced");
+
+        match color {
+            Color::White => *white,
+            Color::Black => *black,
+            Color::Any => panic!("No term can be associated with color \"Any\""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::evaluation::{evaluate, Phased, Term, MAX_PHASE};
+    use crate::game::ChessEngine;
+    use crate::game::board::Board;
+    use crate::game::board::board_builder::BoardBuilder;
+    use crate::game::board::color::Color;
+    use crate::game::pieces::Piece;
+    use crate::game::pieces::king::King;
+    use crate::game::pieces::pawn::Pawn;
+    use crate::game::pieces::piece_kind::PieceKind;
+    use crate::game::pieces::queen::Queen;
+
+    use super::EvalTrace;
+
+    #[test]
+    fn test_trace_matches_evaluate() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Queen(Queen::new((4isize, 3isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 2isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 2isize).into(), Color::Black)))
+            .build();
+        let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
+
+        let trace: EvalTrace = EvalTrace::new(chess_game.board());
+
+        assert_eq!(evaluate(&chess_game), trace.score());
+    }
+
+    #[test]
+    fn test_trace_starting_position() {
+        let chess_game: ChessEngine = ChessEngine::new();
+
+        let trace: EvalTrace = EvalTrace::new(chess_game.board());
+
+        assert_eq!(MAX_PHASE, trace.phase());
+        assert_eq!(Phased::default(), trace.total());
+        for term in Term::values() {
+            assert_eq!(trace.term(term, Color::White), trace.term(term, Color::Black), "{term:?}");
+        }
+    }
+
+    #[test]
+    fn test_trace_lines() {
+        let chess_game: ChessEngine = ChessEngine::new();
+
+        let lines: Vec<String> = EvalTrace::new(chess_game.board()).lines();
+
+        assert_eq!(Term::values().len() + 4, lines.len());
+        assert!(lines[1].starts_with("Material"));
+        assert_eq!("Score: +0.00 (white's point of view)", lines[lines.len() - 1]);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::game::ChessEngine;
+use crate::game::board::position::Position;
+
+use super::Bot;
+use super::alpha_beta_bot::ordered_moves;
+use super::evaluation::evaluate;
+use super::score::Score;
+use super::search::{SearchControl, SearchLimits, SearchResult};
+
+/// Looks one move ahead: grabs whatever material it can, and never sees the
+/// recapture coming
+pub(crate) struct GreedyBot;
+
+impl Bot for GreedyBot {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut control: SearchControl = SearchControl::new(&limits);
+        let mut chess_game: ChessEngine = chess_game.clone();
+        let mut best: (Score, Vec<(Position, Position)>) = (-Score::INFINITY, Vec::new());
+
+        control.visit(0);
+        for possible_move in ordered_moves(&chess_game) {
+            let (from, to): (Position, Position) = (possible_move.from(), possible_move.to());
+            let current_player = chess_game.current_player();
+
+            control.visit(1);
+            chess_game.try_move(Some(from), Some(to));
+            let score: Score = evaluate(&chess_game).relative(current_player);
+            chess_game.undo_move();
+
+            if score > best.0 {
+                best = (score, vec![(from, to)]);
+            }
+        }
+
+        let result: SearchResult = control.result(best.0, best.1, 1);
+        on_iteration(&result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::Bot;
+    use crate::bot::search::SearchLimits;
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::GreedyBot;
+
+    #[test]
+    fn test_greedy_takes_the_queen() -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/6p1/8/3q4/8/8/8/3R2K1 w - - 0 1")?;
+
+        let predicted_move: Option<(Position, Position)> = GreedyBot
+            .choose_move(&chess_game, SearchLimits::default())
+            .best_move();
+
+        assert_eq!(Position::from_notation("d1").zip(Position::from_notation("d5")), predicted_move);
+        Ok(())
+    }
+}
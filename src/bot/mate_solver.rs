@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::game::{ChessEngine, Result};
+use crate::game::board::move_struct::Move;
+use crate::game::board::position::Position;
+
+use super::alpha_beta_bot::ordered_moves;
+
+/// Proves or refutes forced mates with an exhaustive search, made bearable
+/// by trying checks first and by remembering the positions already solved.
+/// Unlike the bots it doesn't stop at the first solution: a composition with
+/// more than one key move is cooked
+pub(crate) struct MateSolver {
+    // Whether the side to move mates in the given number of moves
+    solved: HashMap<(u64, usize), bool>,
+    nodes: u64,
+}
+
+impl MateSolver {
+    pub(crate) fn new() -> Self {
+        Self {
+            solved: HashMap::new(),
+            nodes: 0,
+        }
+    }
+
+    pub(crate) const fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Every first move forcing mate in `moves` moves or fewer, with the
+    /// number of moves of its fastest mate, fastest first
+    pub(crate) fn key_moves(&mut self, chess_game: &ChessEngine, moves: usize) -> Vec<((Position, Position), usize)> {
+        let mut chess_game: ChessEngine = chess_game.clone();
+        let mut keys: Vec<((Position, Position), usize)> = Vec::new();
+
+        for (from, to) in self.checks_first(&mut chess_game) {
+            chess_game.try_move(Some(from), Some(to));
+            if let Some(length) = (1..=moves).find(|&length| self.defender_is_mated(&mut chess_game, length - 1)) {
+                keys.push(((from, to), length));
+            }
+            chess_game.undo_move();
+        }
+
+        keys.sort_by_key(|((from, to), length)| (*length, from.to_notation(), to.to_notation()));
+        keys
+    }
+
+    /// The side to move mates in `moves` moves at most
+    fn mates(&mut self, chess_game: &mut ChessEngine, moves: usize) -> bool {
+        self.nodes += 1;
+        if moves == 0 || chess_game.is_end() {
+            return false;
+        }
+
+        let key: (u64, usize) = (chess_game.key(), moves);
+        if let Some(&mates) = self.solved.get(&key) {
+            return mates;
+        }
+
+        let mates: bool = if moves == 1 {
+            // The last move has to be a checkmate, no need to order anything
+            ordered_moves(chess_game).iter().any(|m| {
+                chess_game.try_move(Some(m.from()), Some(m.to()));
+                let checkmate: bool = chess_game.result() == Result::Checkmate;
+                chess_game.undo_move();
+                checkmate
+            })
+        } else {
+            self.checks_first(chess_game).into_iter().any(|(from, to)| {
+                chess_game.try_move(Some(from), Some(to));
+                let mates: bool = self.defender_is_mated(chess_game, moves - 1);
+                chess_game.undo_move();
+                mates
+            })
+        };
+
+        self.solved.insert(key, mates);
+        mates
+    }
+
+    /// After the attacker's move: every reply of the defender is mated in
+    /// `moves` moves at most
+    fn defender_is_mated(&mut self, chess_game: &mut ChessEngine, moves: usize) -> bool {
+        self.nodes += 1;
+        match chess_game.result() {
+            Result::Checkmate => return true,
+            Result::Stalemate | Result::Draw => return false,
+            Result::None => {}
+        }
+        if moves == 0 {
+            return false;
+        }
+
+        ordered_moves(chess_game).iter().all(|m| {
+            chess_game.try_move(Some(m.from()), Some(m.to()));
+            let mated: bool = self.mates(chess_game, moves);
+            chess_game.undo_move();
+            mated
+        })
+    }
+
+    /// The legal moves, the ones giving check first
+    fn checks_first(&mut self, chess_game: &mut ChessEngine) -> Vec<(Position, Position)> {
+        let (checks, others): (Vec<Move>, Vec<Move>) = ordered_moves(chess_game).into_iter().partition(|m| {
+            chess_game.try_move(Some(m.from()), Some(m.to()));
+            let check: bool = chess_game.checked_king().is_some();
+            chess_game.undo_move();
+            check
+        });
+
+        checks
+            .iter()
+            .chain(&others)
+            .map(|m| (m.from(), m.to()))
+            .collect()
+    }
+}
+
+impl Default for MateSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::MateSolver;
+
+    fn notation(keys: &[((Position, Position), usize)]) -> Vec<(String, usize)> {
+        keys
+            .iter()
+            .map(|((from, to), length)| (format!("{}{}", from.to_notation(), to.to_notation()), *length))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", 1, vec![("e1e8", 1)])]
+    #[case("6k1/5ppp/8/8/8/8/8/R3R1K1 w - - 0 1", 1, vec![("a1a8", 1), ("e1e8", 1)])]
+    #[case("3r2k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1", 1, vec![("d8d1", 1)])]
+    #[case("k7/8/2K5/8/8/8/8/7R w - - 0 1", 1, vec![])]
+    fn test_key_moves(
+        #[case]
+        fen: &str,
+        #[case]
+        moves: usize,
+        #[case]
+        expected: Vec<(&str, usize)>
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let keys: Vec<(String, usize)> = notation(&MateSolver::new().key_moves(&chess_game, moves));
+
+        let expected: Vec<(String, usize)> = expected.into_iter().map(|(key, length)| (key.to_string(), length)).collect();
+        assert_eq!(expected, keys);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cooked_mate_in_two() -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("k7/8/2K5/8/8/8/8/7R w - - 0 1")?;
+        let mut solver: MateSolver = MateSolver::new();
+
+        let keys: Vec<(String, usize)> = notation(&solver.key_moves(&chess_game, 2));
+
+        // Cooked: Kc7 Ka7 Ra1# works as well as Kb6 Kb8 Rh8#
+        assert_eq!([("c6b6".to_string(), 2), ("c6c7".to_string(), 2)], keys[..]);
+        assert!(solver.nodes() > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_mate_from_the_start() {
+        assert!(MateSolver::new().key_moves(&ChessEngine::new(), 1).is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::game::{ChessEngine, Result};
+use crate::game::board::move_struct::Move;
+use crate::game::board::position::Position;
+
+use super::alpha_beta_bot::{captured, ordered_moves};
+use super::Bot;
+use super::evaluation::evaluate;
+use super::rng::Rng;
+use super::score::Score;
+use super::search::{SearchControl, SearchLimits, SearchResult};
+
+/// Iterations when neither the limits nor `with_iterations` bound the search
+const DEFAULT_ITERATIONS: usize = 2_000;
+// Iterations between two reports of the best line
+const REPORT_INTERVAL: usize = 500;
+// Random moves are slow to decide a game: the playouts stop after this many
+// plies and the evaluation estimates the outcome
+const PLAYOUT_PLIES: usize = 8;
+// How often a playout plays its best capture instead of a random move
+const CAPTURE_BIAS: f64 = 0.5;
+// Balances exploring the moves seldom tried against exploiting the best ones
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+#[derive(Debug)]
+struct Node {
+    parent: Option<usize>,
+    played: Option<(Position, Position)>,
+    children: Vec<usize>,
+    untried: Vec<(Position, Position)>,
+    visits: u32,
+    // Summed outcomes, for the player who played the move leading here
+    wins: f64,
+}
+
+impl Node {
+    fn new(parent: Option<usize>, played: Option<(Position, Position)>, chess_game: &ChessEngine) -> Self {
+        // Popped from the back: the captures are expanded first
+        let mut untried: Vec<(Position, Position)> = ordered_moves(chess_game).iter().map(|m| (m.from(), m.to())).collect();
+        untried.reverse();
+
+        Self {
+            parent,
+            played,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    fn win_rate(&self) -> f64 {
+        if self.visits == 0 {
+            0.5
+        } else {
+            self.wins / f64::from(self.visits)
+        }
+    }
+}
+
+/// Monte Carlo tree search: grows a tree of the most promising moves, picked
+/// with UCT, and scores each new node with a short playout of random moves,
+/// captures preferred. Plays the move tried the most
+pub(crate) struct MctsBot {
+    iterations: Option<usize>,
+    rng: Rng,
+    nodes: Vec<Node>,
+}
+
+impl MctsBot {
+    pub(crate) const fn new(rng: Rng) -> Self {
+        Self {
+            iterations: None,
+            rng,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Iterations of the search, on top of the time and node limits
+    pub(crate) const fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// One iteration: selection, expansion, playout and backpropagation
+    fn iterate(&mut self, chess_game: &mut ChessEngine, control: &mut SearchControl) {
+        let mut node: usize = 0;
+        let mut ply: usize = 0;
+
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node);
+            let (from, to) = self.nodes[node].played.expect("Only the root has no move");
+            chess_game.try_move(Some(from), Some(to));
+            ply += 1;
+            control.visit(ply);
+        }
+
+        if !chess_game.is_end() {
+            if let Some((from, to)) = self.nodes[node].untried.pop() {
+                chess_game.try_move(Some(from), Some(to));
+                ply += 1;
+                control.visit(ply);
+
+                let child: Node = Node::new(Some(node), Some((from, to)), chess_game);
+                self.nodes.push(child);
+                let child: usize = self.nodes.len() - 1;
+                self.nodes[node].children.push(child);
+                node = child;
+            }
+        }
+
+        // The outcome for the player who moved last, the one owning `node`
+        let mut outcome: f64 = 1.0 - self.playout(chess_game, control, ply);
+        loop {
+            self.nodes[node].visits += 1;
+            self.nodes[node].wins += outcome;
+            let Some(parent) = self.nodes[node].parent else {
+                break;
+            };
+            chess_game.undo_move();
+            outcome = 1.0 - outcome;
+            node = parent;
+        }
+    }
+
+    /// The child with the highest upper confidence bound
+    fn select(&self, node: usize) -> usize {
+        let parent_visits: f64 = f64::from(self.nodes[node].visits).ln();
+        let bound = |child: usize| {
+            let visits: f64 = f64::from(self.nodes[child].visits);
+            self.nodes[child].win_rate() + EXPLORATION * (parent_visits / visits).sqrt()
+        };
+
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .max_by(|&first, &second| bound(first).total_cmp(&bound(second)))
+            .expect("A node with children")
+    }
+
+    /// Plays a few random moves from the position and undoes them, returns
+    /// the expected outcome for the side to move: 1 for a win, 0.5 for a draw
+    fn playout(&mut self, chess_game: &mut ChessEngine, control: &mut SearchControl, ply: usize) -> f64 {
+        let player = chess_game.current_player();
+        let mut played: usize = 0;
+
+        while played < PLAYOUT_PLIES && !chess_game.is_end() {
+            let moves: Vec<Move> = ordered_moves(chess_game);
+            let capture: bool = captured(&moves[0]).is_some() && self.rng.unit() < CAPTURE_BIAS;
+            let index: usize = if capture { 0 } else { self.rng.below(moves.len()) };
+
+            chess_game.try_move(Some(moves[index].from()), Some(moves[index].to()));
+            played += 1;
+            control.visit(ply + played);
+        }
+
+        let outcome: f64 = match chess_game.result() {
+            Result::Checkmate if chess_game.current_player() == player => 0.0,
+            Result::Checkmate => 1.0,
+            Result::Stalemate | Result::Draw => 0.5,
+            Result::None => evaluate(chess_game).relative(player).win_probability(),
+        };
+
+        for _ in 0..played {
+            chess_game.undo_move();
+        }
+
+        outcome
+    }
+
+    /// The line of the moves tried the most, with the score of the first one
+    fn result(&self, control: &SearchControl) -> SearchResult {
+        let mut pv: Vec<(Position, Position)> = Vec::new();
+        let mut score: Score = Score::ZERO;
+        let mut node: usize = 0;
+
+        while let Some(&child) = self.nodes[node].children.iter().max_by_key(|&&child| self.nodes[child].visits) {
+            if pv.is_empty() {
+                score = Score::from_win_probability(self.nodes[child].win_rate());
+            }
+            pv.extend(self.nodes[child].played);
+            node = child;
+        }
+
+        control.result(score, pv.clone(), pv.len() as i16)
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new(Rng::from_time())
+    }
+}
+
+impl Bot for MctsBot {
+    fn name(&self) -> &str {
+        "mcts"
+    }
+
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut control: SearchControl = SearchControl::new(&limits);
+        let mut chess_game: ChessEngine = chess_game.clone();
+        let iterations: usize = match (self.iterations, limits.max_movetime(), limits.max_nodes()) {
+            (Some(iterations), _, _) => iterations,
+            (None, None, None) => DEFAULT_ITERATIONS,
+            (None, _, _) => usize::MAX,
+        };
+
+        self.nodes = vec![Node::new(None, None, &chess_game)];
+        control.visit(0);
+        for iteration in 1..=iterations {
+            if control.aborted() || limits.stop_requested() {
+                break;
+            }
+            self.iterate(&mut chess_game, &mut control);
+
+            if iteration % REPORT_INTERVAL == 0 {
+                on_iteration(&self.result(&control));
+            }
+        }
+
+        let result: SearchResult = self.result(&control);
+        on_iteration(&result);
+        self.nodes.clear();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::Bot;
+    use crate::bot::rng::Rng;
+    use crate::bot::score::Score;
+    use crate::bot::search::{SearchLimits, SearchResult};
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::MctsBot;
+
+    #[test]
+    fn test_mcts_takes_the_queen() -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("6k1/6p1/8/3q4/8/8/8/3R2K1 w - - 0 1")?;
+        let mut bot: MctsBot = MctsBot::new(Rng::new(5)).with_iterations(300);
+
+        let result: SearchResult = bot.choose_move(&chess_game, SearchLimits::default());
+
+        assert_eq!(Position::from_notation("d1").zip(Position::from_notation("d5")), result.best_move());
+        assert!(result.score() > Score::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mcts_stops_on_time() {
+        let chess_game: ChessEngine = ChessEngine::new();
+        let mut bot: MctsBot = MctsBot::new(Rng::new(5));
+        let mut reports: usize = 0;
+
+        let result: SearchResult =
+            bot.search(&chess_game, SearchLimits::movetime(Duration::from_millis(50)), &mut |_| reports += 1);
+
+        assert!(result.best_move().is_some());
+        assert!(result.elapsed() < Duration::from_secs(2));
+        assert!(reports >= 1);
+    }
+}
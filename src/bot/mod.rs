@@ -1 +1,131 @@
+use anyhow::{bail, Context, Result};
+
+use std::sync::Arc;
+
+use crate::game::ChessEngine;
+use crate::protocol::uci_client::UciEngine;
+use crate::tablebase::Tablebase;
+
+use alpha_beta_bot::AlphaBetaBot;
+use difficulty::{Difficulty, LEVELS};
+use greedy_bot::GreedyBot;
+use mcts_bot::MctsBot;
+use negamax_bot::NegaMaxBot;
+use random_bot::RandomBot;
+use search::{SearchLimits, SearchResult};
+
+pub(crate) mod alpha_beta_bot;
+pub(crate) mod difficulty;
+pub(crate) mod evaluation;
+mod greedy_bot;
+pub(crate) mod mate_solver;
+mod mcts_bot;
 mod negamax_bot;
+mod random_bot;
+pub(crate) mod rng;
+pub(crate) mod score;
+pub(crate) mod search;
+pub(crate) mod transposition;
+
+/// Names accepted by `from_name`, weakest first
+pub(crate) const BOT_NAMES: [&str; 5] = ["random", "greedy", "mcts", "negamax", "alpha-beta"];
+const UCI_PREFIX: &str = "uci:";
+const MCTS_PREFIX: &str = "mcts:";
+
+pub(crate) trait Bot: Send {
+    fn name(&self) -> &str;
+
+    /// Searches the position within `limits`, `on_iteration` gets the
+    /// intermediate results of the bots that deepen iteratively
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult;
+
+    fn choose_move(&mut self, chess_game: &ChessEngine, limits: SearchLimits) -> SearchResult {
+        self.search(chess_game, limits, &mut |_| {})
+    }
+
+    /// Forgets what was learned from previous searches
+    fn new_game(&mut self) {}
+
+    /// Bots without a transposition table ignore it
+    fn set_hash_size(&mut self, _megabytes: usize) {}
+
+    /// Threads searching together, bots that can't share their search
+    /// ignore it
+    fn set_threads(&mut self, _threads: usize) {}
+
+    /// Endgame tables to play perfectly once few pieces are left, the bots
+    /// that don't search ignore them
+    fn set_tablebase(&mut self, _tablebase: Arc<Tablebase>) {}
+
+    /// Why the last search came back without a move, for the bots that can
+    /// fail
+    fn failure(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// One of `BOT_NAMES`, "mcts:<iterations>" for a Monte Carlo search with
+/// its own budget, or "uci:<command>" for an external UCI engine
+pub(crate) fn from_name(name: &str) -> Result<Box<dyn Bot>> {
+    if let Some(command) = name.strip_prefix(UCI_PREFIX) {
+        let mut parts = command.split_whitespace();
+        let Some(program) = parts.next() else {
+            bail!("Expected \"{UCI_PREFIX}<command>\"");
+        };
+        let args: Vec<&str> = parts.collect();
+
+        return Ok(Box::new(UciEngine::spawn(program, &args)?));
+    }
+
+    if let Some(iterations) = name.strip_prefix(MCTS_PREFIX) {
+        let iterations: usize = iterations
+            .parse()
+            .with_context(|| format!("Expected \"{MCTS_PREFIX}<iterations>\""))?;
+
+        return Ok(Box::new(MctsBot::default().with_iterations(iterations)));
+    }
+
+    if let Some(difficulty) = Difficulty::from_name(name) {
+        return Ok(difficulty.bot());
+    }
+
+    let bot: Box<dyn Bot> = match name {
+        "random" => Box::new(RandomBot::default()),
+        "greedy" => Box::new(GreedyBot),
+        "mcts" => Box::new(MctsBot::default()),
+        "negamax" => Box::new(NegaMaxBot::new()),
+        "alpha-beta" => Box::new(AlphaBetaBot::new()),
+        _ => bail!(
+            "Unknown bot \"{name}\", expected one of: {}, a level ({}), {MCTS_PREFIX}<iterations> or {UCI_PREFIX}<command>",
+            BOT_NAMES.join(", "),
+            LEVELS.map(|level| level.name()).join(", "),
+        ),
+    };
+
+    Ok(bot)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{from_name, BOT_NAMES};
+
+    #[test]
+    fn test_from_name() -> anyhow::Result<()> {
+        for name in BOT_NAMES {
+            assert_eq!(name, from_name(name)?.name());
+        }
+        assert_eq!("beginner", from_name("beginner")?.name());
+        assert_eq!("alpha-beta", from_name("expert")?.name());
+        assert_eq!("mcts", from_name("mcts:100")?.name());
+        assert!(from_name("mcts:many").is_err());
+        assert!(from_name("stockfish").is_err());
+        Ok(())
+    }
+}
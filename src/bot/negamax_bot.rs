@@ -1,75 +1,113 @@
 use std::collections::HashSet;
 
 use crate::game::{ChessEngine, Result};
-use crate::game::board::color::Color;
 use crate::game::board::position::Position;
 
-struct NegaMaxBot {
-    chess_game: ChessEngine,
+use super::Bot;
+use super::evaluation::evaluate;
+use super::score::Score;
+use super::search::{SearchControl, SearchLimits, SearchResult};
+
+/// Plain full width search, slow but easy to trust
+pub(crate) struct NegaMaxBot {
+    control: SearchControl,
 }
 
 impl NegaMaxBot {
-    pub fn new(chess_game: ChessEngine) -> Self {
+    pub(crate) fn new() -> Self {
         Self {
-            chess_game,
+            control: SearchControl::new(&SearchLimits::default()),
         }
     }
 
-    pub fn run(&mut self, depth: i16) -> (Position, Position) {
-        if let (_, Some(predicted)) = self.negamax(1, depth) {
-            return predicted;
+    /// Returns the best score for the current player, and the line leading to it
+    fn negamax(&mut self, chess_game: &mut ChessEngine, depth: i16, ply: usize) -> (Score, Vec<(Position, Position)>) {
+        if !self.control.visit(ply) {
+            return (Score::ZERO, Vec::new());
         }
 
-        todo!("no no no");
-    }
-
-    fn negamax(&mut self, color_score: i16, depth: i16) -> (i16, Option<(Position, Position)>) {
         if depth == 0 {
-            return (self.pieces_score() * color_score, None);
+            return (evaluate(chess_game).relative(chess_game.current_player()), Vec::new());
         }
 
-        let mut max: (i16, Option<(Position, Position)>) = (-100, None);
-        let mut score: (i16, Option<(Position, Position)>);
-        let possible_moves: HashSet<(Position, Position)> = self
-            .chess_game
+        let mut max: (Score, Vec<(Position, Position)>) = (-Score::INFINITY, Vec::new());
+        let mut score: Score;
+        let mut line: Vec<(Position, Position)>;
+        let possible_moves: HashSet<(Position, Position)> = chess_game
             .possible_moves()
-            .iter()
-            .flat_map(|(_, moves)| moves)
+            .values()
+            .flatten()
             .map(|m| (m.from(), m.to()))
             .collect();
 
         for (from, to) in possible_moves {
-            self.chess_game.try_move(Some(from), Some(to));
-
-            println!("depth: {depth}: ({from:?}, {to:?}) ({:?})", self.chess_game.result());
-            score = match self.chess_game.result() {
-                Result::Checkmate => return (100 * color_score, Some((from, to))),
-                Result::Draw | Result::Stalemate => (0, Some((from, to))),
-                _ => self.negamax(-color_score, depth-1),
+            chess_game.try_move(Some(from), Some(to));
+
+            (score, line) = match chess_game.result() {
+                Result::Checkmate => {
+                    chess_game.undo_move();
+                    return (Score::mate_in(ply + 1), vec![(from, to)]);
+                }
+                Result::Draw | Result::Stalemate => (Score::ZERO, Vec::new()),
+                Result::None => {
+                    let (score, line) = self.negamax(chess_game, depth - 1, ply + 1);
+                    (-score, line)
+                }
             };
 
-            score.0 *= -1;
-            println!("depth: {depth}: {score:?} ({max:?})");
+            chess_game.undo_move();
 
-            if score.0 > max.0 {
-                max = (score.0, Some((from, to)));
+            if self.control.aborted() {
+                break;
             }
 
-            self.chess_game.undo_move();
+            if score > max.0 {
+                line.insert(0, (from, to));
+                max = (score, line);
+            }
         }
 
         max
     }
+}
+
+impl Default for NegaMaxBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bot for NegaMaxBot {
+    fn name(&self) -> &str {
+        "negamax"
+    }
 
-    fn pieces_score(&self) -> i16 {
-        let white_sum = self.chess_game.points(Color::White);
-        let black_sum = self.chess_game.points(Color::Black);
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut chess_game: ChessEngine = chess_game.clone();
+        self.control = SearchControl::new(&limits);
+        let mut result: SearchResult = self.control.result(Score::ZERO, Vec::new(), 0);
+
+        for depth in 1..=limits.max_depth() {
+            let (score, pv): (Score, Vec<(Position, Position)>) = self.negamax(&mut chess_game, depth, 0);
+
+            // An unfinished iteration is only better than nothing
+            if self.control.aborted() {
+                if result.best_move().is_none() {
+                    result = self.control.result(score, pv, depth);
+                }
+                break;
+            }
 
-        if self.chess_game.current_player() == Color::White {
-            black_sum - white_sum
-        } else {
-            white_sum - black_sum
+            result = self.control.result(score, pv, depth);
+            on_iteration(&result);
         }
+
+        result
     }
 }
 
@@ -93,82 +131,86 @@ mod tests {
     use crate::game::pieces::queen::Queen;
     use crate::game::pieces::rook::Rook;
 
+    use crate::bot::Bot;
+    use crate::bot::score::Score;
+    use crate::bot::search::{SearchLimits, SearchResult};
+
     use super::NegaMaxBot;
 
     #[test]
     fn test_negamax_depth_1_checkmate_1() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((1isize, 0isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((1isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Rook(Rook::new((0isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((1isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((1isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((0isize, 5isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((1isize, 0isize).into(), (1isize, 6isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_2() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((2isize, 0isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((3isize, 6isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 7isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((2isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((3isize, 6isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 7isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((2isize, 0isize).into(), (2isize, 7isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_3() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((7isize, 4isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 7isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((7isize, 4isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 7isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((7isize, 4isize).into(), (0isize, 4isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_4() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((0isize, 4isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((0isize, 0isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((0isize, 4isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((0isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let mut expected: HashSet<(Position, Position)> = HashSet::new();
         expected.insert(((0isize, 4isize).into(), (1isize, 3isize).into()));
         expected.insert(((0isize, 4isize).into(), (1isize, 4isize).into()));
         expected.insert(((0isize, 4isize).into(), (0isize, 2isize).into()));
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
         assert!(
-            expected.contains(&predicted_move),
+            predicted_move.is_some_and(|predicted| expected.contains(&predicted)),
             "expected:\n{expected:?}\npredicted: {predicted_move:?}"
             );
     }
@@ -176,144 +218,164 @@ mod tests {
     #[test]
     fn test_negamax_depth_1_checkmate_5() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((2isize, 2isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((2isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((2isize, 2isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((2isize, 2isize).into(), (0isize, 2isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_6() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((2isize, 2isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((2isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((2isize, 2isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((2isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((2isize, 2isize).into(), (0isize, 2isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_7() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Queen(Queen::new((5isize, 5isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 7isize).into(), Color::Black)))
-            .add(PieceKind::King(King::new((0isize, 7isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Queen(Queen::new((5isize, 5isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 7isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((0isize, 7isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((5isize, 5isize).into(), (0isize, 5isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_8() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((7isize, 5isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((7isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Queen(Queen::new((2isize, 3isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((7isize, 5isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((7isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Queen(Queen::new((2isize, 3isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::Black);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((2isize, 3isize).into(), (5isize, 6isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_9() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Queen(Queen::new((4isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 5isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
-            .add(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Queen(Queen::new((4isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::Pawn(Pawn::new((1isize, 6isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((4isize, 7isize).into(), (0isize, 3isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_10() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((5isize, 1isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((5isize, 0isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((4isize, 1isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Rook(Rook::new((6isize, 3isize).into(), Color::Black)))
-            .add(PieceKind::Queen(Queen::new((3isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((5isize, 1isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((5isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((4isize, 1isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((6isize, 3isize).into(), Color::Black)))
+            .with(PieceKind::Queen(Queen::new((3isize, 5isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::Black);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((3isize, 5isize).into(), (6isize, 2isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_11() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Queen(Queen::new((2isize, 5isize).into(), Color::White)))
-            .add(PieceKind::Rook(Rook::new((4isize, 7isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Queen(Queen::new((2isize, 5isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((4isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((4isize, 7isize).into(), (0isize, 7isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(Some(expected), predicted_move);
     }
 
     #[test]
     fn test_negamax_depth_1_checkmate_12() {
         let board: Board = BoardBuilder::new()
-            .add(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
-            .add(PieceKind::Queen(Queen::new((3isize, 7isize).into(), Color::White)))
-            .add(PieceKind::Pawn(Pawn::new((3isize, 5isize).into(), Color::White)))
-            .add(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
-            .add(PieceKind::Bishop(Bishop::new((0isize, 5isize).into(), Color::Black)))
-            .add(PieceKind::Rook(Rook::new((0isize, 7isize).into(), Color::Black)))
+            .with(PieceKind::King(King::new((6isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Queen(Queen::new((3isize, 7isize).into(), Color::White)))
+            .with(PieceKind::Pawn(Pawn::new((3isize, 5isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((1isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Bishop(Bishop::new((0isize, 5isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((0isize, 7isize).into(), Color::Black)))
             .build();
         let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
-        let mut bot: NegaMaxBot = NegaMaxBot::new(chess_game);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
         let expected: (Position, Position) = ((3isize, 7isize).into(), (2isize, 6isize).into());
 
-        let predicted_move: (Position, Position) = bot.run(1);
+        let predicted_move: Option<(Position, Position)> = bot.choose_move(&chess_game, SearchLimits::depth(1)).best_move();
+
+        assert_eq!(Some(expected), predicted_move);
+    }
+
+    #[test]
+    fn test_negamax_search_result() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new((7isize, 6isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((1isize, 0isize).into(), Color::White)))
+            .with(PieceKind::Rook(Rook::new((1isize, 7isize).into(), Color::White)))
+            .with(PieceKind::King(King::new((0isize, 6isize).into(), Color::Black)))
+            .with(PieceKind::Rook(Rook::new((0isize, 5isize).into(), Color::Black)))
+            .build();
+        let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
+        let mut bot: NegaMaxBot = NegaMaxBot::new();
+        let mut depths: Vec<i16> = Vec::new();
+
+        let result: SearchResult = bot.search(&chess_game, SearchLimits::depth(2), &mut |info| depths.push(info.depth()));
 
-        assert_eq!(expected, predicted_move);
+        assert_eq!(vec![1, 2], depths);
+        assert_eq!(Score::mate_in(1), result.score());
+        assert_eq!(&[((1isize, 0isize).into(), (1isize, 6isize).into())], result.pv());
     }
 }
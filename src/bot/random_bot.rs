@@ -0,0 +1,82 @@
+use crate::game::ChessEngine;
+use crate::game::board::position::Position;
+
+use super::Bot;
+use super::alpha_beta_bot::ordered_moves;
+use super::rng::Rng;
+use super::score::Score;
+use super::search::{SearchControl, SearchLimits, SearchResult};
+
+/// Plays any legal move, for people learning the rules
+pub(crate) struct RandomBot {
+    rng: Rng,
+}
+
+impl RandomBot {
+    pub(crate) const fn new(rng: Rng) -> Self {
+        Self {
+            rng,
+        }
+    }
+}
+
+impl Default for RandomBot {
+    fn default() -> Self {
+        Self::new(Rng::from_time())
+    }
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        let mut control: SearchControl = SearchControl::new(&limits);
+        control.visit(0);
+
+        let moves: Vec<(Position, Position)> = ordered_moves(chess_game)
+            .iter()
+            .map(|m| (m.from(), m.to()))
+            .collect();
+        let pv: Vec<(Position, Position)> = if moves.is_empty() {
+            Vec::new()
+        } else {
+            vec![moves[self.rng.below(moves.len())]]
+        };
+
+        let result: SearchResult = control.result(Score::ZERO, pv, 1);
+        on_iteration(&result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::Bot;
+    use crate::bot::rng::Rng;
+    use crate::bot::search::SearchLimits;
+    use crate::game::ChessEngine;
+
+    use super::RandomBot;
+
+    #[test]
+    fn test_random_move_is_legal() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut bot: RandomBot = RandomBot::new(Rng::new(7));
+
+        for _ in 0..10 {
+            let (from, to) = bot
+                .choose_move(&chess_game, SearchLimits::default())
+                .best_move()
+                .expect("There is a legal move");
+
+            assert!(chess_game.try_move(Some(from), Some(to)));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Xorshift64*: fast and good enough to pick moves, nothing more
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) const fn new(seed: u64) -> Self {
+        // Xorshift never leaves zero
+        if seed == 0 {
+            Self(0x9E37_79B9_7F4A_7C15)
+        } else {
+            Self(seed)
+        }
+    }
+
+    pub(crate) fn from_time() -> Self {
+        let nanos: u128 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+
+        Self::new(nanos as u64)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number in `0..bound`
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "The bound should be positive");
+
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// A number in `0.0..1.0`
+    pub(crate) fn unit(&mut self) -> f64 {
+        // The 53 high bits fill the mantissa of a f64
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::Rng;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let mut rng1: Rng = Rng::new(42);
+        let mut rng2: Rng = Rng::new(42);
+
+        for _ in 0..10 {
+            assert_eq!(rng1.next_u64(), rng2.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_below() {
+        let mut rng: Rng = Rng::new(0);
+
+        for _ in 0..100 {
+            assert!(rng.below(6) < 6);
+            assert!((0.0..1.0).contains(&rng.unit()));
+        }
+    }
+}
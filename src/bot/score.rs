@@ -0,0 +1,174 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, AddAssign, Neg, Sub};
+
+use crate::game::board::color::Color;
+
+// Centipawns turning a win probability of 91% into a 400 points advantage
+const WIN_PROBABILITY_SCALE: f64 = 400.0;
+
+/// A score in centipawns, from white's point of view unless stated otherwise
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Score(i32);
+
+impl Score {
+    pub(crate) const ZERO: Self = Self(0);
+    pub(crate) const MATE: Self = Self(30_000);
+    pub(crate) const INFINITY: Self = Self(32_000);
+    // Below the mates: a tablebase win still has to be played
+    pub(crate) const TABLEBASE_WIN: Self = Self(20_000);
+
+    pub(crate) const fn centipawns(value: i32) -> Self {
+        Self(value)
+    }
+
+    pub(crate) const fn value(self) -> i32 {
+        self.0
+    }
+
+    /// Score of the side to move when it checkmates `ply` plies from the
+    /// root: a faster mate is a better score
+    pub(crate) const fn mate_in(ply: usize) -> Self {
+        Self(Self::MATE.0 - ply as i32)
+    }
+
+    /// Score of the side to move when it is checkmated `ply` plies from the
+    /// root: a slower mate is a better score
+    pub(crate) const fn mated_in(ply: usize) -> Self {
+        Self(-Self::MATE.0 + ply as i32)
+    }
+
+    /// Score of the side to move when the tablebase says it wins `ply`
+    /// plies from the root
+    pub(crate) const fn tablebase_win_in(ply: usize) -> Self {
+        Self(Self::TABLEBASE_WIN.0 - ply as i32)
+    }
+
+    pub(crate) const fn is_mate(self) -> bool {
+        self.0.abs() > Self::MATE.0 - 1_000
+    }
+
+    /// Moves (not plies) until checkmate, negative when the side the score
+    /// belongs to is the one getting checkmated
+    pub(crate) const fn mate_moves(self) -> Option<i32> {
+        if !self.is_mate() {
+            return None;
+        }
+
+        let moves: i32 = (Self::MATE.0 - self.0.abs() + 1) / 2;
+        if self.0 > 0 {
+            Some(moves)
+        } else {
+            Some(-moves)
+        }
+    }
+
+    /// Chances of winning with this advantage, draws counting half, as in
+    /// the Elo model
+    pub(crate) fn win_probability(self) -> f64 {
+        1.0 / (1.0 + 10f64.powf(-f64::from(self.0) / WIN_PROBABILITY_SCALE))
+    }
+
+    /// The advantage giving these chances of winning
+    pub(crate) fn from_win_probability(probability: f64) -> Self {
+        let probability: f64 = probability.clamp(0.001, 0.999);
+
+        Self((-WIN_PROBABILITY_SCALE * (1.0 / probability - 1.0).log10()).round() as i32)
+    }
+
+    /// Converts a score from white's point of view to `color`'s point of view
+    /// (or back, the operation is its own inverse)
+    pub(crate) const fn relative(self, color: Color) -> Self {
+        match color {
+            Color::White => self,
+            Color::Black => Self(-self.0),
+            Color::Any => panic!("A score can not be relative to color \"Any\""),
+        }
+    }
+}
+
+impl Neg for Score {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Add<Self> for Score {
+    type Output = Self;
+
+    fn add(self, value: Self) -> Self::Output {
+        Self(self.0 + value.0)
+    }
+}
+
+impl AddAssign<Self> for Score {
+    fn add_assign(&mut self, value: Self) {
+        self.0 += value.0;
+    }
+}
+
+impl Sub<Self> for Score {
+    type Output = Self;
+
+    fn sub(self, value: Self) -> Self::Output {
+        Self(self.0 - value.0)
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mate_moves() {
+            Some(moves) => write!(f, "#{moves}"),
+            None => write!(f, "{:+.2}", self.0 as f32 / 100f32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::board::color::Color;
+
+    use super::Score;
+
+    #[rstest]
+    #[case(Score::centipawns(35), Color::White, Score::centipawns(35))]
+    #[case(Score::centipawns(35), Color::Black, Score::centipawns(-35))]
+    fn test_relative(
+        #[case]
+        score: Score,
+        #[case]
+        color: Color,
+        #[case]
+        expected: Score
+    ) {
+        assert_eq!(expected, score.relative(color));
+    }
+
+    #[test]
+    fn test_win_probability() {
+        assert_eq!(0.5, Score::ZERO.win_probability());
+        assert!(Score::centipawns(300).win_probability() > 0.8);
+        assert!(Score::mate_in(3).win_probability() > 0.999);
+        assert_eq!(Score::centipawns(-200), Score::from_win_probability(Score::centipawns(-200).win_probability()));
+    }
+
+    #[rstest]
+    #[case(Score::centipawns(135), "+1.35")]
+    #[case(Score::centipawns(-20), "-0.20")]
+    #[case(Score::ZERO, "+0.00")]
+    #[case(Score::mate_in(1), "#1")]
+    #[case(Score::mate_in(5), "#3")]
+    #[case(Score::mated_in(4), "#-2")]
+    fn test_display(
+        #[case]
+        score: Score,
+        #[case]
+        expected: &str
+    ) {
+        assert_eq!(expected, score.to_string());
+    }
+}
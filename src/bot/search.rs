@@ -0,0 +1,354 @@
+use std::fmt::{self, Display};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::game::board::position::Position;
+
+use super::score::Score;
+
+/// Depth searched when the limits don't bound it
+pub(crate) const MAX_DEPTH: i16 = 64;
+// Nodes between two looks at the clock
+const CLOCK_INTERVAL: u64 = 64;
+// Assumed when the GUI doesn't say how many moves are left before the
+// next time control
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// Kept on the clock for the exchanges with the GUI
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+const MIN_MOVETIME: Duration = Duration::from_millis(10);
+
+/// The time to spend on a move with `time` left on the clock, gaining
+/// `increment` after each move
+pub(crate) fn time_budget(time: Duration, increment: Duration, moves_to_go: Option<u32>) -> Duration {
+    let moves_to_go: u32 = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+    let budget: Duration = (time / moves_to_go + increment * 3 / 4).min(time.saturating_sub(MOVE_OVERHEAD));
+
+    budget.max(MIN_MOVETIME)
+}
+
+/// When a search has to stop, no limit at all means searching until
+/// `MAX_DEPTH` or until the stop flag is raised
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SearchLimits {
+    depth: Option<i16>,
+    movetime: Option<Duration>,
+    nodes: Option<u64>,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+    pub(crate) const fn depth(depth: i16) -> Self {
+        Self {
+            depth: Some(depth),
+            movetime: None,
+            nodes: None,
+            stop: None,
+        }
+    }
+
+    pub(crate) const fn movetime(movetime: Duration) -> Self {
+        Self {
+            depth: None,
+            movetime: Some(movetime),
+            nodes: None,
+            stop: None,
+        }
+    }
+
+    pub(crate) const fn with_depth(mut self, depth: i16) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub(crate) const fn with_movetime(mut self, movetime: Duration) -> Self {
+        self.movetime = Some(movetime);
+        self
+    }
+
+    /// Checked with the clock, the search can go over by a few nodes
+    pub(crate) const fn with_nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    /// The search stops as soon as `stop` is set, from any thread
+    pub(crate) fn with_stop(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub(crate) const fn depth_limit(&self) -> Option<i16> {
+        self.depth
+    }
+
+    pub(crate) fn max_depth(&self) -> i16 {
+        self.depth.unwrap_or(MAX_DEPTH).max(1)
+    }
+
+    pub(crate) const fn max_movetime(&self) -> Option<Duration> {
+        self.movetime
+    }
+
+    pub(crate) const fn max_nodes(&self) -> Option<u64> {
+        self.nodes
+    }
+
+    pub(crate) fn stop_requested(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+}
+
+/// Bookkeeping shared by the searching bots: statistics, and whether the
+/// search ran out of time
+pub(crate) struct SearchControl {
+    start: Instant,
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    stop: Option<Arc<AtomicBool>>,
+    nodes: u64,
+    // Nodes of every thread of the search, updated every `CLOCK_INTERVAL`
+    // nodes
+    total_nodes: Arc<AtomicU64>,
+    seldepth: usize,
+    aborted: bool,
+}
+
+impl SearchControl {
+    pub(crate) fn new(limits: &SearchLimits) -> Self {
+        let start: Instant = Instant::now();
+
+        Self {
+            start,
+            deadline: limits.movetime.map(|movetime| start + movetime),
+            max_nodes: limits.nodes,
+            stop: limits.stop.clone(),
+            nodes: 0,
+            total_nodes: Arc::new(AtomicU64::new(0)),
+            seldepth: 0,
+            aborted: false,
+        }
+    }
+
+    /// The control of a helper thread: same clock and node count, but its
+    /// own `stop` flag, raised by the main thread once it is done
+    pub(crate) fn helper(&self, stop: Arc<AtomicBool>) -> Self {
+        Self {
+            start: self.start,
+            deadline: self.deadline,
+            max_nodes: self.max_nodes,
+            stop: Some(stop),
+            nodes: 0,
+            total_nodes: Arc::clone(&self.total_nodes),
+            seldepth: 0,
+            aborted: false,
+        }
+    }
+
+    /// Counts a node at `ply`, returns false when the search has to stop
+    pub(crate) fn visit(&mut self, ply: usize) -> bool {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.nodes.is_multiple_of(CLOCK_INTERVAL) {
+            let total_nodes: u64 = self.total_nodes.fetch_add(CLOCK_INTERVAL, Ordering::Relaxed) + CLOCK_INTERVAL;
+
+            if self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                || self.max_nodes.is_some_and(|max_nodes| total_nodes >= max_nodes)
+                || self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+            {
+                self.aborted = true;
+            }
+        }
+
+        !self.aborted
+    }
+
+    pub(crate) const fn aborted(&self) -> bool {
+        self.aborted
+    }
+
+    pub(crate) fn result(&self, score: Score, pv: Vec<(Position, Position)>, depth: i16) -> SearchResult {
+        let nodes: u64 = self.total_nodes.load(Ordering::Relaxed) + self.nodes % CLOCK_INTERVAL;
+
+        SearchResult::new(score, pv, depth, self.seldepth, nodes, self.start.elapsed())
+    }
+}
+
+/// What a search found, reported after every completed iteration and once
+/// the search is over
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SearchResult {
+    score: Score,
+    pv: Vec<(Position, Position)>,
+    depth: i16,
+    seldepth: usize,
+    nodes: u64,
+    elapsed: Duration,
+}
+
+impl SearchResult {
+    pub(crate) fn new(
+        score: Score,
+        pv: Vec<(Position, Position)>,
+        depth: i16,
+        seldepth: usize,
+        nodes: u64,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            score,
+            pv,
+            depth,
+            seldepth,
+            nodes,
+            elapsed,
+        }
+    }
+
+    /// Score of the side to move
+    pub(crate) const fn score(&self) -> Score {
+        self.score
+    }
+
+    /// The principal variation, starting with the best move
+    pub(crate) fn pv(&self) -> &[(Position, Position)] {
+        &self.pv
+    }
+
+    pub(crate) fn best_move(&self) -> Option<(Position, Position)> {
+        self.pv.first().copied()
+    }
+
+    pub(crate) const fn depth(&self) -> i16 {
+        self.depth
+    }
+
+    /// Deepest ply reached, extensions and quiescence included
+    pub(crate) const fn seldepth(&self) -> usize {
+        self.seldepth
+    }
+
+    pub(crate) const fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub(crate) const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub(crate) fn nps(&self) -> u64 {
+        let millis: u128 = self.elapsed.as_millis().max(1);
+
+        (u128::from(self.nodes) * 1_000 / millis) as u64
+    }
+}
+
+impl Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pv: Vec<String> = self
+            .pv
+            .iter()
+            .map(|(from, to)| format!("{}{}", from.to_notation(), to.to_notation()))
+            .collect();
+
+        write!(
+            f,
+            "depth {} seldepth {} score {} nodes {} nps {} time {}ms pv {}",
+            self.depth,
+            self.seldepth,
+            self.score,
+            self.nodes,
+            self.nps(),
+            self.elapsed.as_millis(),
+            pv.join(" "),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::score::Score;
+    use crate::game::board::position::Position;
+
+    use super::{time_budget, SearchControl, SearchLimits, SearchResult, MAX_DEPTH};
+
+    #[test]
+    fn test_search_result_display() {
+        let pv: Vec<(Position, Position)> = vec![
+            ((6isize, 4isize).into(), (4isize, 4isize).into()),
+            ((1isize, 4isize).into(), (3isize, 4isize).into()),
+        ];
+        let result: SearchResult = SearchResult::new(Score::centipawns(25), pv, 2, 5, 3_000, Duration::from_millis(1_500));
+
+        assert_eq!(2_000, result.nps());
+        assert_eq!(Some(((6isize, 4isize).into(), (4isize, 4isize).into())), result.best_move());
+        assert_eq!(
+            "depth 2 seldepth 5 score +0.25 nodes 3000 nps 2000 time 1500ms pv e2e4 e7e5",
+            result.to_string(),
+        );
+    }
+
+    #[test]
+    fn test_search_limits() {
+        assert_eq!(MAX_DEPTH, SearchLimits::default().max_depth());
+        assert_eq!(3, SearchLimits::movetime(Duration::from_secs(1)).with_depth(3).max_depth());
+    }
+
+    #[test]
+    fn test_time_budget() {
+        assert_eq!(Duration::from_millis(3_750), time_budget(Duration::from_secs(60), Duration::from_secs(1), Some(20)));
+        assert_eq!(Duration::from_millis(2_000), time_budget(Duration::from_secs(60), Duration::ZERO, None));
+        assert_eq!(Duration::from_millis(10), time_budget(Duration::from_millis(40), Duration::from_secs(1), Some(1)));
+    }
+
+    #[test]
+    fn test_search_control_deadline() {
+        let mut control: SearchControl = SearchControl::new(&SearchLimits::movetime(Duration::ZERO));
+
+        while control.visit(1) {}
+
+        assert!(control.aborted());
+        assert_eq!(64, control.result(Score::ZERO, Vec::new(), 1).nodes());
+    }
+
+    #[test]
+    fn test_search_control_stop() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+        let mut control: SearchControl = SearchControl::new(&SearchLimits::default().with_stop(stop));
+
+        while control.visit(1) {}
+
+        assert!(control.aborted());
+    }
+
+    #[test]
+    fn test_search_control_nodes() {
+        let mut control: SearchControl = SearchControl::new(&SearchLimits::default().with_nodes(200));
+
+        while control.visit(1) {}
+
+        assert_eq!(256, control.result(Score::ZERO, Vec::new(), 1).nodes());
+    }
+
+    #[test]
+    fn test_search_control_helper() {
+        let mut control: SearchControl = SearchControl::new(&SearchLimits::default());
+        let mut helper: SearchControl = control.helper(Arc::new(AtomicBool::new(false)));
+
+        for _ in 0..100 {
+            control.visit(1);
+        }
+        for _ in 0..128 {
+            helper.visit(1);
+        }
+
+        assert_eq!(228, control.result(Score::ZERO, Vec::new(), 1).nodes());
+    }
+}
@@ -0,0 +1,227 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::game::board::COLUMNS;
+use crate::game::board::position::Position;
+
+use super::score::Score;
+
+/// How the stored score relates to the real score of the position
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Bound {
+    Exact,
+    // The search failed high, the real score is at least this one
+    Lower,
+    // The search failed low, the real score is at most this one
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Entry {
+    key: u64,
+    depth: i16,
+    score: Score,
+    bound: Bound,
+    best_move: Option<(Position, Position)>,
+}
+
+impl Entry {
+    /// Mate scores are stored relative to the position rather than to the
+    /// root, the same mate can be found at different plies
+    pub(crate) fn new(key: u64, depth: i16, score: Score, bound: Bound, best_move: Option<(Position, Position)>, ply: usize) -> Self {
+        Self {
+            key,
+            depth,
+            score: shift_mate(score, ply as i32),
+            bound,
+            best_move,
+        }
+    }
+
+    pub(crate) const fn depth(&self) -> i16 {
+        self.depth
+    }
+
+    pub(crate) fn score(&self, ply: usize) -> Score {
+        shift_mate(self.score, -(ply as i32))
+    }
+
+    pub(crate) const fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub(crate) const fn best_move(&self) -> Option<(Position, Position)> {
+        self.best_move
+    }
+
+    /// Everything but the key in one word: the score on 16 bits, the depth on
+    /// 8, the bound on 2 (never 0, so that an empty slot is 0) and the best
+    /// move on 13
+    fn pack(&self) -> u64 {
+        let score: u64 = u64::from(self.score.value() as i16 as u16);
+        let depth: u64 = u64::from(self.depth.clamp(i16::from(i8::MIN), i16::from(i8::MAX)) as i8 as u8);
+        let bound: u64 = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        let best_move: u64 = self.best_move.map_or(0, |(from, to)| 1 | square(from) << 1 | square(to) << 7);
+
+        score | depth << 16 | bound << 24 | best_move << 26
+    }
+
+    fn unpack(key: u64, data: u64) -> Self {
+        let bound: Bound = match data >> 24 & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let best_move: u64 = data >> 26;
+
+        Self {
+            key,
+            depth: i16::from(((data >> 16) & 0xFF) as u8 as i8),
+            score: Score::centipawns(i32::from((data & 0xFFFF) as u16 as i16)),
+            bound,
+            best_move: (best_move & 1 == 1).then(|| (position(best_move >> 1 & 0x3F), position(best_move >> 7 & 0x3F))),
+        }
+    }
+}
+
+fn square(position: Position) -> u64 {
+    (position.row() * COLUMNS + position.column()) as u64
+}
+
+fn position(square: u64) -> Position {
+    (square as usize / COLUMNS, square as usize % COLUMNS).into()
+}
+
+fn shift_mate(score: Score, ply: i32) -> Score {
+    if !score.is_mate() {
+        score
+    } else if score > Score::ZERO {
+        score + Score::centipawns(ply)
+    } else {
+        score - Score::centipawns(ply)
+    }
+}
+
+/// Results of previous searches, indexed by position key, shared by the
+/// search threads. A new entry always replaces the old one.
+///
+/// There is no lock: each slot holds the packed entry and the key xor-ed with
+/// it, so an entry torn by two threads writing at once doesn't match its key
+/// anymore and is ignored
+pub(crate) struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+}
+
+impl TranspositionTable {
+    pub(crate) const DEFAULT_MEGABYTES: usize = 16;
+
+    pub(crate) fn new(megabytes: usize) -> Self {
+        let length: usize = (megabytes * 1024 * 1024 / mem::size_of::<[AtomicU64; 2]>()).max(1);
+
+        Self {
+            entries: (0..length).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+        }
+    }
+
+    pub(crate) fn probe(&self, key: u64) -> Option<Entry> {
+        let [check, data] = &self.entries[self.index(key)];
+        let data: u64 = data.load(Ordering::Relaxed);
+        let check: u64 = check.load(Ordering::Relaxed);
+
+        (data != 0 && check ^ data == key).then(|| Entry::unpack(key, data))
+    }
+
+    pub(crate) fn store(&self, entry: Entry) {
+        let [check, data] = &self.entries[self.index(entry.key)];
+        let packed: u64 = entry.pack();
+        data.store(packed, Ordering::Relaxed);
+        check.store(entry.key ^ packed, Ordering::Relaxed);
+    }
+
+    pub(crate) fn clear(&self) {
+        for [check, data] in &self.entries {
+            check.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MEGABYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use std::thread;
+
+    use crate::bot::score::Score;
+    use crate::game::board::position::Position;
+
+    use super::{Bound, Entry, TranspositionTable};
+
+    #[test]
+    fn test_store_probe() {
+        let table: TranspositionTable = TranspositionTable::new(1);
+        let entry: Entry = Entry::new(42, 3, Score::centipawns(50), Bound::Exact, None, 2);
+
+        table.store(entry);
+
+        assert_eq!(Some(entry), table.probe(42));
+        assert_eq!(None, table.probe(43));
+        table.clear();
+        assert_eq!(None, table.probe(42));
+    }
+
+    #[test]
+    fn test_mate_relative_to_ply() {
+        // Mate in 5 plies from the root, found 2 plies deep: mate in 3 from there
+        let entry: Entry = Entry::new(1, 1, Score::mate_in(5), Bound::Exact, None, 2);
+
+        assert_eq!(Score::mate_in(3), entry.score(0));
+        assert_eq!(Score::mate_in(7), entry.score(4));
+    }
+
+    #[test]
+    fn test_pack_unpack() {
+        let best_move: (Position, Position) = ((6isize, 4isize).into(), (4isize, 4isize).into());
+
+        for entry in [
+            Entry::new(7, -3, Score::mated_in(4), Bound::Upper, None, 0),
+            Entry::new(7, 12, Score::centipawns(-250), Bound::Lower, Some(best_move), 0),
+            Entry::new(7, 0, Score::ZERO, Bound::Exact, Some((best_move.1, best_move.0)), 0),
+        ] {
+            assert_eq!(entry, Entry::unpack(7, entry.pack()));
+        }
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let table: TranspositionTable = TranspositionTable::new(1);
+
+        thread::scope(|scope| {
+            for depth in 1..=4 {
+                let table: &TranspositionTable = &table;
+                scope.spawn(move || {
+                    for key in 0..1_000 {
+                        table.store(Entry::new(key, depth, Score::centipawns(depth.into()), Bound::Exact, None, 0));
+                    }
+                });
+            }
+        });
+
+        let entry: Entry = table.probe(500).expect("Every key was stored");
+        assert_eq!(Score::centipawns(entry.depth().into()), entry.score(0));
+    }
+}
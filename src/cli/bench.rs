@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::bot::Bot;
+use crate::bot::alpha_beta_bot::{AlphaBetaBot, Pruning};
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::fen_parser::FenParser;
+
+const DEFAULT_DEPTH: i16 = 4;
+const POSITIONS: [&str; 3] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "6k1/5ppp/8/8/3n4/8/5PPP/3R2K1 w - - 0 1",
+];
+
+/// `chessterm bench [depth]`: compares the nodes visited by the full width
+/// search and the selective search, depth by depth
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let max_depth: i16 = args
+        .first()
+        .map(|depth| depth.parse())
+        .transpose()
+        .context("The depth should be a number")?
+        .unwrap_or(DEFAULT_DEPTH);
+
+    println!("{:<8}{:>14}{:>12}{:>14}{:>12}", "depth", "full width", "time", "selective", "time");
+
+    for depth in 1..=max_depth {
+        let (full_width_nodes, full_width_time) = bench(depth, Pruning::NONE)?;
+        let (selective_nodes, selective_time) = bench(depth, Pruning::ALL)?;
+
+        println!(
+            "{:<8}{:>14}{:>12.2?}{:>14}{:>12.2?}",
+            depth, full_width_nodes, full_width_time, selective_nodes, selective_time,
+        );
+    }
+
+    Ok(())
+}
+
+fn bench(depth: i16, pruning: Pruning) -> Result<(u64, Duration)> {
+    let mut nodes: u64 = 0;
+    let mut elapsed: Duration = Duration::ZERO;
+
+    for fen in POSITIONS {
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new().with_pruning(pruning);
+        let result: SearchResult = bot.choose_move(&FenParser::parse(fen)?, SearchLimits::depth(depth));
+        nodes += result.nodes();
+        elapsed += result.elapsed();
+    }
+
+    Ok((nodes, elapsed))
+}
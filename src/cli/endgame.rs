@@ -0,0 +1,28 @@
+use anyhow::{bail, Context, Result};
+
+use crate::bot::rng::Rng;
+use crate::endgame::{Endgame, ENDGAMES};
+use crate::ui;
+
+/// `chessterm endgame <name> [--seed n] [--syzygy dir]`: practices an
+/// endgame against the bot, from random or textbook positions. The bot
+/// defends with the tables of the directory, which tell the distance to mate
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let usage: String = format!(
+        "Usage: chessterm endgame <{}> [--seed n] [--syzygy dir]",
+        ENDGAMES.map(|endgame| endgame.name()).join("|")
+    );
+    let (tablebase, args) = super::syzygy(args)?;
+    let [name, options @ ..] = &args[..] else {
+        bail!("{usage}");
+    };
+
+    let endgame: Endgame = Endgame::from_name(name)?;
+    let rng: Rng = match options {
+        [] => Rng::from_time(),
+        [option, seed] if option == "--seed" => Rng::new(seed.parse().context("The seed should be a number")?),
+        _ => bail!("{usage}"),
+    };
+
+    ui::endgame::run(endgame, rng, tablebase)
+}
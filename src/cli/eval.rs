@@ -0,0 +1,23 @@
+use anyhow::{bail, Result};
+
+use crate::bot::evaluation::trace::EvalTrace;
+use crate::game::ChessEngine;
+use crate::game::fen_parser::FenParser;
+
+/// `chessterm eval <fen>`: prints the evaluation breakdown of a position
+pub(super) fn run(args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        bail!("Usage: chessterm eval <fen>");
+    }
+
+    // The FEN may be given as one quoted argument or as several
+    let fen: String = args.join(" ");
+    let chess_game: ChessEngine = FenParser::parse(&fen)?;
+
+    println!("{}", EvalTrace::new(chess_game.board()));
+    if chess_game.is_end() {
+        println!("Result: {:?}", chess_game.result());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
+
+use crate::tablebase::dtm::{DtmTable, Ending, ENDINGS};
+
+/// `chessterm generate <directory> [ending...]`: builds the DTM tables of
+/// the endings, all of them by default, into the directory
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let Some((directory, names)) = args.split_first() else {
+        bail!("Usage: chessterm generate <directory> [{}...]", ENDINGS.map(|ending| ending.name()).join("|"));
+    };
+    let endings: Vec<Ending> = match names {
+        [] => ENDINGS.to_vec(),
+        names => names.iter().map(|name| Ending::from_name(name)).collect::<Result<_>>()?,
+    };
+    let directory: &Path = Path::new(directory);
+    fs::create_dir_all(directory).with_context(|| format!("Could not create the directory {}", directory.display()))?;
+
+    // The pawn promotes into the queen's table
+    let mut queen: Option<DtmTable> = None;
+    for ending in endings {
+        let path: PathBuf = directory.join(format!("{}.dtm", ending.name()));
+        let start: Instant = Instant::now();
+        if ending == Ending::Pawn && queen.is_none() {
+            queen = Some(match DtmTable::load(&directory.join("kqk.dtm")) {
+                Ok(table) => table,
+                Err(_) => DtmTable::generate(Ending::Queen, None)?,
+            });
+        }
+
+        let table: DtmTable = DtmTable::generate(ending, queen.as_ref())?;
+        table.save(&path)?;
+        println!(
+            "{}: longest mate in {} moves, saved to {} in {:.1}s",
+            ending.name(),
+            table.longest() / 2,
+            path.display(),
+            start.elapsed().as_secs_f64()
+        );
+        if table.ending() == Ending::Queen {
+            queen = Some(table);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::mate_solver::MateSolver;
+use crate::game::ChessEngine;
+use crate::game::board::position::Position;
+use crate::game::fen_parser::FenParser;
+use crate::game::san::to_san;
+
+/// `chessterm mate <fen> <n>`: proves or refutes a forced mate in `n` moves,
+/// listing every key move to catch cooked compositions
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [fen @ .., moves] = args else {
+        bail!("Usage: chessterm mate <fen> <n>");
+    };
+    if fen.is_empty() {
+        bail!("Usage: chessterm mate <fen> <n>");
+    }
+
+    let moves: usize = moves.parse().context("The number of moves should be a number")?;
+    let chess_game: ChessEngine = FenParser::parse(&fen.join(" "))?;
+    let start: Instant = Instant::now();
+    let mut solver: MateSolver = MateSolver::new();
+
+    let keys: Vec<((Position, Position), usize)> = solver.key_moves(&chess_game, moves);
+    let summary: String = format!("{} nodes, {:.2?}", solver.nodes(), start.elapsed());
+
+    println!("{} ({summary})", verdict(moves, &keys));
+    for (key, length) in &keys {
+        let san: String = to_san(&chess_game, *key).expect("The key moves are legal");
+        println!("  {san} mates in {length}");
+    }
+    if keys.len() > 1 {
+        println!("Cooked: {} key moves", keys.len());
+    }
+
+    Ok(())
+}
+
+/// The shortest mate found, the fastest key move coming first, which can be
+/// shorter than the `moves` searched
+fn verdict(moves: usize, keys: &[((Position, Position), usize)]) -> String {
+    match keys.first() {
+        Some((_, length)) => format!("Mate in {length}"),
+        None => format!("No mate in {moves}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::mate_solver::MateSolver;
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+
+    use super::verdict;
+
+    #[rstest]
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", 1, "Mate in 1")]
+    // Asked for more, the back-rank mate is still in 1
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", 3, "Mate in 1")]
+    #[case("k7/8/2K5/8/8/8/8/7R w - - 0 1", 1, "No mate in 1")]
+    fn test_verdict(
+        #[case] fen: &str,
+        #[case] moves: usize,
+        #[case] expected: &str,
+    ) -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        assert_eq!(expected, verdict(moves, &MateSolver::new().key_moves(&chess_game, moves)));
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+
+use crate::tablebase::Tablebase;
+use crate::ui;
+
+mod bench;
+mod endgame;
+mod eval;
+mod generate;
+mod mate;
+mod play;
+mod puzzle;
+mod repertoire;
+mod review;
+mod search;
+mod tablebase;
+mod tactics;
+mod tournament;
+mod uci;
+mod xboard;
+
+pub(crate) fn run(command: &str, args: &[String]) -> Result<()> {
+    match command {
+        "bench" => bench::run(args),
+        "endgame" => endgame::run(args),
+        "eval" => eval::run(args),
+        "generate" => generate::run(args),
+        "match" => tournament::run(args),
+        "mate" => mate::run(args),
+        "play" => play::run(args),
+        "puzzle" => puzzle::run(args),
+        "repertoire" => repertoire::run(args),
+        "review" => review::run(args),
+        "search" => search::run(args),
+        "tablebase" => tablebase::run(args),
+        "tactics" => tactics::run(args),
+        "uci" => uci::run(args),
+        "xboard" => xboard::run(args),
+        _ => bail!("Unknown command \"{command}\""),
+    }
+}
+
+/// Takes `--threads <n>` out of the arguments, one thread by default
+fn threads(args: &[String]) -> Result<(usize, Vec<String>)> {
+    let Some(index) = args.iter().position(|arg| arg == "--threads") else {
+        return Ok((1, args.to_vec()));
+    };
+
+    let threads: usize = args
+        .get(index + 1)
+        .and_then(|threads| threads.parse().ok())
+        .context("--threads should be followed by a number")?;
+    let rest: Vec<String> = args[..index].iter().chain(&args[index + 2..]).cloned().collect();
+
+    Ok((threads.max(1), rest))
+}
+
+/// Takes `--syzygy <directory>` out of the arguments and opens its Syzygy
+/// and DTM tables
+fn syzygy(args: &[String]) -> Result<(Option<Arc<Tablebase>>, Vec<String>)> {
+    let Some(index) = args.iter().position(|arg| arg == "--syzygy") else {
+        return Ok((None, args.to_vec()));
+    };
+
+    let directory: &String = args.get(index + 1).context("--syzygy should be followed by a directory")?;
+    let tablebase: Tablebase = Tablebase::open(directory)?;
+    let rest: Vec<String> = args[..index].iter().chain(&args[index + 2..]).cloned().collect();
+
+    Ok((Some(Arc::new(tablebase)), rest))
+}
+
+/// Takes `--engine <bot>` out of the arguments, the bot analyzing and
+/// reviewing the game: the alpha-beta bot by default
+fn engine(args: &[String]) -> Result<(String, Vec<String>)> {
+    let Some(index) = args.iter().position(|arg| arg == "--engine") else {
+        return Ok((ui::DEFAULT_ENGINE.to_string(), args.to_vec()));
+    };
+
+    let engine: &String = args.get(index + 1).context("--engine should be followed by a bot name")?;
+    let rest: Vec<String> = args[..index].iter().chain(&args[index + 2..]).cloned().collect();
+
+    Ok((engine.clone(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{engine, syzygy, threads};
+
+    #[test]
+    fn test_threads() {
+        let args: Vec<String> = ["alpha-beta", "--threads", "4", "3"].map(String::from).to_vec();
+
+        let (threads, rest) = threads(&args).unwrap();
+
+        assert_eq!(4, threads);
+        assert_eq!(["alpha-beta", "3"], rest[..]);
+        assert!(super::threads(&["--threads".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_engine() {
+        let args: Vec<String> = ["alpha-beta", "--engine", "uci:stockfish", "3"].map(String::from).to_vec();
+
+        let (engine, rest) = engine(&args).unwrap();
+
+        assert_eq!("uci:stockfish", engine);
+        assert_eq!(["alpha-beta", "3"], rest[..]);
+        assert_eq!("alpha-beta", super::engine(&rest).unwrap().0);
+        assert!(super::engine(&["--engine".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_syzygy() {
+        let args: Vec<String> = ["alpha-beta", "3"].map(String::from).to_vec();
+
+        assert_eq!(args, syzygy(&args).unwrap().1);
+        assert!(syzygy(&["--syzygy".to_string()]).is_err());
+        assert!(syzygy(&["--syzygy".to_string(), "/nonexistent/syzygy".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::{self, Bot};
+use crate::bot::search::SearchLimits;
+use crate::game::board::color::Color;
+use crate::ui::{self, Opponent};
+
+const DEFAULT_DEPTH: i16 = 4;
+// Keeps the slow bots from thinking forever
+const MOVETIME: Duration = Duration::from_secs(5);
+
+/// `chessterm play <bot> [white|black] [depth] [--threads n] [--syzygy dir]
+/// [--engine bot]`: plays against a bot in the terminal, with the white
+/// pieces unless told otherwise
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let (threads, args) = super::threads(args)?;
+    let (tablebase, args) = super::syzygy(&args)?;
+    let (engine, args) = super::engine(&args)?;
+    let Some(name) = args.first() else {
+        bail!(
+            "Usage: chessterm play <{}> [white|black] [depth] [--threads n] [--syzygy dir] [--engine bot]",
+            bot::BOT_NAMES.join("|")
+        );
+    };
+
+    let player: Color = match args.get(1).map(String::as_str) {
+        None | Some("white") => Color::White,
+        Some("black") => Color::Black,
+        Some(color) => bail!("Unknown color \"{color}\", expected white or black"),
+    };
+    let depth: i16 = args
+        .get(2)
+        .map(|depth| depth.parse())
+        .transpose()
+        .context("The depth should be a number")?
+        .unwrap_or(DEFAULT_DEPTH);
+
+    let limits: SearchLimits = SearchLimits::movetime(MOVETIME).with_depth(depth);
+    let mut bot: Box<dyn Bot> = bot::from_name(name)?;
+    bot.set_threads(threads);
+    if let Some(tablebase) = &tablebase {
+        bot.set_tablebase(Arc::clone(tablebase));
+    }
+    let opponent: Opponent = Opponent::new(bot, player.other(), limits);
+
+    ui::run(Some(opponent), tablebase, &engine)
+}
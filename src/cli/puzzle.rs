@@ -0,0 +1,41 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::puzzle::{read_puzzles, Puzzle};
+use crate::ui;
+
+// In the working directory, shared by every puzzle file
+const DEFAULT_STATS: &str = "chessterm-puzzles.txt";
+const USAGE: &str = "Usage: chessterm puzzle <csv or epd file> [--theme name] [--stats file]";
+
+/// `chessterm puzzle <file> [options]`: solves the puzzles of a Lichess CSV
+/// export or of an EPD file in the terminal, the opponent replying on its own
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [path, options @ ..] = args else {
+        bail!("{USAGE}");
+    };
+
+    let mut theme: Option<String> = None;
+    let mut stats: String = DEFAULT_STATS.to_string();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().with_context(|| format!("{option} expects a value\n{USAGE}"));
+        match option.as_str() {
+            "--theme" => theme = Some(value()?.clone()),
+            "--stats" => stats = value()?.clone(),
+            _ => bail!("Unknown option \"{option}\"\n{USAGE}"),
+        }
+    }
+
+    let content: String = fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+    let puzzles: Vec<Puzzle> = read_puzzles(&content)?
+        .into_iter()
+        .filter(|puzzle| theme.as_ref().is_none_or(|theme| puzzle.themes().contains(theme)))
+        .collect();
+    if puzzles.is_empty() {
+        bail!("No puzzle to solve in {path}");
+    }
+
+    ui::puzzle::run(&puzzles, &stats)
+}
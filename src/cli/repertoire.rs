@@ -0,0 +1,37 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::rng::Rng;
+use crate::game::board::color::Color;
+use crate::repertoire::Repertoire;
+use crate::ui;
+
+// In the working directory, shared by every repertoire
+const DEFAULT_SCHEDULE: &str = "chessterm-repertoire.txt";
+const USAGE: &str = "Usage: chessterm repertoire <pgn file> [white|black] [--schedule file]";
+
+/// `chessterm repertoire <pgn file> [white|black] [options]`: drills the
+/// lines of the PGN variations for one side, the bot playing the other
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [path, options @ ..] = args else {
+        bail!("{USAGE}");
+    };
+
+    let mut color: Color = Color::White;
+    let mut schedule: String = DEFAULT_SCHEDULE.to_string();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "white" => color = Color::White,
+            "black" => color = Color::Black,
+            "--schedule" => schedule = options.next().with_context(|| format!("--schedule expects a value\n{USAGE}"))?.clone(),
+            _ => bail!("Unknown option \"{option}\"\n{USAGE}"),
+        }
+    }
+
+    let content: String = fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+    let repertoire: Repertoire = Repertoire::new(&content, color)?;
+
+    ui::repertoire::run(&repertoire, &schedule, Rng::from_time())
+}
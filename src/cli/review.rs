@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::{self, Bot};
+use crate::bot::search::SearchLimits;
+use crate::game::ChessEngine;
+use crate::game::pgn::read_game;
+use crate::review::Review;
+use crate::ui;
+
+const DEFAULT_DEPTH: i16 = 3;
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+const USAGE: &str = "Usage: chessterm review <pgn file> [--depth d] [--movetime ms] [--output file] [--engine bot]";
+
+/// `chessterm review <pgn file> [options]`: evaluates every move of a game,
+/// and writes it back annotated with the mistakes and the better lines
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [path, options @ ..] = args else {
+        bail!("{USAGE}");
+    };
+
+    let mut limits: SearchLimits = SearchLimits::movetime(DEFAULT_MOVETIME).with_depth(DEFAULT_DEPTH);
+    let mut output: Option<String> = None;
+    let mut engine: String = ui::DEFAULT_ENGINE.to_string();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().with_context(|| format!("{option} expects a value\n{USAGE}"));
+        match option.as_str() {
+            "--depth" => limits = limits.with_depth(value()?.parse().context("The depth should be a number")?),
+            "--movetime" => {
+                let millis: u64 = value()?.parse().context("The movetime should be a number of milliseconds")?;
+                limits = limits.with_movetime(Duration::from_millis(millis));
+            }
+            "--output" => output = Some(value()?.clone()),
+            "--engine" => engine = value()?.clone(),
+            _ => bail!("Unknown option \"{option}\"\n{USAGE}"),
+        }
+    }
+
+    let content: String = fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+    let chess_game: ChessEngine = read_game(&content)?;
+    let mut bot: Box<dyn Bot> = bot::from_name(&engine)?;
+    let review: Review = Review::new(&chess_game, bot.as_mut(), &limits, &mut |done, total| {
+        eprint!("\rReviewing position {}/{total}", done + 1);
+        let _ = io::stderr().flush();
+    });
+    eprintln!();
+
+    let pgn: String = review.annotate(&chess_game).to_string();
+    match output {
+        Some(path) => fs::write(&path, pgn).with_context(|| format!("Could not write {path}"))?,
+        None => print!("{pgn}"),
+    }
+    eprintln!("{review}");
+
+    Ok(())
+}
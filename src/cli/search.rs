@@ -0,0 +1,33 @@
+use anyhow::{bail, Context, Result};
+
+use crate::bot;
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::ChessEngine;
+use crate::game::fen_parser::FenParser;
+
+/// `chessterm search <bot> <depth> [--threads n] [fen]`: prints what the bot
+/// thinks of a position, iteration by iteration
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let (threads, args) = super::threads(args)?;
+    let [name, depth, fen @ ..] = &args[..] else {
+        bail!("Usage: chessterm search <{}> <depth> [--threads n] [fen]", bot::BOT_NAMES.join("|"));
+    };
+
+    let depth: i16 = depth.parse().context("The depth should be a number")?;
+    let chess_game: ChessEngine = if fen.is_empty() {
+        ChessEngine::new()
+    } else {
+        FenParser::parse(&fen.join(" "))?
+    };
+
+    let mut bot = bot::from_name(name)?;
+    bot.set_threads(threads);
+    let result: SearchResult = bot.search(&chess_game, SearchLimits::depth(depth), &mut |info| println!("{info}"));
+
+    match result.best_move() {
+        Some((from, to)) => println!("best move {}{} ({} {})", from.to_notation(), to.to_notation(), bot.name(), result.score()),
+        None => println!("No legal move ({:?})", chess_game.result()),
+    }
+
+    Ok(())
+}
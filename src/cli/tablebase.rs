@@ -0,0 +1,39 @@
+use anyhow::{bail, Context, Result};
+
+use crate::game::ChessEngine;
+use crate::game::fen_parser::FenParser;
+use crate::game::san::to_san;
+use crate::tablebase::{Tablebase, Wdl, MAX_PIECES};
+
+const USAGE: &str = "Usage: chessterm tablebase <directory> <fen>";
+
+/// `chessterm tablebase <directory> <fen>`: the result of a position in the
+/// Syzygy or DTM tables of the directory, and the move keeping it. The
+/// Syzygy tables with pawns are not read
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [directory, fen @ ..] = args else {
+        bail!("{USAGE}");
+    };
+    if fen.is_empty() {
+        bail!("{USAGE}");
+    }
+
+    let tablebase: Tablebase = Tablebase::open(directory)?;
+    let chess_game: ChessEngine = FenParser::parse(&fen.join(" "))?;
+    let wdl: Wdl = tablebase
+        .probe_wdl(&chess_game)
+        .with_context(|| format!("Not in the {} tables: up to {MAX_PIECES} pieces, no castling, pawns only in DTM tables", tablebase.len()))?;
+
+    println!("WDL: {wdl}");
+    if let Some(dtm) = tablebase.probe_dtm(&chess_game) {
+        println!("DTM: {dtm}");
+    }
+    if let Some(dtz) = tablebase.probe_dtz(&chess_game) {
+        println!("DTZ: {dtz}");
+    }
+    if let Some((played, _)) = tablebase.best_move(&chess_game) {
+        println!("Best move: {}", to_san(&chess_game, played).unwrap_or_default());
+    }
+
+    Ok(())
+}
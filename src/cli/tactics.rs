@@ -0,0 +1,42 @@
+use anyhow::{bail, Context, Result};
+
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::fen_parser::FenParser;
+use crate::protocol::parse_move;
+use crate::tactics::{find_motifs, motifs_after, Motif};
+
+const USAGE: &str = "Usage: chessterm tactics <fen> [--move m]";
+
+/// `chessterm tactics <fen> [--move m]`: lists the tactical motifs on the
+/// board, or the ones a move makes
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let (fen, played): (&[String], Option<&String>) = match args {
+        [fen @ .., option, played] if option == "--move" => (fen, Some(played)),
+        fen => (fen, None),
+    };
+    if fen.is_empty() {
+        bail!("{USAGE}");
+    }
+
+    let chess_game: ChessEngine = FenParser::parse(&fen.join(" "))?;
+    let motifs: Vec<Motif> = match played {
+        Some(notation) => {
+            let played: (Position, Position) = parse_move(notation).with_context(|| USAGE)?;
+            motifs_after(&chess_game, played).with_context(|| format!("Illegal move \"{notation}\""))?
+        }
+        None => find_motifs(&chess_game),
+    };
+
+    if motifs.is_empty() {
+        println!("No motif found");
+    }
+    for motif in &motifs {
+        let side: &str = if motif.color() == Color::White { "White" } else { "Black" };
+        let squares: Vec<String> = motif.squares().iter().map(|square| square.to_notation()).collect();
+        println!("{side}, {} on {}: {motif}", motif.kind(), squares.join(" "));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::{self, Bot};
+use crate::bot::score::Score;
+use crate::bot::search::SearchLimits;
+use crate::tournament::stats::{Sprt, SprtStatus, Tally};
+use crate::tournament::{read_openings, Adjudication, Match};
+
+const DEFAULT_GAMES: usize = 2;
+const DEFAULT_DEPTH: i16 = 3;
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+const USAGE: &str = "Usage: chessterm match <bot> <bot> [--games n] [--openings file] [--depth d] [--movetime ms] [--threads n] \
+                     [--max-plies n] [--resign cp moves] [--pgn file] [--sprt elo0 elo1]";
+
+/// `chessterm match <bot> <bot> [options]`: plays games between two bots or
+/// `uci:` engines, and reports the Elo difference of the first one
+pub(super) fn run(args: &[String]) -> Result<()> {
+    let [first, second, options @ ..] = args else {
+        bail!("{USAGE}");
+    };
+
+    let mut games: usize = DEFAULT_GAMES;
+    let mut limits: SearchLimits = SearchLimits::movetime(DEFAULT_MOVETIME).with_depth(DEFAULT_DEPTH);
+    let mut adjudication: Adjudication = Adjudication::default();
+    let mut openings: Option<String> = None;
+    let mut pgn: Option<String> = None;
+    let mut sprt: Option<Sprt> = None;
+    let mut threads: usize = 1;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().with_context(|| format!("{option} expects a value\n{USAGE}"));
+        match option.as_str() {
+            "--games" => games = value()?.parse().context("The number of games should be a number")?,
+            "--openings" => openings = Some(value()?.clone()),
+            "--depth" => limits = limits.with_depth(value()?.parse().context("The depth should be a number")?),
+            "--movetime" => {
+                let millis: u64 = value()?.parse().context("The movetime should be a number of milliseconds")?;
+                limits = limits.with_movetime(Duration::from_millis(millis));
+            }
+            "--max-plies" => {
+                let max_plies: usize = value()?.parse().context("The maximum plies should be a number")?;
+                adjudication = adjudication.with_max_plies(max_plies);
+            }
+            "--resign" => {
+                let centipawns: i32 = value()?.parse().context("The resign score should be a number of centipawns")?;
+                let moves: usize = value()?.parse().context("The resign moves should be a number")?;
+                adjudication = adjudication.with_resign(Score::centipawns(centipawns), moves);
+            }
+            "--threads" => threads = value()?.parse().context("The number of threads should be a number")?,
+            "--pgn" => pgn = Some(value()?.clone()),
+            "--sprt" => {
+                let elo0: f64 = value()?.parse().context("elo0 should be a number")?;
+                let elo1: f64 = value()?.parse().context("elo1 should be a number")?;
+                sprt = Some(Sprt::new(elo0, elo1));
+            }
+            _ => bail!("Unknown option \"{option}\"\n{USAGE}"),
+        }
+    }
+
+    let mut bots: [Box<dyn Bot>; 2] = [bot::from_name(first)?, bot::from_name(second)?];
+    for bot in &mut bots {
+        bot.set_threads(threads);
+    }
+    let [first_bot, second_bot] = bots;
+    let mut tournament: Match = Match::new(first_bot, second_bot, limits)
+        .with_games(games)
+        .with_adjudication(adjudication);
+    if let Some(path) = openings {
+        let content: String = fs::read_to_string(&path).with_context(|| format!("Could not read {path}"))?;
+        tournament = tournament.with_openings(read_openings(&content)?);
+    }
+    if let Some(sprt) = sprt {
+        tournament = tournament.with_sprt(sprt);
+    }
+    let mut pgn: Option<BufWriter<File>> = pgn
+        .map(|path| File::create(&path).with_context(|| format!("Could not create {path}")))
+        .transpose()?
+        .map(BufWriter::new);
+
+    let mut written: Result<()> = Ok(());
+    let tally: Tally = tournament.run(&mut |game, tally| {
+        println!("{game}, {first} {tally}");
+        if let (Some(pgn), Ok(())) = (pgn.as_mut(), &written) {
+            written = writeln!(pgn, "{}", game.pgn()).context("Could not write the PGN");
+        }
+    });
+    written?;
+    if let Some(mut pgn) = pgn {
+        pgn.flush()?;
+    }
+
+    println!("{first} vs {second}: {tally}");
+    if let Some(sprt) = sprt {
+        let (lower, upper) = sprt.bounds();
+        let status: &str = match sprt.status(&tally) {
+            SprtStatus::Continue => "inconclusive",
+            SprtStatus::AcceptH0 => "H0 accepted",
+            SprtStatus::AcceptH1 => "H1 accepted",
+        };
+        println!("SPRT LLR {:.2} ({lower:.2}, {upper:.2}) {status}", sprt.llr(&tally));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::protocol::uci::Uci;
+
+/// `chessterm uci`: speaks the Universal Chess Interface on stdin/stdout
+pub(super) fn run(_args: &[String]) -> Result<()> {
+    Uci::new(io::stdout())?.run(io::stdin().lock())
+}
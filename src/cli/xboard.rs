@@ -0,0 +1,10 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::protocol::xboard::XBoard;
+
+/// `chessterm xboard`: speaks the XBoard protocol (CECP v2) on stdin/stdout
+pub(super) fn run(_args: &[String]) -> Result<()> {
+    XBoard::new(io::stdout())?.run(io::stdin().lock())
+}
@@ -0,0 +1,295 @@
+use std::fmt::{self, Display};
+
+use anyhow::{bail, Result};
+
+use crate::bot::rng::Rng;
+use crate::game::{ChessEngine, Result as GameResult};
+use crate::game::board::{Board, COLUMNS, ROWS};
+use crate::game::board::board_builder::BoardBuilder;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::fen_parser::FenParser;
+use crate::game::pieces::Piece;
+use crate::game::pieces::bishop::Bishop;
+use crate::game::pieces::king::King;
+use crate::game::pieces::knight::Knight;
+use crate::game::pieces::piece_kind::PieceKind;
+use crate::game::pieces::queen::Queen;
+use crate::game::pieces::rook::Rook;
+
+// Random boards are mostly legal, this is far more than ever needed
+const MAX_ATTEMPTS: usize = 1_000;
+
+// White wins: 1. Rd1+ Ke7 2. Rd4, building the bridge
+const LUCENA: [&str; 2] = [
+    "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1",
+    "2K5/2P1k3/8/8/8/8/1r6/3R4 w - - 0 1",
+];
+// Black draws by keeping the rook on the sixth rank until the pawn advances
+const PHILIDOR: [&str; 2] = [
+    "4k3/8/1r6/3KP3/8/8/8/7R b - - 0 1",
+    "3k4/8/r7/2KP4/8/8/8/6R1 b - - 0 1",
+];
+
+/// The endgames to practice
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Endgame {
+    KingQueen,
+    KingRook,
+    KingBishopKnight,
+    Lucena,
+    Philidor,
+}
+
+pub(crate) const ENDGAMES: [Endgame; 5] = [
+    Endgame::KingQueen,
+    Endgame::KingRook,
+    Endgame::KingBishopKnight,
+    Endgame::Lucena,
+    Endgame::Philidor,
+];
+
+impl Endgame {
+    pub(crate) const fn name(&self) -> &'static str {
+        match self {
+            Self::KingQueen => "kqk",
+            Self::KingRook => "krk",
+            Self::KingBishopKnight => "kbnk",
+            Self::Lucena => "lucena",
+            Self::Philidor => "philidor",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Result<Self> {
+        match ENDGAMES.into_iter().find(|endgame| endgame.name() == name) {
+            Some(endgame) => Ok(endgame),
+            None => bail!(
+                "Unknown endgame \"{name}\", expected one of {}",
+                ENDGAMES.map(|endgame| endgame.name()).join(", ")
+            ),
+        }
+    }
+
+    /// What the player has to do, within a number of their moves
+    pub(crate) const fn goal(&self) -> Goal {
+        match self {
+            Self::KingQueen => Goal::Mate(10),
+            Self::KingRook => Goal::Mate(16),
+            Self::KingBishopKnight => Goal::Mate(33),
+            Self::Lucena => Goal::Promote(12),
+            Self::Philidor => Goal::Draw(25),
+        }
+    }
+
+    /// A position to practice: random for the basic mates, the strong side
+    /// being white and to move, one of the textbook positions otherwise
+    pub(crate) fn position(&self, rng: &mut Rng) -> ChessEngine {
+        let curated: &[&str] = match self {
+            Self::Lucena => &LUCENA,
+            Self::Philidor => &PHILIDOR,
+            Self::KingQueen => return random_position(rng, &[|position| PieceKind::Queen(Queen::new(position, Color::White))]),
+            Self::KingRook => return random_position(rng, &[|position| PieceKind::Rook(Rook::new(position, Color::White))]),
+            Self::KingBishopKnight => return random_position(
+                rng,
+                &[
+                    |position| PieceKind::Bishop(Bishop::new(position, Color::White)),
+                    |position| PieceKind::Knight(Knight::new(position, Color::White)),
+                ],
+            ),
+        };
+
+        FenParser::parse(curated[rng.below(curated.len())]).expect("The textbook positions are valid")
+    }
+}
+
+impl Display for Endgame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KingQueen => write!(f, "King and queen against king"),
+            Self::KingRook => write!(f, "King and rook against king"),
+            Self::KingBishopKnight => write!(f, "King, bishop and knight against king"),
+            Self::Lucena => write!(f, "Lucena position"),
+            Self::Philidor => write!(f, "Philidor position"),
+        }
+    }
+}
+
+/// The two kings and white's `pieces` on random squares, white to move,
+/// until the board is legal and the game not over yet
+fn random_position(rng: &mut Rng, pieces: &[fn(Position) -> PieceKind]) -> ChessEngine {
+    for _ in 0..MAX_ATTEMPTS {
+        let mut squares: Vec<Position> = Vec::new();
+        while squares.len() < pieces.len() + 2 {
+            let square: Position = (rng.below(ROWS), rng.below(COLUMNS)).into();
+            if !squares.contains(&square) {
+                squares.push(square);
+            }
+        }
+
+        // The kings have moved: no castling from a random board
+        let mut builder: BoardBuilder = BoardBuilder::new()
+            .with(PieceKind::King(King::new(squares[0], Color::White).with_has_moved()))
+            .with(PieceKind::King(King::new(squares[1], Color::Black).with_has_moved()));
+        for (piece, &square) in pieces.iter().zip(&squares[2..]) {
+            builder.add(piece(square));
+        }
+        let board: Board = builder.build();
+
+        if board.is_legal(Color::White) {
+            let chess_game: ChessEngine = ChessEngine::from_board(board, Color::White);
+            if !chess_game.is_end() {
+                return chess_game;
+            }
+        }
+    }
+
+    panic!("No legal position found in {MAX_ATTEMPTS} attempts");
+}
+
+/// What the player has to do, the number being the most moves they can take
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Goal {
+    Mate(usize),
+    // Bringing a pawn to the last row, or mating
+    Promote(usize),
+    // Not being mated nor losing material for that many moves
+    Draw(usize),
+}
+
+impl Goal {
+    /// Where the player stands in `chess_game`, which started from `start`
+    /// with `player` playing
+    pub(crate) fn progress(&self, start: &ChessEngine, chess_game: &ChessEngine, player: Color) -> Progress {
+        let plies: usize = chess_game.history().len() - start.history().len();
+        let played: usize = (plies + usize::from(start.current_player() == player)) / 2;
+        // The deadline is checked once the player has moved
+        let out_of_moves = |moves: usize| played >= moves && chess_game.current_player() != player;
+        let mated: bool = chess_game.result() == GameResult::Checkmate;
+        let player_mated: bool = mated && chess_game.current_player() == player;
+        let drawn: bool = matches!(chess_game.result(), GameResult::Stalemate | GameResult::Draw);
+        let lost_material: bool = chess_game.points(player) < start.points(player);
+
+        match *self {
+            Self::Mate(_) | Self::Promote(_) if mated && !player_mated => Progress::Achieved,
+            Self::Promote(_) if promoted(chess_game, player) => Progress::Achieved,
+            Self::Mate(_) | Self::Promote(_) if drawn => Progress::Failed("the game is drawn"),
+            Self::Mate(_) | Self::Promote(_) if lost_material => Progress::Failed("material was lost"),
+            Self::Mate(moves) | Self::Promote(moves) if out_of_moves(moves) => Progress::Failed("out of moves"),
+            Self::Draw(_) if player_mated => Progress::Failed("checkmated"),
+            Self::Draw(_) if promoted(chess_game, player.other()) => Progress::Failed("the pawn promoted"),
+            Self::Draw(_) if balance(chess_game, player) < balance(start, player) => Progress::Failed("material was lost"),
+            Self::Draw(moves) if drawn || out_of_moves(moves) => Progress::Achieved,
+            _ => Progress::Playing,
+        }
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mate(moves) => write!(f, "mate within {moves} moves"),
+            Self::Promote(moves) => write!(f, "promote within {moves} moves"),
+            Self::Draw(moves) => write!(f, "hold the draw for {moves} moves"),
+        }
+    }
+}
+
+/// Whether a pawn of `color` stands on the last row
+fn promoted(chess_game: &ChessEngine, color: Color) -> bool {
+    let last_row: usize = if color == Color::White { 0 } else { ROWS - 1 };
+
+    chess_game
+        .board()
+        .pieces(color)
+        .iter()
+        .any(|piece| matches!(piece, PieceKind::Pawn(_)) && piece.position().row() == last_row)
+}
+
+/// The material of `player` minus their opponent's
+fn balance(chess_game: &ChessEngine, player: Color) -> i16 {
+    chess_game.points(player) - chess_game.points(player.other())
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Progress {
+    Playing,
+    Achieved,
+    // Why the goal can't be reached anymore
+    Failed(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::rng::Rng;
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::play_moves;
+
+    use super::{Endgame, Goal, Progress, ENDGAMES};
+
+    #[test]
+    fn test_positions_are_legal() {
+        let mut rng: Rng = Rng::new(7);
+
+        for endgame in ENDGAMES {
+            for _ in 0..20 {
+                let chess_game: ChessEngine = endgame.position(&mut rng);
+
+                assert!(chess_game.board().is_legal(chess_game.current_player()), "{endgame}");
+                assert!(!chess_game.is_end(), "{endgame}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_positions_have_their_pieces() {
+        let chess_game: ChessEngine = Endgame::KingBishopKnight.position(&mut Rng::new(3));
+
+        let mut letters: Vec<char> = chess_game.board().pieces(Color::White).iter().map(|piece| piece.letter()).collect();
+        letters.sort_unstable();
+
+        assert_eq!(vec!['B', 'K', 'N'], letters);
+        assert_eq!(1, chess_game.board().pieces(Color::Black).len());
+        assert_eq!(Color::White, chess_game.current_player());
+    }
+
+    #[rstest]
+    // Mate on the back rank
+    #[case("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1", Goal::Mate(1), &["a1a8"], Progress::Achieved)]
+    #[case("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1", Goal::Mate(1), &["a1a2"], Progress::Failed("out of moves"))]
+    #[case("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1", Goal::Mate(2), &["a1a2"], Progress::Playing)]
+    #[case("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1", Goal::Mate(5), &["a1f6"], Progress::Failed("the game is drawn"))]
+    #[case("6k1/8/8/8/8/8/6K1/Q7 w - - 0 1", Goal::Mate(5), &["a1a7", "g8f8", "a7f7", "f8f7"], Progress::Failed("material was lost"))]
+    #[case("1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1", Goal::Promote(12), &["c1d1", "d8e7", "b8c7"], Progress::Playing)]
+    #[case("3k4/1P6/2K5/8/8/8/r7/3R4 w - - 0 1", Goal::Promote(12), &["b7b8"], Progress::Achieved)]
+    #[case("4k3/8/1r6/3KP3/8/8/8/7R b - - 0 1", Goal::Draw(1), &["b6a6"], Progress::Achieved)]
+    #[case("4k3/8/1r6/3KP3/8/8/8/7R b - - 0 1", Goal::Draw(2), &["b6d6", "d5d6"], Progress::Failed("material was lost"))]
+    fn test_progress(
+        #[case]
+        fen: &str,
+        #[case]
+        goal: Goal,
+        #[case]
+        moves: &[&str],
+        #[case]
+        expected: Progress
+    ) -> Result<()> {
+        let start: ChessEngine = FenParser::parse(fen)?;
+        let mut chess_game: ChessEngine = start.clone();
+        play_moves(&mut chess_game, moves)?;
+
+        assert_eq!(expected, goal.progress(&start, &chess_game, start.current_player()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Endgame::Lucena, Endgame::from_name("lucena").unwrap());
+        assert!(Endgame::from_name("kpk").is_err());
+    }
+}
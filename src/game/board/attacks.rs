@@ -0,0 +1,322 @@
+use crate::game::pieces::piece_kind::PieceKind;
+
+use super::Board;
+use super::color::Color;
+use super::move_kind::MoveKind;
+use super::move_struct::Move;
+use super::position::Position;
+
+const ORTHOGONALS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const DIAGONALS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+const KNIGHT_JUMPS: [(isize, isize); 8] = [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)];
+
+/// Who attacks what, computed from the squares rather than from the
+/// possible moves: an attacked square doesn't need to be reachable by a legal
+/// move, and the attacks can be looked at with some pieces lifted off
+impl Board {
+    /// The pieces of `color` attacking `square`, whatever stands on it
+    pub(crate) fn attackers_of(&self, square: Position, color: Color) -> Vec<Position> {
+        self.attackers_without(square, color, &[])
+    }
+
+    pub(crate) fn is_square_attacked(&self, square: Position, color: Color) -> bool {
+        !self.attackers_of(square, color).is_empty()
+    }
+
+    /// The pieces giving check to the king of `color`
+    pub(crate) fn checkers(&self, color: Color) -> Vec<Position> {
+        self
+            .king_position(color)
+            .map(|king| self.attackers_of(king, color.other()))
+            .unwrap_or_default()
+    }
+
+    /// The pieces of `color` that can't leave the line between their king
+    /// and an enemy rook, bishop or queen without exposing the king
+    pub(crate) fn pinned_pieces(&self, color: Color) -> Vec<Position> {
+        let Some(king) = self.king_position(color) else {
+            return Vec::new();
+        };
+        let mut pinned: Vec<Position> = Vec::new();
+
+        for (directions, diagonal) in [(ORTHOGONALS, false), (DIAGONALS, true)] {
+            for direction in directions {
+                let Some(shield) = self.first_piece(king, direction, &[]) else {
+                    continue;
+                };
+                if self.piece(shield, color).is_none() {
+                    continue;
+                }
+                let pinner: Option<&PieceKind> = self
+                    .first_piece(shield, direction, &[])
+                    .and_then(|pinner| self.piece(pinner, color.other()));
+                if pinner.is_some_and(|pinner| slides(pinner, diagonal)) {
+                    pinned.push(shield);
+                }
+            }
+        }
+
+        pinned
+    }
+
+    /// Static exchange evaluation: the material won by `piece_move` in
+    /// pawns, once every capture on its target square has been played out,
+    /// the least valuable attacker first. The pieces lined up behind the
+    /// ones capturing join in (x-rays), the pinned ones only capture along
+    /// their pin. Every quiet move, a pawn's too, scores what the piece
+    /// loses on its new square: 0 when it is safe. Castling scores 0
+    pub(crate) fn see(&self, piece_move: &Move) -> i16 {
+        let (from, to): (Position, Position) = (piece_move.from(), piece_move.to());
+        let Some(moved) = self.piece(from, Color::Any) else {
+            return 0;
+        };
+        let mut lifted: Vec<Position> = vec![from];
+        let victim: i16 = match piece_move.kind() {
+            MoveKind::Attack(_) => self.piece(to, moved.color().other()).map_or(0, |victim| victim.points()),
+            MoveKind::PawnSimpleMove | MoveKind::PawnDoubleMove => 0,
+            MoveKind::EnPassant(victim) => {
+                lifted.push((from.row(), to.column()).into());
+                victim.points()
+            }
+            _ => return 0,
+        };
+
+        let pinned: Vec<Position> = [Color::White, Color::Black]
+            .into_iter()
+            .flat_map(|color| self.pinned_pieces(color))
+            .collect();
+
+        // What each capture wins, if the exchange stopped right after it
+        let mut gains: Vec<i16> = vec![victim];
+        let mut on_square: i16 = moved.points();
+        let mut color: Color = moved.color().other();
+        while let Some(attacker) = self
+            .attackers_without(to, color, &lifted)
+            .into_iter()
+            .filter(|&attacker| {
+                !pinned.contains(&attacker)
+                    || self.king_position(color).is_some_and(|king| direction(king, attacker) == direction(king, to))
+            })
+            .min_by_key(|&attacker| self.piece(attacker, color).map_or(0, |piece| piece.points()))
+        {
+            gains.push(on_square - gains[gains.len() - 1]);
+            on_square = self.piece(attacker, color).map_or(0, |piece| piece.points());
+            lifted.push(attacker);
+            color = color.other();
+        }
+
+        // Each side stops capturing as soon as it stops paying
+        while gains.len() > 1 {
+            let last: i16 = gains.pop().unwrap_or_default();
+            let previous: &mut i16 = gains.last_mut().expect("Two gains at least");
+            *previous = -(-*previous).max(last);
+        }
+
+        gains[0]
+    }
+
+    /// `attackers_of`, the pieces on `lifted` being taken off the board
+    fn attackers_without(&self, square: Position, color: Color, lifted: &[Position]) -> Vec<Position> {
+        let mut attackers: Vec<Position> = Vec::new();
+        let mut attacks = |position: Position, attacks: &dyn Fn(&PieceKind) -> bool| {
+            if !lifted.contains(&position) && self.piece(position, color).is_some_and(attacks) {
+                attackers.push(position);
+            }
+        };
+
+        for (directions, diagonal) in [(ORTHOGONALS, false), (DIAGONALS, true)] {
+            for direction in directions {
+                if let Some(slider) = self.first_piece(square, direction, lifted) {
+                    attacks(slider, &|piece| slides(piece, diagonal));
+                }
+                attacks(square + direction, &|piece| matches!(piece, PieceKind::King(_)));
+            }
+        }
+        for jump in KNIGHT_JUMPS {
+            attacks(square + jump, &|piece| matches!(piece, PieceKind::Knight(_)));
+        }
+        // The pawns attacking `square` stand one row behind it
+        let behind: isize = match color {
+            Color::White => 1,
+            Color::Black => -1,
+            Color::Any => panic!("No pawn can be associated with color \"Any\""),
+        };
+        for side in [-1isize, 1isize] {
+            attacks(square + (behind, side), &|piece| matches!(piece, PieceKind::Pawn(_)));
+        }
+
+        attackers
+    }
+
+    /// The first piece met from `square` in `direction`, the pieces on
+    /// `lifted` being taken off the board
+    fn first_piece(&self, square: Position, direction: (isize, isize), lifted: &[Position]) -> Option<Position> {
+        let mut position: Position = square + direction;
+
+        loop {
+            let square = self.square(position)?;
+            if square.piece(Color::Any).is_some() && !lifted.contains(&position) {
+                return Some(position);
+            }
+            position = position + direction;
+        }
+    }
+}
+
+/// The step leading from `from` towards `to`, when they share a row, a
+/// column or a diagonal
+fn direction(from: Position, to: Position) -> Option<(isize, isize)> {
+    let rows: isize = to.row() as isize - from.row() as isize;
+    let columns: isize = to.column() as isize - from.column() as isize;
+
+    if (rows, columns) == (0, 0) || rows != 0 && columns != 0 && rows.abs() != columns.abs() {
+        None
+    } else {
+        Some((rows.signum(), columns.signum()))
+    }
+}
+
+/// Whether `piece` slides diagonally, or along rows and columns
+const fn slides(piece: &PieceKind, diagonal: bool) -> bool {
+    match piece {
+        PieceKind::Queen(_) => true,
+        PieceKind::Bishop(_) => diagonal,
+        PieceKind::Rook(_) => !diagonal,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::board::move_struct::Move;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    fn squares(notations: &[&str]) -> Vec<Position> {
+        sorted(notations.iter().filter_map(|notation| Position::from_notation(notation)).collect())
+    }
+
+    fn sorted(mut positions: Vec<Position>) -> Vec<Position> {
+        positions.sort_by_key(|square| square.to_notation());
+        positions
+    }
+
+    fn square(notation: &str) -> Position {
+        Position::from_notation(notation).expect("A valid square")
+    }
+
+    #[rstest]
+    #[case("e5", Color::White, &["d4", "f3", "e1"])]
+    #[case("e5", Color::Black, &["d6", "e8"])]
+    #[case("h8", Color::White, &[])]
+    fn test_attackers_of(
+        #[case]
+        target: &str,
+        #[case]
+        color: Color,
+        #[case]
+        expected: &[&str]
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("4r1k1/8/3p4/8/3P4/5N2/8/4R1K1 w - - 0 1")?;
+
+        let attackers: Vec<Position> = sorted(chess_game.board().attackers_of(square(target), color));
+
+        assert_eq!(squares(expected), attackers);
+        assert_eq!(!expected.is_empty(), chess_game.board().is_square_attacked(square(target), color));
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkers() -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("4k3/8/3N4/8/8/8/8/4R1K1 b - - 0 1")?;
+
+        assert_eq!(squares(&["d6", "e1"]), sorted(chess_game.board().checkers(Color::Black)));
+        assert!(chess_game.board().checkers(Color::White).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_pieces() -> Result<()> {
+        // The bishop is blocked by the d-pawn, which shields a pawn only
+        let chess_game: ChessEngine = FenParser::parse("4r1k1/8/8/qb6/8/3P4/3NP3/4K3 w - - 0 1")?;
+
+        assert_eq!(Vec::<Position>::new(), chess_game.board().pinned_pieces(Color::Black));
+        assert_eq!(squares(&["d2", "e2"]), sorted(chess_game.board().pinned_pieces(Color::White)));
+        Ok(())
+    }
+
+    #[rstest]
+    // Pawn takes a defended knight
+    #[case("4k3/8/3p4/4n3/3P4/8/8/4K3 w - - 0 1", "d4", "e5", 3 - 1)]
+    // Rook takes a pawn defended by a pawn
+    #[case("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1", "e1", "e5", 1 - 5)]
+    // The queen behind the rook x-rays: RxP RxR QxR
+    #[case("3rk3/8/8/3p4/8/8/3R4/3QK3 w - - 0 1", "d2", "d5", 1)]
+    // Without the queen the exchange loses the exchange
+    #[case("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2", "d5", 1 - 5)]
+    // Undefended queen
+    #[case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "d1", "d5", 9)]
+    // The knight defending the pawn is pinned
+    #[case("4k3/4n3/8/3p4/8/2N5/8/4R1K1 w - - 0 1", "c3", "d5", 1)]
+    // Pinned along the move, the bishop still takes the queen
+    #[case("7k/6b1/8/8/8/8/8/Q6K w - - 0 1", "a1", "d4", -9)]
+    // En passant
+    #[case("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5", "d6", 1)]
+    fn test_see(
+        #[case]
+        fen: &str,
+        #[case]
+        from: &str,
+        #[case]
+        to: &str,
+        #[case]
+        expected: i16
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let piece_move: &Move = chess_game.legal_move(square(from), square(to)).expect("A legal move");
+
+        assert_eq!(expected, chess_game.board().see(piece_move));
+        Ok(())
+    }
+
+    #[rstest]
+    // Safe squares
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e2", "e4", 0)]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1", "f3", 0)]
+    #[case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", "e1", "e2", 0)]
+    #[case("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1", 0)]
+    // Each piece moving where a pawn takes it
+    #[case("4k3/8/8/3p4/8/4P3/8/4K3 w - - 0 1", "e3", "e4", -1)]
+    #[case("4k3/8/8/3p4/8/8/4P3/4K3 w - - 0 1", "e2", "e4", -1)]
+    #[case("4k3/8/8/8/1p6/8/8/1N2K3 w - - 0 1", "b1", "c3", -3)]
+    #[case("4k3/8/8/3p4/8/8/8/4KB2 w - - 0 1", "f1", "c4", -3)]
+    #[case("4k3/8/6p1/8/8/8/8/3QK3 w - - 0 1", "d1", "h5", -9)]
+    // A rook where a knight takes it
+    #[case("4k3/1n6/8/8/8/8/8/R3K3 w - - 0 1", "a1", "a5", -5)]
+    // Defended, the knight is traded for the pawn
+    #[case("4k3/8/8/8/1p6/8/3P4/1N2K3 w - - 0 1", "b1", "c3", -2)]
+    fn test_see_quiet_move(
+        #[case]
+        fen: &str,
+        #[case]
+        from: &str,
+        #[case]
+        to: &str,
+        #[case]
+        expected: i16
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let piece_move: &Move = chess_game.legal_move(square(from), square(to)).expect("A legal move");
+
+        assert_eq!(expected, chess_game.board().see(piece_move));
+        Ok(())
+    }
+}
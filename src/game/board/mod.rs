@@ -19,6 +19,7 @@ use crate::game::pieces::rook::Rook;
 
 use super::pieces::Piece;
 
+mod attacks;
 pub mod board_builder;
 pub(crate) mod color;
 pub(crate) mod move_struct;
@@ -103,7 +104,7 @@ impl Board {
         self.squares.get_mut(position.to_index()?)
     }
 
-    pub(super) fn piece(&self, position: Position, color: Color) -> Option<&PieceKind> {
+    pub(crate) fn piece(&self, position: Position, color: Color) -> Option<&PieceKind> {
         self.square(position)?.piece(color)
     }
 
@@ -184,6 +185,8 @@ impl Board {
                     rook.set_has_moved();
                 } else if let PieceKind::King(ref mut king) = piece_from {
                     king.set_has_moved();
+                } else if let PieceKind::Pawn(ref mut pawn) = piece_from {
+                    pawn.set_has_moved();
                 };
 
                 piece_from.set_position(to);
@@ -346,14 +349,24 @@ impl Board {
     }
 
     pub fn checked(&self, color: Color) -> bool {
-        if let Some(king_position) = self.king_position(color) {
-            self
-                .possible_moves(color.other())
-                .iter()
-                .any(|m| m.to() == king_position)
-        } else {
-            false
-        }
+        self
+            .king_position(color)
+            .is_some_and(|king_position| self.is_square_attacked(king_position, color.other()))
+    }
+
+    /// Whether a game could reach the board with `to_move` to move: one
+    /// king each, no pawn on the first or last row, and the side that just
+    /// moved not left in check
+    pub(crate) fn is_legal(&self, to_move: Color) -> bool {
+        let one_king: bool = [Color::White, Color::Black].into_iter().all(|color| {
+            self.pieces(color).iter().filter(|piece| matches!(piece, PieceKind::King(_))).count() == 1
+        });
+        let pawns_on_edges: bool = self
+            .pieces(Color::Any)
+            .iter()
+            .any(|piece| matches!(piece, PieceKind::Pawn(_)) && (piece.position().row() == 0 || piece.position().row() == ROWS - 1));
+
+        one_king && !pawns_on_edges && !self.checked(to_move.other())
     }
 }
 
@@ -369,12 +382,14 @@ mod tests {
     use rstest::rstest;
     use std::collections::HashSet;
 
+    use crate::game::ChessEngine;
     use crate::game::board::board_builder::BoardBuilder;
     use crate::game::board::color::Color;
     use crate::game::board::move_kind::MoveKind;
     use crate::game::board::move_struct::Move;
     use crate::game::board::position::Position;
     use crate::game::board::square::Square;
+    use crate::game::fen_parser::FenParser;
     use crate::game::pieces::Piece;
     use crate::game::pieces::bishop::Bishop;
     use crate::game::pieces::king::King;
@@ -515,6 +530,23 @@ mod tests {
         assert_eq!(expected, board);
     }
 
+    #[test]
+    fn test_make_move_pawn_capture() {
+        let bishop: PieceKind = PieceKind::Bishop(Bishop::new((2isize, 3isize).into(), Color::White));
+        let tested_move: Move = Move::new((1isize, 2isize).into(), (2isize, 3isize).into(), MoveKind::Attack(Some(bishop)));
+        let mut board: Board = BoardBuilder::new()
+            .with(PieceKind::Pawn(Pawn::new((1isize, 2isize).into(), Color::Black)))
+            .with(bishop)
+            .build();
+        let expected: Board = BoardBuilder::new()
+            .with(PieceKind::Pawn(Pawn::new((2isize, 3isize).into(), Color::Black).with_has_moved()))
+            .build();
+
+        board.make_move(&tested_move, Color::Black);
+
+        assert_eq!(expected, board);
+    }
+
     #[test]
     fn test_make_move_pawn_move() {
         let tested_move: Move = Move::new((1isize, 2isize).into(), (2isize, 2isize).into(), MoveKind::PawnSimpleMove);
@@ -734,4 +766,27 @@ mod tests {
 
         assert!(!board.checked(Color::Black));
     }
+
+    #[rstest]
+    #[case("4k3/8/8/8/8/8/8/4K2R w - - 0 1", Color::White, true)]
+    #[case("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1", Color::Black, true)]
+    // The side that just moved is in check
+    #[case("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1", Color::White, false)]
+    // Kings side by side
+    #[case("8/8/8/8/8/8/3k4/4K3 w - - 0 1", Color::White, false)]
+    #[case("4k3/8/8/8/8/8/8/R7 w - - 0 1", Color::White, false)]
+    #[case("4k1P1/8/8/8/8/8/8/4K3 b - - 0 1", Color::Black, false)]
+    fn test_is_legal(
+        #[case]
+        fen: &str,
+        #[case]
+        to_move: Color,
+        #[case]
+        expected: bool
+    ) -> anyhow::Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        assert_eq!(expected, chess_game.board().is_legal(to_move));
+        Ok(())
+    }
 }
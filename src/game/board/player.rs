@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use super::color::Color;
 use super::move_kind::MoveKind;
@@ -8,22 +9,23 @@ use super::position::Position;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct Player {
     color: Color,
-    possible_moves: HashSet<Move>,
+    // Replaced after every move, shared by the clones until then
+    possible_moves: Arc<HashSet<Move>>,
 }
 
 impl Player {
     pub(super) fn new(color: Color) -> Self {
         Self {
             color,
-            possible_moves: HashSet::new(),
+            possible_moves: Arc::new(HashSet::new()),
         }
     }
 
     pub(super) fn set_possible_moves(&mut self, possible_moves: HashSet<Move>) {
-        self.possible_moves = possible_moves;
+        self.possible_moves = Arc::new(possible_moves);
     }
 
-    pub(crate) const fn possible_moves(&self) -> &HashSet<Move> {
+    pub(crate) fn possible_moves(&self) -> &HashSet<Move> {
         &self.possible_moves
     }
 
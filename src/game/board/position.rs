@@ -56,6 +56,13 @@ impl Position {
 
         Some((row, column).into())
     }
+
+    pub(crate) fn to_notation(self) -> String {
+        let column: char = (b'a' + self.column() as u8) as char;
+        let row: usize = ROWS - self.row();
+
+        format!("{column}{row}")
+    }
 }
 
 impl From<(usize, usize)> for Position {
@@ -135,6 +142,21 @@ mod tests {
         assert_eq!(expected, position);
     }
 
+    #[rstest]
+    #[case((7isize, 1isize).into(), "b1")]
+    #[case((4isize, 4isize).into(), "e4")]
+    #[case((0isize, 7isize).into(), "h8")]
+    fn test_to_notation(
+        #[case]
+        position: Position,
+        #[case]
+        expected: &str
+    ) {
+        let notation = position.to_notation();
+
+        assert_eq!(expected, notation);
+    }
+
     #[test]
     fn test_position_add_position() {
         let position1: Position = (2isize, 3isize).into();
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::OnceLock;
+
+use crate::game::ChessEngine;
+use crate::game::fen_writer::FenWriter;
+use crate::game::pgn::read_game;
+
+// One opening per line: its ECO code, its name and its moves in SAN
+const TABLE: &str = include_str!("eco.tsv");
+
+/// A named opening of the Encyclopaedia of Chess Openings
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Opening {
+    code: &'static str,
+    name: &'static str,
+}
+
+impl Opening {
+    pub(crate) fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Display for Opening {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.code, self.name)
+    }
+}
+
+/// The openings by the key of the position they reach, so that the move
+/// orders transposing into them are recognized too
+fn openings() -> &'static HashMap<u64, Opening> {
+    static OPENINGS: OnceLock<HashMap<u64, Opening>> = OnceLock::new();
+
+    OPENINGS.get_or_init(|| {
+        let mut openings: HashMap<u64, Opening> = HashMap::new();
+        for line in TABLE.lines().filter(|line| !line.is_empty()) {
+            let mut fields = line.split('\t');
+            let (Some(code), Some(name), Some(moves)) = (fields.next(), fields.next(), fields.next()) else {
+                panic!("Malformed ECO line: {line}");
+            };
+            let chess_game: ChessEngine = read_game(moves).unwrap_or_else(|e| panic!("Illegal ECO line {line}: {e}"));
+            // The first line naming a position wins
+            openings.entry(chess_game.key()).or_insert(Opening { code, name });
+        }
+
+        openings
+    })
+}
+
+/// The last opening reached in `chess_game`, which stays named once the
+/// game leaves the table. None for games not played from the start
+pub(crate) fn classify(chess_game: &ChessEngine) -> Option<Opening> {
+    let mut replay: ChessEngine = chess_game.starting_position();
+    if FenWriter::write(&replay) != FenWriter::write(&ChessEngine::new()) {
+        return None;
+    }
+
+    let mut opening: Option<Opening> = None;
+    for played in chess_game.history() {
+        replay.try_move(Some(played.0), Some(played.1));
+        opening = openings().get(&replay.key()).copied().or(opening);
+    }
+
+    opening
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+    use crate::game::pgn::read_game;
+    use crate::protocol::play_moves;
+
+    use super::{classify, openings, TABLE};
+
+    #[rstest]
+    #[case("1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6", Some("B90 Sicilian Defense: Najdorf Variation"))]
+    #[case("1. e4 e5 2. Nf3 Nf6", Some("C42 Russian Game"))]
+    #[case("1. d4 d5 2. c4 c6 3. Nf3 Nf6 4. Nc3 e6", Some("D43 Semi-Slav Defense"))]
+    #[case(
+        "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 O-O 8. c3 d5",
+        Some("C89 Ruy Lopez: Marshall Attack")
+    )]
+    // Transpositions
+    #[case("1. c4 e6 2. Nc3 Nf6 3. d4 Bb4", Some("E20 Nimzo-Indian Defense"))]
+    #[case("1. c4 g6 2. Nc3 Bg7 3. d4 Nf6", Some("E61 King's Indian Defense"))]
+    // Out of the table, the last opening reached
+    #[case("1. e4 e5 2. Nf3 Nf6 3. h3 h6 4. a3", Some("C42 Russian Game"))]
+    #[case("1. a3", None)]
+    #[case("", None)]
+    fn test_classify(
+        #[case] moves: &str,
+        #[case] expected: Option<&str>,
+    ) -> Result<()> {
+        let chess_game: ChessEngine = read_game(moves)?;
+
+        assert_eq!(classify(&chess_game).map(|opening| opening.to_string()).as_deref(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_fen() -> Result<()> {
+        let mut chess_game: ChessEngine = FenParser::parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w Qkq - 0 1")?;
+        play_moves(&mut chess_game, &["e2e4"])?;
+
+        assert_eq!(classify(&chess_game), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table() {
+        let lines: usize = TABLE.lines().filter(|line| !line.is_empty()).count();
+
+        assert!(openings().len() > 100);
+        assert!(openings().len() <= lines);
+        assert!(openings().values().all(|opening| opening.code().len() == 3 && !opening.name().is_empty()));
+    }
+}
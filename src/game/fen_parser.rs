@@ -20,7 +20,9 @@ pub struct FenParser;
 impl FenParser {
     pub(crate) fn parse(fen: &str) -> Result<ChessEngine> {
         let parts: Vec<&str> = fen.split_whitespace().collect();
-        assert_eq!(6, parts.len());
+        if parts.len() != 6 {
+            bail!("A FEN should have 6 parts, found {}: \"{fen}\"", parts.len());
+        }
 
         let starting_player: Color = FenParser::part2(parts[1])?;
         let en_passantable: Option<Position> = FenParser::part4(parts[3]);
@@ -126,12 +128,10 @@ impl FenParser {
     }
 
     fn part2(part: &str) -> Result<Color> {
-        assert_eq!(1, part.len());
-
-        match part.chars().next().expect("Unknown characters in FEN part2") {
-            'w' => Ok(Color::White),
-            'b' => Ok(Color::Black),
-            c => bail!("Unknown character in FEN part2: \'{c}\'"),
+        match part {
+            "w" => Ok(Color::White),
+            "b" => Ok(Color::Black),
+            _ => bail!("Unknown FEN part2: \"{part}\""),
         }
     }
 
@@ -142,32 +142,27 @@ impl FenParser {
         }
 
         let castles: Vec<char> = part.chars().collect();
-        println!("castles: {castles:?}");
 
         if no_castle || !castles.contains(&'K') {
             if let Some(PieceKind::Rook(rook)) = board.king_side_rook_mut(Color::White) {
-                println!("No king side castle for white");
                 rook.set_has_moved();
             };
         }
 
         if no_castle || !castles.contains(&'k') {
             if let Some(PieceKind::Rook(rook)) = board.king_side_rook_mut(Color::Black) {
-                println!("No king side castle for black");
                 rook.set_has_moved();
             };
         }
 
         if no_castle || !castles.contains(&'Q') {
             if let Some(PieceKind::Rook(rook)) = board.queen_side_rook_mut(Color::White) {
-                println!("No queen side castle for white");
                 rook.set_has_moved();
             };
         }
 
         if no_castle || !castles.contains(&'q') {
             if let Some(PieceKind::Rook(rook)) = board.queen_side_rook_mut(Color::Black) {
-                println!("No queen side castle for black");
                 rook.set_has_moved();
             };
         }
@@ -204,6 +199,15 @@ mod tests {
 
     use super::FenParser;
 
+    #[test]
+    fn test_from_fen_missing_parts() {
+        let fen: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+
+        let chess_game: Result<ChessEngine> = FenParser::parse(fen);
+
+        assert!(chess_game.is_err());
+    }
+
     #[test]
     fn test_from_fen_1() -> Result<()> {
         let fen: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
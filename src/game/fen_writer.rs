@@ -0,0 +1,139 @@
+use crate::game::board::{Board, COLUMNS, ROWS};
+use crate::game::board::color::Color;
+use crate::game::pieces::Piece;
+use crate::game::pieces::piece_kind::PieceKind;
+
+use super::ChessEngine;
+
+/// The reverse of `FenParser`. The engine doesn't count the moves without
+/// captures, so the halfmove clock is always 0
+pub(crate) struct FenWriter;
+
+impl FenWriter {
+    pub(crate) fn write(chess_game: &ChessEngine) -> String {
+        let board: &Board = chess_game.board();
+        let player: &str = match chess_game.current_player() {
+            Color::White => "w",
+            _ => "b",
+        };
+        let fullmove: usize = 1 + chess_game.history().len() / 2;
+
+        format!(
+            "{} {player} {} {} 0 {fullmove}",
+            FenWriter::placement(board),
+            FenWriter::castling(board),
+            FenWriter::en_passant(board, chess_game.current_player()),
+        )
+    }
+
+    fn placement(board: &Board) -> String {
+        let mut rows: Vec<String> = Vec::new();
+
+        for row in 0..ROWS {
+            let mut text: String = String::new();
+            let mut empty: u32 = 0;
+
+            for column in 0..COLUMNS {
+                match board.piece((row, column).into(), Color::Any) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            text.push(char::from_digit(empty, 10).expect("At most 8 empty squares"));
+                            empty = 0;
+                        }
+                        text.push(letter(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                text.push(char::from_digit(empty, 10).expect("At most 8 empty squares"));
+            }
+
+            rows.push(text);
+        }
+
+        rows.join("/")
+    }
+
+    fn castling(board: &Board) -> String {
+        let mut castling: String = String::new();
+
+        for (color, king_side, queen_side) in [(Color::White, 'K', 'Q'), (Color::Black, 'k', 'q')] {
+            let Some(PieceKind::King(king)) = board.king(color) else {
+                continue;
+            };
+            if king.has_moved() || king.position() != king.original_position() {
+                continue;
+            }
+
+            let unmoved_rook = |position| matches!(board.piece(position, color), Some(PieceKind::Rook(rook)) if !rook.has_moved());
+            if unmoved_rook(king.king_side_castling_rook_position()) {
+                castling.push(king_side);
+            }
+            if unmoved_rook(king.queen_side_castling_rook_position()) {
+                castling.push(queen_side);
+            }
+        }
+
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        castling
+    }
+
+    /// The square behind the pawn of the opponent that just moved two squares
+    fn en_passant(board: &Board, current_player: Color) -> String {
+        board
+            .pieces(current_player.other())
+            .into_iter()
+            .find_map(|piece| match piece {
+                PieceKind::Pawn(pawn) if pawn.en_passant_possible() => Some((pawn.position() - pawn.direction()).to_notation()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+fn letter(piece: &PieceKind) -> char {
+    match piece.color() {
+        Color::White => piece.letter(),
+        _ => piece.letter().to_ascii_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::FenWriter;
+
+    #[rstest]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[case("r1bk3r/p2pBpNp/n4n2/1p1NP2P/6P1/3P4/P1P1K3/q5b1 b - - 0 1")]
+    #[case("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1")]
+    #[case("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")]
+    fn test_write_parsed(
+        #[case]
+        fen: &str
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        assert_eq!(fen, FenWriter::write(&chess_game));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_after_moves() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        chess_game.try_move(Position::from_notation("g1"), Position::from_notation("f3"));
+        chess_game.try_move(Position::from_notation("g8"), Position::from_notation("f6"));
+
+        assert_eq!("rnbqkb1r/pppppppp/5n2/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 0 2", FenWriter::write(&chess_game));
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+/// The moves or the positions of a game, pushed and popped at the end only.
+/// `fork` shares them with a copy instead of copying them: the copies keep
+/// them in common until one pops them
+#[derive(Clone, Debug)]
+pub(crate) struct History<T> {
+    // Up to the last fork, shared with the copies
+    shared: Arc<Vec<T>>,
+    own: Vec<T>,
+}
+
+impl<T: Clone> History<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            shared: Arc::new(Vec::new()),
+            own: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.own.push(item);
+    }
+
+    /// Copies the shared elements first when a copy still has them
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.own.pop().or_else(|| Arc::make_mut(&mut self.shared).pop())
+    }
+
+    pub(crate) fn last(&self) -> Option<&T> {
+        self.own.last().or_else(|| self.shared.last())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.shared.is_empty() && self.own.is_empty()
+    }
+
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.shared.iter().chain(&self.own)
+    }
+
+    /// A copy sharing the elements: only the ones pushed since the last fork
+    /// are moved, once, to the shared part
+    pub(crate) fn fork(&mut self) -> Self {
+        if !self.own.is_empty() {
+            Arc::make_mut(&mut self.shared).append(&mut self.own);
+        }
+
+        Self {
+            shared: Arc::clone(&self.shared),
+            own: Vec::new(),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for History<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shared.iter().chain(&self.own).eq(other.shared.iter().chain(&other.own))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use super::History;
+
+    fn history(items: &[u64]) -> History<u64> {
+        let mut history: History<u64> = History::new();
+        for &item in items {
+            history.push(item);
+        }
+
+        history
+    }
+
+    #[test]
+    fn test_fork_shares() {
+        let mut history: History<u64> = history(&[1, 2, 3]);
+
+        let mut fork: History<u64> = history.fork();
+        assert!(Arc::ptr_eq(&history.shared, &fork.shared));
+        assert_eq!(history, fork);
+
+        // Each side goes its own way
+        fork.push(4);
+        assert_eq!(Some(3), history.pop());
+        assert_eq!(vec![1, 2], history.iter().copied().collect::<Vec<u64>>());
+        assert_eq!(vec![1, 2, 3, 4], fork.iter().copied().collect::<Vec<u64>>());
+        assert_eq!(Some(&4), fork.last());
+    }
+
+    #[test]
+    fn test_pop_to_empty() {
+        let mut history: History<u64> = history(&[1]);
+        let _fork: History<u64> = history.fork();
+        history.push(2);
+
+        assert_eq!(Some(2), history.pop());
+        assert_eq!(Some(1), history.pop());
+        assert_eq!(None, history.pop());
+        assert!(history.is_empty());
+        assert_eq!(None, history.last());
+    }
+
+    #[test]
+    fn test_eq_across_the_split() {
+        let mut forked: History<u64> = history(&[1, 2]);
+        forked.fork();
+        forked.push(3);
+
+        assert_eq!(history(&[1, 2, 3]), forked);
+        assert!(history(&[1, 2]) != forked);
+    }
+}
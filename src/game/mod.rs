@@ -1,14 +1,21 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
 
 use board::Board;
 use board::color::Color;
 use board::move_struct::Move;
 use board::position::Position;
 use board::square::Square;
+use history::History;
 use pieces::piece_kind::PieceKind;
 
 pub mod fen_parser;
+pub(crate) mod eco;
+pub(crate) mod fen_writer;
+pub(crate) mod history;
+pub(crate) mod pgn;
+pub(crate) mod san;
 pub(super) mod board;
 pub(super) mod pieces;
 
@@ -24,10 +31,11 @@ pub enum Result {
 pub struct ChessEngine {
     board: Board,
     current_player: Color,
-    possible_moves: HashMap<Position, HashSet<Move>>,
+    // Replaced after every move, shared by the clones until then
+    possible_moves: Arc<HashMap<Position, HashSet<Move>>>,
     result: Result,
-    moves: VecDeque<(Move, PieceKind)>,
-    positions: Vec<u64>,
+    moves: History<(Move, PieceKind)>,
+    positions: History<u64>,
 }
 
 impl ChessEngine {
@@ -44,10 +52,10 @@ impl ChessEngine {
         let mut chess_engine = Self {
             board,
             current_player: starting_player,
-            possible_moves: HashMap::new(),
+            possible_moves: Arc::new(HashMap::new()),
             result: Result::None,
-            moves: VecDeque::new(),
-            positions: Vec::new(),
+            moves: History::new(),
+            positions: History::new(),
         };
 
         chess_engine.store_hash();
@@ -55,6 +63,10 @@ impl ChessEngine {
         chess_engine
     }
 
+    pub(crate) fn board(&self) -> &Board {
+        &self.board
+    }
+
     pub fn current_player(&self) -> Color {
         self.current_player
     }
@@ -99,7 +111,7 @@ impl ChessEngine {
                 return false;
             };
 
-        self.moves.push_back((try_move.clone(), *piece_moved));
+        self.moves.push((try_move.clone(), *piece_moved));
         self.board.make_move(try_move, self.current_player);
         let last_hash: u64 = self.store_hash();
         self.next_turn();
@@ -112,13 +124,78 @@ impl ChessEngine {
     }
 
     pub fn undo_move(&mut self) {
-        let (last_move, last_piece_moved): (Move, PieceKind) = self.moves.pop_back().expect("Can't undo a move if no moves happened");
+        let (last_move, last_piece_moved): (Move, PieceKind) = self.moves.pop().expect("Can't undo a move if no moves happened");
         self.positions.pop();
         self.result = Result::None;
         self.board.undo_move(last_move, last_piece_moved);
         self.next_turn();
     }
 
+    /// Gives the turn to the other player without moving a piece
+    pub(crate) fn make_null_move(&mut self) {
+        assert!(!self.is_end(), "Can't pass the turn once the game is over");
+
+        self.current_player = self.current_player.other();
+        self.set_possible_moves();
+    }
+
+    pub(crate) fn undo_null_move(&mut self) {
+        self.result = Result::None;
+        self.current_player = self.current_player.other();
+        self.set_possible_moves();
+    }
+
+    pub(crate) fn legal_move(&self, from: Position, to: Position) -> Option<&Move> {
+        self
+            .possible_moves
+            .get(&from)?
+            .iter()
+            .find(|m| m.to() == to)
+    }
+
+    /// The moves played since the game was created
+    pub(crate) fn history(&self) -> Vec<(Position, Position)> {
+        self
+            .moves
+            .iter()
+            .map(|(played, _)| (played.from(), played.to()))
+            .collect()
+    }
+
+    /// A copy for another thread: the board is copied, the moves and
+    /// positions played so far are shared with `self` instead
+    pub(crate) fn fork(&mut self) -> Self {
+        Self {
+            board: self.board.clone(),
+            current_player: self.current_player,
+            possible_moves: Arc::clone(&self.possible_moves),
+            result: self.result,
+            moves: self.moves.fork(),
+            positions: self.positions.fork(),
+        }
+    }
+
+    /// The position the game was created from
+    pub(crate) fn starting_position(&self) -> Self {
+        let mut chess_game: Self = self.clone();
+        while !chess_game.moves.is_empty() {
+            chess_game.undo_move();
+        }
+
+        chess_game
+    }
+
+    /// Identifies the position and the player to move, for transposition
+    /// tables
+    pub(crate) fn key(&self) -> u64 {
+        let hash: u64 = *self.positions.last().expect("The starting position is always hashed");
+
+        match self.current_player {
+            Color::Black => !hash,
+            _ => hash,
+        }
+    }
+
     fn store_hash(&mut self) -> u64 {
         let mut hasher: DefaultHasher = DefaultHasher::new();
         hasher.write_u64(0);
@@ -143,8 +220,13 @@ impl ChessEngine {
         }
     }
 
+    /// The pieces giving check to the side to move
+    pub(crate) fn checkers(&self) -> Vec<Position> {
+        self.board.checkers(self.current_player)
+    }
+
     pub fn set_possible_moves(&mut self) {
-        self.possible_moves = self
+        self.possible_moves = Arc::new(self
             .board
             .pieces(self.current_player)
             .iter()
@@ -153,7 +235,7 @@ impl ChessEngine {
                 self.filter_check_block(&mut possible_moves);
                 (piece.position(), possible_moves)
             })
-        .collect();
+        .collect());
 
         if self.possible_moves.iter().all(|(_, possible_moves)| possible_moves.is_empty()) {
             if self.board.checked(self.current_player) {
@@ -167,10 +249,9 @@ impl ChessEngine {
     fn filter_check_block(&self, possible_moves: &mut HashSet<Move>) {
         possible_moves
             .retain(|simulated_move| {
-                let mut simulated_board: Board = self
+                let simulated_board: Board = self
                     .board
                     .simulate_move(simulated_move, self.current_player);
-                simulated_board.set_possible_moves(self.current_player.other());
                 !simulated_board
                     .checked(self.current_player)
             });
@@ -485,6 +566,37 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_make_undo_null_move() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let expected: ChessEngine = chess_game.clone();
+
+        chess_game.make_null_move();
+        assert_eq!(Color::Black, chess_game.current_player());
+        chess_game.undo_null_move();
+
+        assert_eq!(expected, chess_game);
+    }
+
+    #[test]
+    fn test_can_be_sent_to_search_threads() {
+        fn assert_send<T: Clone + Send + Sync>() {}
+
+        assert_send::<ChessEngine>();
+    }
+
+    #[test]
+    fn test_key_depends_on_player() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let key: u64 = chess_game.key();
+
+        chess_game.make_null_move();
+        assert_ne!(key, chess_game.key());
+        chess_game.undo_null_move();
+
+        assert_eq!(key, chess_game.key());
+    }
+
     #[test]
     fn test_result_stalemate() {
         let board: Board = BoardBuilder::new()
@@ -548,4 +660,24 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_fork() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        chess_game.try_move(Some((6isize, 4isize).into()), Some((4isize, 4isize).into()));
+        chess_game.try_move(Some((1isize, 4isize).into()), Some((3isize, 4isize).into()));
+
+        let mut fork: ChessEngine = chess_game.fork();
+        assert_eq!(chess_game, fork);
+
+        // Each game goes its own way, back to the start too
+        fork.try_move(Some((7isize, 6isize).into()), Some((5isize, 5isize).into()));
+        chess_game.undo_move();
+        chess_game.undo_move();
+        assert_eq!(ChessEngine::new(), chess_game);
+        assert_eq!(3, fork.history().len());
+        fork.undo_move();
+        assert_eq!(2, fork.history().len());
+        assert_eq!(Color::White, fork.current_player());
+    }
 }
@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::eco::classify;
+use crate::game::fen_parser::FenParser;
+use crate::game::fen_writer::FenWriter;
+use crate::game::san::{from_san, to_san};
+
+const LINE_WIDTH: usize = 80;
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// What is written after a move: a suffix like "?!", a comment and a
+/// variation, in SAN with its move numbers
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Annotation {
+    pub(crate) suffix: &'static str,
+    pub(crate) comment: Option<String>,
+    pub(crate) variation: Option<String>,
+}
+
+/// A game in Portable Game Notation, with the seven mandatory tags first
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Pgn {
+    tags: Vec<(String, String)>,
+    moves: Vec<String>,
+    first_player: Color,
+    // Before the first move
+    comment: Option<String>,
+    // By ply
+    annotations: HashMap<usize, Annotation>,
+}
+
+impl Pgn {
+    /// The moves played in `chess_game`, from the position it was created from
+    pub(crate) fn new(chess_game: &ChessEngine) -> Self {
+        let mut replay: ChessEngine = chess_game.starting_position();
+        let first_player: Color = replay.current_player();
+        let mut moves: Vec<String> = Vec::new();
+
+        for played in chess_game.history() {
+            moves.push(to_san(&replay, played).expect("The moves of a game are legal"));
+            replay.try_move(Some(played.0), Some(played.1));
+        }
+
+        let mut pgn: Self = Self {
+            tags: Vec::new(),
+            moves,
+            first_player,
+            comment: None,
+            annotations: HashMap::new(),
+        };
+        for name in ["Event", "Site", "Round", "White", "Black"] {
+            pgn = pgn.with_tag(name, "?");
+        }
+        pgn = pgn.with_tag("Date", &today()).with_tag("Result", "*");
+
+        let fen: String = FenWriter::write(&replay.starting_position());
+        if fen != FenWriter::write(&ChessEngine::new()) {
+            pgn = pgn.with_tag("SetUp", "1").with_tag("FEN", &fen);
+        }
+        if let Some(opening) = classify(chess_game) {
+            pgn = pgn.with_tag("ECO", opening.code()).with_tag("Opening", opening.name());
+        }
+
+        pgn
+    }
+
+    /// Adds the tag, or replaces its value
+    pub(crate) fn with_tag(mut self, name: &str, value: &str) -> Self {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// A comment on the whole game, written before the first move
+    pub(crate) fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Annotates the move played at `ply`, counted from the first move
+    pub(crate) fn with_annotation(mut self, ply: usize, annotation: Annotation) -> Self {
+        self.annotations.insert(ply, annotation);
+        self
+    }
+
+    pub(crate) fn tag(&self, name: &str) -> Option<&str> {
+        self
+            .tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn movetext(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = self.comment.iter().flat_map(|comment| words(&format!("{{{comment}}}"))).collect();
+        let black_first: bool = self.first_player == Color::Black;
+        // Black moves are numbered too after a comment or a variation
+        let mut numbered: bool = black_first;
+
+        // Move numbers stay on the line of their move
+        for (ply, san) in self.moves.iter().enumerate() {
+            let number: usize = (ply + usize::from(black_first)) / 2 + 1;
+            let annotation: Option<&Annotation> = self.annotations.get(&ply);
+            let suffix: &str = annotation.map_or("", |annotation| annotation.suffix);
+            if (ply + usize::from(black_first)).is_multiple_of(2) {
+                tokens.push(format!("{number}. {san}{suffix}"));
+            } else if numbered {
+                tokens.push(format!("{number}... {san}{suffix}"));
+            } else {
+                tokens.push(format!("{san}{suffix}"));
+            }
+
+            numbered = false;
+            if let Some(annotation) = annotation {
+                if let Some(comment) = &annotation.comment {
+                    tokens.extend(words(&format!("{{{comment}}}")));
+                    numbered = true;
+                }
+                if let Some(variation) = &annotation.variation {
+                    tokens.extend(words(&format!("({variation})")));
+                    numbered = true;
+                }
+            }
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+
+        let mut lines: Vec<String> = vec![String::new()];
+        for token in tokens {
+            let line: &mut String = lines.last_mut().expect("There is always a line");
+            if line.is_empty() {
+                *line = token;
+            } else if line.len() + 1 + token.len() <= LINE_WIDTH {
+                line.push(' ');
+                line.push_str(&token);
+            } else {
+                lines.push(token);
+            }
+        }
+
+        lines
+    }
+}
+
+impl Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{name} \"{}\"]", value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+        for line in self.movetext() {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The words of `text`, the move numbers kept with their move
+fn words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        match words.last_mut() {
+            Some(last) if last.ends_with('.') => {
+                last.push(' ');
+                last.push_str(word);
+            }
+            _ => words.push(word.to_string()),
+        }
+    }
+
+    words
+}
+
+/// Replays the main line of the first game in `text`, from the position of
+/// its FEN tag or from the start. Comments, variations and numeric
+/// annotation glyphs are skipped
+pub(crate) fn read_game(text: &str) -> Result<ChessEngine> {
+    let Some((mut chess_game, movetext)) = games(text)?.into_iter().next() else {
+        return Ok(ChessEngine::new());
+    };
+
+    let mut depth: usize = 0;
+    for token in tokens(&movetext) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth = depth.saturating_sub(1),
+            Token::Move(san) if depth == 0 => play_san(&mut chess_game, &san)?,
+            Token::Move(_) => {}
+            Token::Result => break,
+        }
+    }
+
+    Ok(chess_game)
+}
+
+/// Every line of every game in `text`, the main lines and the variations,
+/// each replayed to its last move
+pub(crate) fn read_lines(text: &str) -> Result<Vec<ChessEngine>> {
+    let mut lines: Vec<ChessEngine> = Vec::new();
+
+    for (mut chess_game, movetext) in games(text)? {
+        // The lines left to go back to at the end of each variation
+        let mut parents: Vec<ChessEngine> = Vec::new();
+        for token in tokens(&movetext) {
+            match token {
+                Token::Move(san) => play_san(&mut chess_game, &san)?,
+                // A variation replaces the move before it
+                Token::Open => {
+                    parents.push(chess_game.clone());
+                    if chess_game.history().is_empty() {
+                        bail!("A variation needs a move to replace");
+                    }
+                    chess_game.undo_move();
+                }
+                Token::Close => {
+                    let parent: ChessEngine = parents.pop().context("A variation is closed but never opened")?;
+                    lines.push(std::mem::replace(&mut chess_game, parent));
+                }
+                // Another game may follow without tags
+                Token::Result => {
+                    lines.push(std::mem::replace(&mut chess_game, ChessEngine::new()));
+                    parents.clear();
+                }
+            }
+        }
+        if !chess_game.history().is_empty() {
+            lines.push(chess_game);
+        }
+    }
+
+    Ok(lines)
+}
+
+fn play_san(chess_game: &mut ChessEngine, san: &str) -> Result<()> {
+    let Some((from, to)) = from_san(chess_game, san) else {
+        bail!("Illegal move \"{san}\" after {} plies", chess_game.history().len());
+    };
+    chess_game.try_move(Some(from), Some(to));
+
+    Ok(())
+}
+
+/// The movetext of each game in `text`, with the position of its FEN tag or
+/// the starting position
+fn games(text: &str) -> Result<Vec<(ChessEngine, String)>> {
+    let mut games: Vec<(ChessEngine, String)> = Vec::new();
+    let mut chess_game: ChessEngine = ChessEngine::new();
+    let mut movetext: String = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(tag) = line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) {
+            if !movetext.trim().is_empty() {
+                // The tags of the next game
+                games.push((std::mem::replace(&mut chess_game, ChessEngine::new()), std::mem::take(&mut movetext)));
+            }
+            if let Some((name, value)) = tag.split_once(' ') {
+                if name == "FEN" {
+                    chess_game = FenParser::parse(value.trim().trim_matches('"'))?;
+                }
+            }
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+    if !movetext.trim().is_empty() {
+        games.push((chess_game, movetext));
+    }
+
+    Ok(games)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Token {
+    // In SAN, without its number
+    Move(String),
+    // The start and the end of a variation
+    Open,
+    Close,
+    // Ends the game
+    Result,
+}
+
+/// The moves, the variations and the results of the movetext, without the
+/// comments, the glyphs and the move numbers
+fn tokens(movetext: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut word: String = String::new();
+    let mut chars = movetext.chars();
+
+    while let Some(c) = chars.next() {
+        let token: Option<Token> = match c {
+            '{' => {
+                chars.by_ref().find(|&c| c == '}');
+                None
+            }
+            ';' => {
+                chars.by_ref().find(|&c| c == '\n');
+                None
+            }
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            c if c.is_whitespace() => None,
+            c => {
+                word.push(c);
+                continue;
+            }
+        };
+        tokens.extend(word_token(&std::mem::take(&mut word)));
+        tokens.extend(token);
+    }
+    tokens.extend(word_token(&word));
+
+    tokens
+}
+
+/// The move or the result of a word of the movetext, if it has one
+fn word_token(word: &str) -> Option<Token> {
+    // Move numbers may be glued to their move, as in "1.e4"
+    let san: &str = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if RESULTS.contains(&san) || RESULTS.contains(&word) {
+        Some(Token::Result)
+    } else if san.is_empty() || word.starts_with('$') {
+        None
+    } else {
+        Some(Token::Move(san.to_string()))
+    }
+}
+
+/// The current date in the PGN format, "2024.01.31"
+fn today() -> String {
+    let days: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| (duration.as_secs() / 86_400) as i64);
+
+    // Civil date from days since 1970-01-01, by Howard Hinnant
+    let z: i64 = days + 719_468;
+    let era: i64 = z.div_euclid(146_097);
+    let day_of_era: i64 = z - era * 146_097;
+    let year_of_era: i64 = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month: i64 = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year: i64 = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::play_moves;
+
+    use super::{read_game, read_lines, Annotation, Pgn};
+
+    #[test]
+    fn test_pgn_from_start() -> Result<()> {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        play_moves(&mut chess_game, &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"])?;
+
+        let pgn: Pgn = Pgn::new(&chess_game)
+            .with_tag("White", "alpha-beta")
+            .with_tag("Result", "1/2-1/2");
+        let text: String = pgn.to_string();
+
+        assert!(text.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Round \"?\"]\n[White \"alpha-beta\"]\n[Black \"?\"]\n[Date \""));
+        assert!(!text.contains("FEN"));
+        assert_eq!(Some("C60"), pgn.tag("ECO"));
+        assert_eq!(Some("Ruy Lopez"), pgn.tag("Opening"));
+        assert!(text.ends_with("\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 1/2-1/2\n"), "{text}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pgn_from_fen() -> Result<()> {
+        let fen: &str = "6k1/5ppp/8/8/8/8/8/4R1K1 b - - 0 1";
+        let mut chess_game: ChessEngine = FenParser::parse(fen)?;
+        play_moves(&mut chess_game, &["h7h6", "e1e8"])?;
+
+        let pgn: Pgn = Pgn::new(&chess_game);
+
+        assert_eq!(Some("1"), pgn.tag("SetUp"));
+        assert_eq!(Some(fen), pgn.tag("FEN"));
+        assert_eq!(None, pgn.tag("ECO"));
+        assert!(pgn.to_string().ends_with("\n\n1... h6 2. Re8+ *\n"), "{pgn}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pgn_line_width() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        for _ in 0..10 {
+            play_moves(&mut chess_game, &["g1f3", "g8f6", "f3g1", "f6g8"]).unwrap();
+        }
+
+        let text: String = Pgn::new(&chess_game).to_string();
+
+        assert!(text.lines().all(|line| line.len() <= 80));
+    }
+
+    #[test]
+    fn test_pgn_annotations() -> Result<()> {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        play_moves(&mut chess_game, &["e2e4", "e7e5", "g1f3", "f7f6"])?;
+        let annotation: Annotation = Annotation {
+            suffix: "?",
+            comment: Some("Mistake".to_string()),
+            variation: Some("2... Nc6 3. Bb5".to_string()),
+        };
+
+        let pgn: Pgn = Pgn::new(&chess_game)
+            .with_comment("Reviewed")
+            .with_annotation(0, Annotation { suffix: "!", ..Annotation::default() })
+            .with_annotation(3, annotation);
+
+        assert!(pgn.to_string().ends_with("\n\n{Reviewed} 1. e4! e5 2. Nf3 f6? {Mistake} (2... Nc6 3. Bb5) *\n"), "{pgn}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_game() -> Result<()> {
+        let text: &str = "[Event \"?\"]\n\n1. e4 {best by test} e5 2.Nf3 (2. f4 exf4) Nc6?! $6 ; a comment\n3. Bb5 a6 1-0\n\n[Event \"?\"]\n\n1. d4 *\n";
+
+        let chess_game: ChessEngine = read_game(text)?;
+
+        assert_eq!("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *", Pgn::new(&chess_game).to_string().lines().last().unwrap_or_default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_game_from_fen() -> Result<()> {
+        let chess_game: ChessEngine = read_game("[FEN \"6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1\"]\n\n1. Re8# 1-0")?;
+
+        assert!(chess_game.is_end());
+        assert!(read_game("1. e4 e4").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_lines() -> Result<()> {
+        let text: &str = "1. e4 e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 {main} Nc6 *\n\n[FEN \"6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1\"]\n\n1. Re8# *\n";
+
+        let lines: Vec<String> = read_lines(text)?
+            .iter()
+            .map(|line| Pgn::new(line).to_string().lines().last().unwrap_or_default().to_string())
+            .collect();
+
+        assert_eq!(
+            vec!["1. e4 c5 2. c3 *", "1. e4 c5 2. Nf3 d6 *", "1. e4 e5 2. Nf3 Nc6 *", "1. Re8# *"],
+            lines
+        );
+        assert!(read_lines("(1. e4) *").is_err());
+        assert!(read_lines("1. e4 e5) *").is_err());
+        Ok(())
+    }
+}
@@ -23,6 +23,14 @@ pub(crate) struct Pawn {
 }
 
 impl Pawn {
+    const fn original_row(color: Color) -> usize {
+        match color {
+            Color::White => 6usize,
+            Color::Black => 1usize,
+            Color::Any => panic!("A pawn of color \"Any\" has no original row"),
+        }
+    }
+
     pub fn en_passant_possible(&self) -> bool {
         self.en_passant_possible
     }
@@ -72,11 +80,17 @@ impl Hash for Pawn {
 
 impl Piece for Pawn {
     fn new(position: Position, color: Color) -> Self {
-        Self {
+        let pawn: Self = Self {
             color,
             position,
             en_passant_possible: false,
             has_moved: false,
+        };
+
+        if position.row() != Self::original_row(color) {
+            pawn.with_has_moved()
+        } else {
+            pawn
         }
     }
 
@@ -203,6 +217,22 @@ mod tests {
         assert_eq!(expected, possible_moves);
     }
 
+    #[test]
+    fn test_simple_moves_last_rank() {
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::Pawn(Pawn::new((1isize, 3isize).into(), Color::White)))
+            .build();
+        let piece: &PieceKind = board
+            .piece((1isize, 3isize).into(), Color::White)
+            .expect("The piece should exist");
+        let mut expected: HashSet<Move> = HashSet::new();
+        expected.insert(Move::new((1isize, 3isize).into(), (0isize, 3isize).into(), MoveKind::PawnSimpleMove));
+
+        let possible_moves = piece.possible_moves(&board);
+
+        assert_eq!(expected, possible_moves);
+    }
+
     #[test]
     fn test_no_moves() {
         let board: Board = BoardBuilder::new()
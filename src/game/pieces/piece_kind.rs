@@ -19,6 +19,20 @@ pub(crate) enum PieceKind {
     Rook(Rook),
 }
 
+impl PieceKind {
+    /// The letter of the piece in English notation, upper case
+    pub(crate) const fn letter(&self) -> char {
+        match self {
+            PieceKind::Bishop(_) => 'B',
+            PieceKind::King(_) => 'K',
+            PieceKind::Knight(_) => 'N',
+            PieceKind::Pawn(_) => 'P',
+            PieceKind::Queen(_) => 'Q',
+            PieceKind::Rook(_) => 'R',
+        }
+    }
+}
+
 impl Deref for PieceKind {
     type Target = dyn Piece;
 
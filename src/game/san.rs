@@ -0,0 +1,204 @@
+use crate::game::ChessEngine;
+use crate::game::Result;
+use crate::game::board::color::Color;
+use crate::game::board::move_kind::MoveKind;
+use crate::game::board::move_struct::Move;
+use crate::game::board::position::Position;
+use crate::game::pieces::piece_kind::PieceKind;
+
+/// Standard algebraic notation of a legal move in the current position, like
+/// "Nbd7", "exd5" or "O-O+"
+pub(crate) fn to_san(chess_game: &ChessEngine, (from, to): (Position, Position)) -> Option<String> {
+    let played: &Move = chess_game.legal_move(from, to)?;
+    let piece: &PieceKind = chess_game.board().piece(from, Color::Any)?;
+    let capture: bool = matches!(played.kind(), MoveKind::Attack(Some(_)) | MoveKind::EnPassant(_));
+
+    let mut san: String = match (played.kind(), piece) {
+        (MoveKind::CastleKingSide(_), _) => "O-O".to_string(),
+        (MoveKind::CastleQueenSide(_), _) => "O-O-O".to_string(),
+        (_, PieceKind::Pawn(_)) if capture => format!("{}x{}", file(from), to.to_notation()),
+        (_, PieceKind::Pawn(_)) => to.to_notation(),
+        _ => format!(
+            "{}{}{}{}",
+            piece.letter(),
+            disambiguation(chess_game, piece, from, to),
+            if capture { "x" } else { "" },
+            to.to_notation(),
+        ),
+    };
+
+    let mut after: ChessEngine = chess_game.clone();
+    after.try_move(Some(from), Some(to));
+    if after.result() == Result::Checkmate {
+        san.push('#');
+    } else if after.checked_king().is_some() {
+        san.push('+');
+    }
+
+    Some(san)
+}
+
+/// The legal move written `san` in the current position. The check marks
+/// and the annotations like "!?" are optional, castling can be written with
+/// zeros
+pub(crate) fn from_san(chess_game: &ChessEngine, san: &str) -> Option<(Position, Position)> {
+    let wanted: String = strip_marks(san).replace('0', "O");
+    // Every move but castling ends with its destination
+    let destination: Option<Position> = wanted.get(wanted.len().checked_sub(2)?..).and_then(Position::from_notation);
+
+    chess_game
+        .possible_moves()
+        .values()
+        .flatten()
+        .filter(|m| destination.is_none_or(|destination| m.to() == destination))
+        .map(|m| (m.from(), m.to()))
+        .find(|&candidate| to_san(chess_game, candidate).is_some_and(|written| strip_marks(&written) == wanted))
+}
+
+fn strip_marks(san: &str) -> &str {
+    san.trim_end_matches(['+', '#', '!', '?'])
+}
+
+/// A line of moves from the current position in SAN, numbered as in a game
+/// score: "12. Nf3 Nc6 13. d4" or "12... Nc6 13. d4". Stops at the first
+/// illegal move
+pub(crate) fn line_to_san(chess_game: &ChessEngine, line: &[(Position, Position)]) -> String {
+    let mut replay: ChessEngine = chess_game.clone();
+    let first_ply: usize = chess_game.history().len() + usize::from(chess_game.starting_position().current_player() == Color::Black);
+    let mut tokens: Vec<String> = Vec::new();
+
+    for (ply, &(from, to)) in (first_ply..).zip(line) {
+        let Some(san) = to_san(&replay, (from, to)) else {
+            break;
+        };
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}. {san}", ply / 2 + 1));
+        } else if tokens.is_empty() {
+            tokens.push(format!("{}... {san}", ply / 2 + 1));
+        } else {
+            tokens.push(san);
+        }
+        replay.try_move(Some(from), Some(to));
+    }
+
+    tokens.join(" ")
+}
+
+/// The file, the rank or both of the moving piece when another piece of the
+/// same kind can reach the same square
+fn disambiguation(chess_game: &ChessEngine, piece: &PieceKind, from: Position, to: Position) -> String {
+    let rivals: Vec<Position> = chess_game
+        .possible_moves()
+        .iter()
+        .filter(|(position, moves)| **position != from && moves.iter().any(|m| m.to() == to))
+        .map(|(position, _)| *position)
+        .filter(|position| {
+            chess_game
+                .board()
+                .piece(*position, Color::Any)
+                .is_some_and(|rival| rival.letter() == piece.letter())
+        })
+        .collect();
+
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|rival| rival.column() != from.column()) {
+        file(from).to_string()
+    } else if rivals.iter().all(|rival| rival.row() != from.row()) {
+        from.to_notation()[1..].to_string()
+    } else {
+        from.to_notation()
+    }
+}
+
+fn file(position: Position) -> char {
+    (b'a' + position.column() as u8) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::parse_move;
+
+    use super::{from_san, line_to_san, to_san};
+
+    #[rstest]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e2e4", "e4")]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1f3", "Nf3")]
+    #[case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "e4d5", "exd5")]
+    #[case("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", "e1g1", "O-O")]
+    #[case("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", "e1c1", "O-O-O")]
+    #[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "a1d1", "Rad1")]
+    #[case("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2", "R1a2")]
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", "e1e8", "Re8#")]
+    #[case("4k3/8/8/8/8/8/8/4K2Q w - - 0 1", "h1h5", "Qh5+")]
+    #[case("4k3/8/8/3n4/8/8/8/3QK3 w - - 0 1", "d1d5", "Qxd5")]
+    fn test_to_san(
+        #[case]
+        fen: &str,
+        #[case]
+        notation: &str,
+        #[case]
+        expected: &str
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let san: Option<String> = to_san(&chess_game, parse_move(notation).expect("The move should be valid"));
+
+        assert_eq!(Some(expected.to_string()), san);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e4", Some("e2e4"))]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "Nf3!?", Some("g1f3"))]
+    #[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e5", None)]
+    #[case("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", "0-0-0", Some("e1c1"))]
+    #[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "Rad1", Some("a1d1"))]
+    #[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "Rd1", None)]
+    #[case("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", "Re8", Some("e1e8"))]
+    fn test_from_san(
+        #[case]
+        fen: &str,
+        #[case]
+        san: &str,
+        #[case]
+        expected: Option<&str>
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        assert_eq!(expected.and_then(|notation| parse_move(notation).ok()), from_san(&chess_game, san));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(&[], &["e2e4", "e7e5", "g1f3"], "1. e4 e5 2. Nf3")]
+    #[case(&["e2e4"], &["e7e5", "g1f3", "a7a6"], "1... e5 2. Nf3 a6")]
+    #[case(&[], &["e2e4", "e2e4"], "1. e4")]
+    fn test_line_to_san(
+        #[case]
+        played: &[&str],
+        #[case]
+        line: &[&str],
+        #[case]
+        expected: &str
+    ) {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        for (from, to) in played.iter().filter_map(|notation| parse_move(notation).ok()) {
+            chess_game.try_move(Some(from), Some(to));
+        }
+        let line: Vec<_> = line.iter().filter_map(|notation| parse_move(notation).ok()).collect();
+
+        assert_eq!(expected, line_to_san(&chess_game, &line));
+    }
+
+    #[test]
+    fn test_to_san_illegal() {
+        assert_eq!(None, to_san(&ChessEngine::new(), parse_move("e2e5").unwrap()));
+    }
+}
@@ -1,39 +1,25 @@
-use std::panic;
+use std::env;
 
 use anyhow::Result;
 
-use game::ChessEngine;
-use ui::cursor::Cursor;
-use ui::cursor::cursor_event::CursorEvent;
-use ui::drawer::{clean_screen, draw_game};
-
 mod bot;
+mod cli;
+mod endgame;
 mod game;
+mod protocol;
+mod puzzle;
+mod repertoire;
+mod review;
+mod tablebase;
+mod tactics;
+mod tournament;
 mod ui;
 
 fn main() -> Result<()> {
-    panic::set_hook(Box::new(|p| {
-        let _ = Cursor::stop();
-        panic!("{p}");
-    }));
-
-    let mut chess_game: ChessEngine = ChessEngine::new();
-    let mut cursor: Cursor = Cursor::new();
-
-    clean_screen();
-    Cursor::start()?;
-    draw_game(&chess_game, &cursor);
-
-    loop {
-        cursor.next_event(&mut chess_game);
-        draw_game(&chess_game, &cursor);
-
-        if CursorEvent::Stop.eq(cursor.event()) || chess_game.is_end() {
-            break;
-        }
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some((command, args)) = args.split_first() {
+        return cli::run(command, args);
     }
 
-    Cursor::stop()?;
-
-    Ok(())
+    ui::run(None, None, ui::DEFAULT_ENGINE)
 }
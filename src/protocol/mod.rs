@@ -0,0 +1,90 @@
+use anyhow::{bail, Context, Result};
+
+use crate::game::ChessEngine;
+use crate::game::board::position::Position;
+
+pub(crate) mod uci;
+pub(crate) mod uci_client;
+pub(crate) mod xboard;
+
+/// Reads a move in the long algebraic notation of the engine protocols,
+/// like "e2e4". A promotion like "e7e8q" is an error: the engine can't
+/// play it, and dropping the piece would leave its board out of step
+pub(crate) fn parse_move(notation: &str) -> Result<(Position, Position)> {
+    // The GUI may send anything, Position::from_notation wants two ASCII
+    // characters
+    if !notation.is_ascii() || !(4..=5).contains(&notation.len()) {
+        bail!("Invalid move \"{notation}\"");
+    }
+    if notation.len() == 5 {
+        bail!("Promotions are not supported: \"{notation}\"");
+    }
+    let square = |range| notation.get(range).and_then(Position::from_notation).with_context(|| format!("Invalid move \"{notation}\""));
+
+    Ok((square(0..2)?, square(2..4)?))
+}
+
+pub(crate) fn format_move((from, to): (Position, Position)) -> String {
+    format!("{}{}", from.to_notation(), to.to_notation())
+}
+
+pub(crate) fn play_moves(chess_game: &mut ChessEngine, moves: &[&str]) -> Result<()> {
+    for notation in moves {
+        let (from, to): (Position, Position) = parse_move(notation)?;
+
+        if !chess_game.try_move(Some(from), Some(to)) {
+            bail!("Illegal move \"{notation}\"");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+
+    use super::{format_move, parse_move, play_moves};
+
+    #[test]
+    fn test_parse_format_move() {
+        let expected: (Position, Position) = ((6isize, 4isize).into(), (4isize, 4isize).into());
+
+        assert_eq!(expected, parse_move("e2e4").unwrap());
+        assert_eq!("e2e4", format_move(expected));
+    }
+
+    #[rstest]
+    #[case("e2", "Invalid move \"e2\"")]
+    #[case("z2e4", "Invalid move \"z2e4\"")]
+    #[case("é2e4", "Invalid move \"é2e4\"")]
+    #[case("e2é4", "Invalid move \"e2é4\"")]
+    #[case("e2e4e5", "Invalid move \"e2e4e5\"")]
+    #[case("e7e8q", "Promotions are not supported: \"e7e8q\"")]
+    #[case("a2a1n", "Promotions are not supported: \"a2a1n\"")]
+    fn test_parse_move_error(
+        #[case] notation: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, parse_move(notation).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_play_moves() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+
+        assert!(play_moves(&mut chess_game, &["e2e4", "e7e5", "g1f3"]).is_ok());
+        assert_eq!(Color::Black, chess_game.current_player());
+        assert!(play_moves(&mut chess_game, &["e5e4"]).is_err());
+        // The board is left as it was
+        let mut promotion: ChessEngine = FenParser::parse("8/4P3/8/8/8/8/8/k3K3 w - - 0 1").unwrap();
+        assert!(play_moves(&mut promotion, &["e7e8q"]).is_err());
+        assert!(promotion.history().is_empty());
+    }
+}
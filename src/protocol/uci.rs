@@ -0,0 +1,554 @@
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::bot::{self, Bot, BOT_NAMES};
+use crate::bot::difficulty::{Difficulty, LEVELS};
+use crate::bot::search::{time_budget, SearchLimits, SearchResult};
+use crate::bot::transposition::TranspositionTable;
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::fen_parser::FenParser;
+use crate::tablebase::Tablebase;
+
+use super::{format_move, play_moves};
+
+const DEFAULT_BOT: &str = "alpha-beta";
+const MAX_HASH: usize = 1024;
+const MAX_THREADS: usize = 64;
+const MAX_SKILL: i16 = 20;
+const DEFAULT_ELO: u32 = 1400;
+
+type Output = Arc<Mutex<dyn Write + Send>>;
+
+struct Search {
+    handle: JoinHandle<Box<dyn Bot>>,
+    stop: Arc<AtomicBool>,
+}
+
+/// The Universal Chess Interface, to be driven by a chess GUI. The search runs
+/// on its own thread so that commands, and "stop" above all, are still read
+pub(crate) struct Uci {
+    output: Output,
+    chess_game: ChessEngine,
+    // Lent to the search thread while it runs
+    bot: Option<Box<dyn Bot>>,
+    search: Option<Search>,
+    bot_name: String,
+    hash: usize,
+    threads: usize,
+    skill: i16,
+    limit_strength: bool,
+    elo: u32,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Uci {
+    pub(crate) fn new(output: impl Write + Send + 'static) -> Result<Self> {
+        Ok(Self {
+            output: Arc::new(Mutex::new(output)),
+            chess_game: ChessEngine::new(),
+            bot: Some(bot::from_name(DEFAULT_BOT)?),
+            search: None,
+            bot_name: DEFAULT_BOT.to_string(),
+            hash: TranspositionTable::DEFAULT_MEGABYTES,
+            threads: 1,
+            skill: MAX_SKILL,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
+            tablebase: None,
+        })
+    }
+
+    /// Reads commands until "quit" or the end of the input
+    pub(crate) fn run(&mut self, input: impl BufRead) -> Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?)? {
+                return Ok(());
+            }
+        }
+
+        self.stop();
+        Ok(())
+    }
+
+    /// Returns false once the engine has to quit. Invalid commands are
+    /// reported to the GUI and otherwise ignored
+    fn handle(&mut self, line: &str) -> Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+
+        let outcome: Result<()> = match *command {
+            "uci" => self.uci(),
+            "isready" => send(&self.output, "readyok"),
+            "ucinewgame" => {
+                self.bot_mut().new_game();
+                self.chess_game = ChessEngine::new();
+                Ok(())
+            }
+            "position" => self.position(args),
+            "go" => self.go(args),
+            "stop" => {
+                self.stop();
+                Ok(())
+            }
+            "setoption" => self.set_option(args),
+            "quit" => {
+                self.stop();
+                return Ok(false);
+            }
+            _ => Err(anyhow!("Unknown command \"{command}\"")),
+        };
+
+        if let Err(error) = outcome {
+            send(&self.output, &format!("info string {error}"))?;
+        }
+
+        Ok(true)
+    }
+
+    fn uci(&self) -> Result<()> {
+        let bots: Vec<String> = BOT_NAMES.iter().map(|name| format!("var {name}")).collect();
+
+        send(&self.output, concat!("id name chessterm ", env!("CARGO_PKG_VERSION")))?;
+        send(&self.output, "id author the chessterm authors")?;
+        send(
+            &self.output,
+            &format!("option name Hash type spin default {} min 1 max {MAX_HASH}", TranspositionTable::DEFAULT_MEGABYTES),
+        )?;
+        send(&self.output, &format!("option name Threads type spin default 1 min 1 max {MAX_THREADS}"))?;
+        send(&self.output, &format!("option name Skill Level type spin default {MAX_SKILL} min 0 max {MAX_SKILL}"))?;
+        send(&self.output, "option name UCI_LimitStrength type check default false")?;
+        send(
+            &self.output,
+            &format!(
+                "option name UCI_Elo type spin default {} min {} max {}",
+                DEFAULT_ELO,
+                LEVELS[0].elo(),
+                LEVELS[LEVELS.len() - 1].elo(),
+            ),
+        )?;
+        send(&self.output, &format!("option name Bot type combo default {DEFAULT_BOT} {}", bots.join(" ")))?;
+        send(&self.output, "option name SyzygyPath type string default <empty>")?;
+        send(&self.output, "uciok")
+    }
+
+    /// `position startpos|fen <fen> [moves <move>...]`
+    fn position(&mut self, args: &[&str]) -> Result<()> {
+        self.stop();
+
+        let (setup, moves): (&[&str], &[&str]) = match args.iter().position(|arg| *arg == "moves") {
+            Some(index) => (&args[..index], &args[index + 1..]),
+            None => (args, &[]),
+        };
+
+        let mut chess_game: ChessEngine = match setup {
+            ["startpos"] => ChessEngine::new(),
+            ["fen", fen @ ..] => FenParser::parse(&fen.join(" "))?,
+            _ => bail!("Expected \"position startpos\" or \"position fen <fen>\""),
+        };
+        play_moves(&mut chess_game, moves)?;
+
+        self.chess_game = chess_game;
+        Ok(())
+    }
+
+    fn go(&mut self, args: &[&str]) -> Result<()> {
+        self.stop();
+
+        let infinite: bool = args.contains(&"infinite");
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let limits: SearchLimits = self.limits(args)?.with_stop(Arc::clone(&stop));
+
+        let mut bot: Box<dyn Bot> = self.bot.take().expect("The bot is back once the search is stopped");
+        let chess_game: ChessEngine = self.chess_game.clone();
+        let output: Output = Arc::clone(&self.output);
+        let searching: Arc<AtomicBool> = Arc::clone(&stop);
+
+        let handle: JoinHandle<Box<dyn Bot>> = thread::spawn(move || {
+            let result: SearchResult = bot.search(&chess_game, limits, &mut |info| {
+                let _ = send(&output, &info_line(info));
+            });
+
+            // An infinite search only answers once it is told to stop
+            while infinite && !searching.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = send(&output, &best_move_line(&result));
+
+            bot
+        });
+
+        self.search = Some(Search {
+            handle,
+            stop,
+        });
+        Ok(())
+    }
+
+    fn limits(&self, args: &[&str]) -> Result<SearchLimits> {
+        let mut limits: SearchLimits = SearchLimits::default();
+        let (time_name, increment_name): (&str, &str) = match self.chess_game.current_player() {
+            Color::White => ("wtime", "winc"),
+            _ => ("btime", "binc"),
+        };
+        let mut time: Option<Duration> = None;
+        let mut increment: Duration = Duration::ZERO;
+        let mut moves_to_go: Option<u32> = None;
+
+        let mut tokens = args.iter();
+        while let Some(&name) = tokens.next() {
+            match name {
+                "depth" => limits = limits.with_depth(value(tokens.next(), name)?),
+                "movetime" => limits = limits.with_movetime(milliseconds(value(tokens.next(), name)?)),
+                "movestogo" => moves_to_go = Some(value(tokens.next(), name)?),
+                "nodes" => limits = limits.with_nodes(value(tokens.next(), name)?),
+                _ if name == time_name => time = Some(milliseconds(value(tokens.next(), name)?)),
+                _ if name == increment_name => increment = milliseconds(value(tokens.next(), name)?),
+                // The opponent's clock, and the limits that are not supported
+                "wtime" | "btime" | "winc" | "binc" | "mate" => {
+                    tokens.next();
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(time), None) = (time, limits.max_movetime()) {
+            limits = limits.with_movetime(time_budget(time, increment, moves_to_go));
+        }
+
+        if self.skill < MAX_SKILL {
+            let depth: i16 = limits.max_depth().min(skill_depth(self.skill));
+            limits = limits.with_depth(depth);
+        }
+
+        Ok(limits)
+    }
+
+    /// `setoption name <name> [value <value>]`
+    fn set_option(&mut self, args: &[&str]) -> Result<()> {
+        self.stop();
+
+        let text: String = args.join(" ");
+        let Some(option) = text.strip_prefix("name ") else {
+            bail!("Expected \"setoption name <name> value <value>\"");
+        };
+        let (name, value): (&str, &str) = option.split_once(" value ").unwrap_or((option, ""));
+
+        match name.to_lowercase().as_str() {
+            "hash" => {
+                self.hash = value.parse::<usize>().context("Hash should be a number")?.clamp(1, MAX_HASH);
+                let hash: usize = self.hash;
+                self.bot_mut().set_hash_size(hash);
+            }
+            "threads" => {
+                self.threads = value.parse::<usize>().context("Threads should be a number")?.clamp(1, MAX_THREADS);
+                let threads: usize = self.threads;
+                self.bot_mut().set_threads(threads);
+            }
+            "skill level" => {
+                self.skill = value.parse::<i16>().context("Skill Level should be a number")?.clamp(0, MAX_SKILL);
+            }
+            "bot" => {
+                // Kept only once the bot exists, a wrong name changes nothing
+                let bot: Box<dyn Bot> = bot::from_name(value)?;
+                self.bot_name = value.to_string();
+                if !self.limit_strength {
+                    self.set_bot(bot);
+                }
+            }
+            "uci_limitstrength" => {
+                self.limit_strength = value.parse().context("UCI_LimitStrength should be true or false")?;
+                self.replace_bot()?;
+            }
+            "uci_elo" => {
+                self.elo = value.parse().context("UCI_Elo should be a number")?;
+                self.replace_bot()?;
+            }
+            "syzygypath" => {
+                self.tablebase = match value {
+                    "" | "<empty>" => None,
+                    directory => Some(Arc::new(Tablebase::open(directory)?)),
+                };
+                self.replace_bot()?;
+            }
+            _ => bail!("Unknown option \"{name}\""),
+        }
+
+        Ok(())
+    }
+
+    /// The bot named by the Bot option, or the level of UCI_Elo when the
+    /// strength is limited
+    fn replace_bot(&mut self) -> Result<()> {
+        let bot: Box<dyn Bot> = if self.limit_strength {
+            Difficulty::from_elo(self.elo).bot()
+        } else {
+            bot::from_name(&self.bot_name)?
+        };
+        self.set_bot(bot);
+        Ok(())
+    }
+
+    /// Gives `bot` the hash size, the threads and the tablebase of the options
+    fn set_bot(&mut self, mut bot: Box<dyn Bot>) {
+        bot.set_hash_size(self.hash);
+        bot.set_threads(self.threads);
+        if let Some(tablebase) = &self.tablebase {
+            bot.set_tablebase(Arc::clone(tablebase));
+        }
+        self.bot = Some(bot);
+    }
+
+    /// Stops the search if one is running, and waits for its best move
+    fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            self.bot = Some(search.handle.join().expect("The search thread should not panic"));
+        }
+    }
+
+    fn bot_mut(&mut self) -> &mut Box<dyn Bot> {
+        self.stop();
+        self.bot.as_mut().expect("The bot is back once the search is stopped")
+    }
+}
+
+/// The depth a skill level is limited to, the full strength has no limit
+const fn skill_depth(skill: i16) -> i16 {
+    1 + skill / 4
+}
+
+fn value<T: FromStr>(token: Option<&&str>, name: &str) -> Result<T> {
+    token
+        .and_then(|token| token.parse().ok())
+        .with_context(|| format!("\"{name}\" should be followed by a number"))
+}
+
+/// Clocks can go below zero in some GUIs
+fn milliseconds(value: i64) -> Duration {
+    Duration::from_millis(value.max(0) as u64)
+}
+
+fn info_line(result: &SearchResult) -> String {
+    let score: String = match result.score().mate_moves() {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", result.score().value()),
+    };
+    let pv: Vec<String> = result.pv().iter().map(|m| format_move(*m)).collect();
+
+    format!(
+        "info depth {} seldepth {} score {score} nodes {} nps {} time {} pv {}",
+        result.depth(),
+        result.seldepth(),
+        result.nodes(),
+        result.nps(),
+        result.elapsed().as_millis(),
+        pv.join(" "),
+    )
+}
+
+fn best_move_line(result: &SearchResult) -> String {
+    match result.pv() {
+        [] => "bestmove 0000".to_string(),
+        [best] => format!("bestmove {}", format_move(*best)),
+        [best, ponder, ..] => format!("bestmove {} ponder {}", format_move(*best), format_move(*ponder)),
+    }
+}
+
+fn send(output: &Output, line: &str) -> Result<()> {
+    let mut output = output.lock().map_err(|_| anyhow!("The output lock is poisoned"))?;
+    writeln!(output, "{line}")?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::search::SearchLimits;
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::play_moves;
+
+    use super::Uci;
+
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedOutput {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn wait(uci: &mut Uci) {
+        let search = uci.search.take().expect("A search should be running");
+        uci.bot = Some(search.handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_handshake() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.run("uci\nisready\nquit\nisready\n".as_bytes())?;
+
+        let lines: Vec<String> = output.lines();
+        assert!(lines[0].starts_with("id name chessterm"));
+        assert!(lines.contains(&"option name Threads type spin default 1 min 1 max 64".to_string()));
+        assert!(lines.contains(&"option name Skill Level type spin default 20 min 0 max 20".to_string()));
+        assert!(lines.contains(&"option name SyzygyPath type string default <empty>".to_string()));
+        assert_eq!(["uciok", "readyok"], lines[lines.len() - 2..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_position() -> anyhow::Result<()> {
+        let mut uci: Uci = Uci::new(SharedOutput::default())?;
+        let mut expected: ChessEngine = ChessEngine::new();
+        play_moves(&mut expected, &["e2e4", "e7e5", "g1f3"])?;
+
+        uci.handle("position startpos moves e2e4 e7e5 g1f3")?;
+        assert_eq!(expected.key(), uci.chess_game.key());
+
+        let mut expected: ChessEngine = FenParser::parse("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1")?;
+        play_moves(&mut expected, &["e1e2"])?;
+
+        uci.handle("position fen 6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1 moves e1e2")?;
+        assert_eq!(expected.key(), uci.chess_game.key());
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_depth() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.handle("position fen 6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1")?;
+        uci.handle("go depth 2")?;
+        wait(&mut uci);
+
+        let lines: Vec<String> = output.lines();
+        assert!(lines[0].starts_with("info depth 1 seldepth 1 score mate 1 nodes"), "{lines:?}");
+        assert_eq!("bestmove e1e8", lines[lines.len() - 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_threads() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.handle("setoption name Threads value 4")?;
+        uci.handle("position fen 6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1")?;
+        uci.handle("go depth 3")?;
+        wait(&mut uci);
+
+        assert_eq!(4, uci.threads);
+        assert_eq!("bestmove e1e8", output.lines()[output.lines().len() - 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_limit_strength() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.handle("uci")?;
+        assert!(output.lines().contains(&"option name UCI_Elo type spin default 1400 min 800 max 2000".to_string()));
+
+        uci.handle("setoption name UCI_Elo value 1100")?;
+        assert_eq!(Some("alpha-beta"), uci.bot.as_ref().map(|bot| bot.name()));
+        uci.handle("setoption name UCI_LimitStrength value true")?;
+        assert_eq!(Some("novice"), uci.bot.as_ref().map(|bot| bot.name()));
+        uci.handle("setoption name UCI_LimitStrength value false")?;
+        assert_eq!(Some("alpha-beta"), uci.bot.as_ref().map(|bot| bot.name()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_infinite_stop() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.handle("go infinite")?;
+        std::thread::sleep(Duration::from_millis(20));
+        uci.handle("stop")?;
+
+        let lines: Vec<String> = output.lines();
+        assert!(lines[lines.len() - 1].starts_with("bestmove "), "{lines:?}");
+        assert!(uci.search.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits() -> anyhow::Result<()> {
+        let mut uci: Uci = Uci::new(SharedOutput::default())?;
+
+        let limits: SearchLimits = uci.limits(&["wtime", "60000", "btime", "1000", "winc", "1000", "movestogo", "20"])?;
+        assert_eq!(Some(Duration::from_millis(3_750)), limits.max_movetime());
+
+        let limits: SearchLimits = uci.limits(&["btime", "1000", "nodes", "5000"])?;
+        assert_eq!(None, limits.max_movetime());
+        assert_eq!(Some(5_000), limits.max_nodes());
+
+        uci.handle("setoption name Skill Level value 3")?;
+        let limits: SearchLimits = uci.limits(&["depth", "10"])?;
+        assert_eq!(1, limits.max_depth());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_commands() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.run("position startpos moves e2e5\nsetoption name Bot value stockfish\nxyzzy\nsetoption name SyzygyPath value /nonexistent\n".as_bytes())?;
+
+        let lines: Vec<String> = output.lines();
+        assert_eq!(4, lines.len());
+        assert!(lines.iter().all(|line| line.starts_with("info string ")), "{lines:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_bot_is_not_kept() -> anyhow::Result<()> {
+        let output: SharedOutput = SharedOutput::default();
+        let mut uci: Uci = Uci::new(output.clone())?;
+
+        uci.run("setoption name Bot value greedy\nsetoption name Bot value stockfish\nsetoption name Threads value 2\nsetoption name UCI_Elo value 1100\n".as_bytes())?;
+
+        assert_eq!(1, output.lines().len(), "{:?}", output.lines());
+        assert_eq!("greedy", uci.bot_name);
+        assert_eq!(Some("greedy"), uci.bot.as_ref().map(|bot| bot.name()));
+        assert_eq!(2, uci.threads);
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::Bot;
+use crate::bot::score::Score;
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::ChessEngine;
+use crate::game::board::position::Position;
+use crate::game::fen_writer::FenWriter;
+
+use super::{format_move, parse_move};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// Granted on top of the move time before the engine is declared unresponsive
+const SEARCH_GRACE: Duration = Duration::from_secs(1);
+// How often the stop flag is looked at while the engine thinks
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const QUIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// An external engine speaking UCI, run as a child process. Once it crashes
+/// or stops answering, every search comes back without a move and
+/// `failure` tells why
+pub(crate) struct UciEngine {
+    name: String,
+    process: Child,
+    input: ChildStdin,
+    // Filled by a thread reading the engine's output, disconnected when the
+    // engine exits
+    lines: Receiver<String>,
+    failure: Option<String>,
+}
+
+impl UciEngine {
+    pub(crate) fn spawn(program: &str, args: &[&str]) -> Result<Self> {
+        let mut process: Child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Can't start the engine \"{program}\""))?;
+        let input: ChildStdin = process.stdin.take().context("The engine's input should be piped")?;
+        let output: ChildStdout = process.stdout.take().context("The engine's output should be piped")?;
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine: Self = Self {
+            name: program.to_string(),
+            process,
+            input,
+            lines,
+            failure: None,
+        };
+        engine.handshake()?;
+
+        Ok(engine)
+    }
+
+    fn handshake(&mut self) -> Result<()> {
+        let deadline: Instant = Instant::now() + HANDSHAKE_TIMEOUT;
+        self.send("uci")?;
+
+        loop {
+            let line: String = self.receive(deadline)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                self.name = name.trim().to_string();
+            }
+            if line.trim() == "uciok" {
+                break;
+            }
+        }
+
+        self.ready()
+    }
+
+    fn ready(&mut self) -> Result<()> {
+        let deadline: Instant = Instant::now() + HANDSHAKE_TIMEOUT;
+        self.send("isready")?;
+
+        while self.receive(deadline)?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    fn send(&mut self, line: &str) -> Result<()> {
+        writeln!(self.input, "{line}")
+            .and_then(|()| self.input.flush())
+            .with_context(|| format!("The engine {} stopped reading its input", self.name))
+    }
+
+    fn receive(&self, deadline: Instant) -> Result<String> {
+        match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => bail!("The engine {} did not answer in time", self.name),
+            Err(RecvTimeoutError::Disconnected) => bail!("The engine {} exited", self.name),
+        }
+    }
+
+    /// Sends the game as its starting position and the moves played since,
+    /// so that the engine knows about repetitions
+    fn set_position(&mut self, chess_game: &ChessEngine) -> Result<()> {
+        let fen: String = FenWriter::write(&chess_game.starting_position());
+        let mut command: String = if fen == FenWriter::write(&ChessEngine::new()) {
+            "position startpos".to_string()
+        } else {
+            format!("position fen {fen}")
+        };
+
+        let moves: Vec<String> = chess_game.history().into_iter().map(format_move).collect();
+        if !moves.is_empty() {
+            command += &format!(" moves {}", moves.join(" "));
+        }
+
+        self.send(&command)
+    }
+
+    fn go(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: &SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> Result<SearchResult> {
+        self.set_position(chess_game)?;
+
+        let mut command: String = "go".to_string();
+        if let Some(depth) = limits.depth_limit() {
+            command += &format!(" depth {depth}");
+        }
+        if let Some(movetime) = limits.max_movetime() {
+            command += &format!(" movetime {}", movetime.as_millis());
+        }
+        if let Some(nodes) = limits.max_nodes() {
+            command += &format!(" nodes {nodes}");
+        }
+        // Only a stop or the engine ends it then
+        if command == "go" {
+            command += " infinite";
+        }
+        self.send(&command)?;
+
+        let start: Instant = Instant::now();
+        let mut deadline: Option<Instant> = limits.max_movetime().map(|movetime| start + movetime + SEARCH_GRACE);
+        let mut stopping: bool = false;
+        let mut last: Option<SearchResult> = None;
+
+        loop {
+            if !stopping && limits.stop_requested() {
+                self.send("stop")?;
+                stopping = true;
+                deadline = Some(Instant::now() + SEARCH_GRACE);
+            }
+
+            let line: String = match self.lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        bail!("The engine {} did not answer in time", self.name);
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => bail!("The engine {} exited", self.name),
+            };
+
+            if let Some(info) = line.strip_prefix("info ") {
+                if let Some(result) = parse_info(info, start.elapsed()) {
+                    on_iteration(&result);
+                    last = Some(result);
+                }
+            } else if let Some(best_move) = line.strip_prefix("bestmove") {
+                return self.best_move(chess_game, best_move, last, start.elapsed());
+            }
+        }
+    }
+
+    fn best_move(
+        &self,
+        chess_game: &ChessEngine,
+        text: &str,
+        last: Option<SearchResult>,
+        elapsed: Duration,
+    ) -> Result<SearchResult> {
+        let last: SearchResult = last.unwrap_or_else(|| SearchResult::new(Score::ZERO, Vec::new(), 0, 0, 0, elapsed));
+        let notation: &str = text.split_whitespace().next().unwrap_or("0000");
+        if notation == "0000" || notation == "(none)" {
+            return Ok(SearchResult::new(last.score(), Vec::new(), last.depth(), last.seldepth(), last.nodes(), elapsed));
+        }
+
+        let best_move: (Position, Position) = parse_move(notation).with_context(|| format!("The engine {} sent a move it can't play", self.name))?;
+        if !chess_game.possible_positions(Some(best_move.0)).is_some_and(|targets| targets.contains(&best_move.1)) {
+            bail!("The engine {} played the illegal move \"{notation}\"", self.name);
+        }
+
+        if last.best_move() == Some(best_move) {
+            Ok(last)
+        } else {
+            Ok(SearchResult::new(last.score(), vec![best_move], last.depth(), last.seldepth(), last.nodes(), elapsed))
+        }
+    }
+}
+
+impl Bot for UciEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn search(
+        &mut self,
+        chess_game: &ChessEngine,
+        limits: SearchLimits,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        if self.failure.is_some() {
+            return SearchResult::new(Score::ZERO, Vec::new(), 0, 0, 0, Duration::ZERO);
+        }
+
+        match self.go(chess_game, &limits, on_iteration) {
+            Ok(result) => result,
+            Err(error) => {
+                self.failure = Some(error.to_string());
+                let _ = self.process.kill();
+                SearchResult::new(Score::ZERO, Vec::new(), 0, 0, 0, Duration::ZERO)
+            }
+        }
+    }
+
+    fn new_game(&mut self) {
+        if self.failure.is_none() {
+            if let Err(error) = self.send("ucinewgame").and_then(|()| self.ready()) {
+                self.failure = Some(error.to_string());
+            }
+        }
+    }
+
+    fn set_hash_size(&mut self, megabytes: usize) {
+        if self.failure.is_none() {
+            let _ = self.send(&format!("setoption name Hash value {megabytes}"));
+        }
+    }
+
+    fn set_threads(&mut self, threads: usize) {
+        if self.failure.is_none() {
+            let _ = self.send(&format!("setoption name Threads value {threads}"));
+        }
+    }
+
+    fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        let deadline: Instant = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Reads the interesting parts of an "info" line, the ones without a
+/// principal variation are skipped
+fn parse_info(info: &str, elapsed: Duration) -> Option<SearchResult> {
+    let mut tokens = info.split_whitespace();
+    let mut score: Option<Score> = None;
+    let (mut depth, mut seldepth, mut nodes, mut elapsed): (i16, usize, u64, Duration) = (0, 0, 0, elapsed);
+    let mut pv: Vec<(Position, Position)> = Vec::new();
+
+    while let Some(name) = tokens.next() {
+        match name {
+            "depth" => depth = tokens.next()?.parse().ok()?,
+            "seldepth" => seldepth = tokens.next()?.parse().ok()?,
+            "nodes" => nodes = tokens.next()?.parse().ok()?,
+            "time" => elapsed = Duration::from_millis(tokens.next()?.parse().ok()?),
+            "score" => {
+                score = match (tokens.next()?, tokens.next()?.parse::<i32>().ok()?) {
+                    ("cp", centipawns) => Some(Score::centipawns(centipawns)),
+                    ("mate", moves) if moves > 0 => Some(Score::mate_in(moves as usize * 2 - 1)),
+                    ("mate", moves) => Some(Score::mated_in(moves.unsigned_abs() as usize * 2)),
+                    _ => return None,
+                };
+            }
+            "pv" => {
+                pv = tokens.by_ref().map_while(|notation| parse_move(notation).ok()).collect();
+            }
+            "string" => return None,
+            "lowerbound" | "upperbound" => {}
+            // Every other field has a single value
+            _ => {
+                tokens.next();
+            }
+        }
+    }
+
+    if pv.is_empty() {
+        return None;
+    }
+
+    Some(SearchResult::new(score?, pv, depth, seldepth, nodes, elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::Bot;
+    use crate::bot::score::Score;
+    use crate::bot::search::{SearchLimits, SearchResult};
+    use crate::game::ChessEngine;
+    use crate::protocol::parse_move;
+
+    use super::{parse_info, UciEngine};
+
+    /// A shell script answering like an engine, `go` is handled by `on_go`
+    fn stub_engine(name: &str, on_go: &str) -> PathBuf {
+        let script: String = format!(
+            r#"while read -r line; do
+    case "$line" in
+        uci) echo "id name Stub {name}"; echo "option name Hash type spin default 1 min 1 max 8"; echo uciok ;;
+        isready) echo readyok ;;
+        go*) {on_go} ;;
+        quit) exit 0 ;;
+    esac
+done
+"#,
+        );
+        let path: PathBuf = env::temp_dir().join(format!("chessterm-stub-{name}-{}.sh", std::process::id()));
+        fs::write(&path, script).expect("The stub engine should be written");
+        path
+    }
+
+    fn spawn(path: &Path) -> anyhow::Result<UciEngine> {
+        UciEngine::spawn("sh", &[path.to_str().expect("The temporary directory should be UTF-8")])
+    }
+
+    #[test]
+    fn test_engine_plays() -> anyhow::Result<()> {
+        let path: PathBuf = stub_engine("plays", r#"echo "info depth 3 seldepth 5 score cp 31 nodes 1200 time 15 pv e2e4 e7e5"; echo "bestmove e2e4 ponder e7e5""#);
+        let mut engine: UciEngine = spawn(&path)?;
+        let mut iterations: usize = 0;
+
+        let result: SearchResult = engine.search(&ChessEngine::new(), SearchLimits::depth(3), &mut |_| iterations += 1);
+
+        assert_eq!("Stub plays", engine.name());
+        assert_eq!(1, iterations);
+        assert_eq!(parse_move("e2e4").ok(), result.best_move());
+        assert_eq!(Score::centipawns(31), result.score());
+        assert_eq!(None, engine.failure());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_nodes() -> anyhow::Result<()> {
+        // The node limit is the only one, without it the search never ends
+        let path: PathBuf = stub_engine("nodes", r#"[ "$line" = "go nodes 1000" ] && echo "bestmove e2e4" || echo "bestmove d2d4""#);
+        let mut engine: UciEngine = spawn(&path)?;
+
+        let result: SearchResult = engine.choose_move(&ChessEngine::new(), SearchLimits::default().with_nodes(1000));
+
+        assert_eq!(parse_move("e2e4").ok(), result.best_move());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_crash() -> anyhow::Result<()> {
+        let path: PathBuf = stub_engine("crash", "exit 1");
+        let mut engine: UciEngine = spawn(&path)?;
+
+        let result: SearchResult = engine.choose_move(&ChessEngine::new(), SearchLimits::depth(3));
+
+        assert_eq!(None, result.best_move());
+        assert!(engine.failure().is_some_and(|failure| failure.contains("exited")), "{:?}", engine.failure());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_timeout() -> anyhow::Result<()> {
+        let path: PathBuf = stub_engine("timeout", "true");
+        let mut engine: UciEngine = spawn(&path)?;
+
+        let result: SearchResult = engine.choose_move(&ChessEngine::new(), SearchLimits::movetime(Duration::from_millis(10)));
+
+        assert_eq!(None, result.best_move());
+        assert!(engine.failure().is_some_and(|failure| failure.contains("in time")), "{:?}", engine.failure());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_illegal_move() -> anyhow::Result<()> {
+        let path: PathBuf = stub_engine("illegal", "echo 'bestmove e2e5'");
+        let mut engine: UciEngine = spawn(&path)?;
+
+        let result: SearchResult = engine.choose_move(&ChessEngine::new(), SearchLimits::depth(1));
+
+        assert_eq!(None, result.best_move());
+        assert!(engine.failure().is_some_and(|failure| failure.contains("illegal")));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_promotion() -> anyhow::Result<()> {
+        let path: PathBuf = stub_engine("promotion", "echo 'bestmove e2e4q'");
+        let mut engine: UciEngine = spawn(&path)?;
+
+        let result: SearchResult = engine.choose_move(&ChessEngine::new(), SearchLimits::depth(1));
+
+        assert_eq!(None, result.best_move());
+        assert!(engine.failure().is_some_and(|failure| failure.contains("can't play")), "{:?}", engine.failure());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_engine() {
+        assert!(UciEngine::spawn("/nonexistent/chessterm-engine", &[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_info() {
+        let result: Option<SearchResult> = parse_info("depth 7 seldepth 9 multipv 1 score mate -2 nodes 10 nps 100 time 250 pv e7e5 g1f3", Duration::ZERO);
+
+        let result: SearchResult = result.expect("The line has a principal variation");
+        assert_eq!(Score::mated_in(4), result.score());
+        assert_eq!((7, 9, 10), (result.depth(), result.seldepth(), result.nodes()));
+        assert_eq!(Duration::from_millis(250), result.elapsed());
+        assert_eq!(2, result.pv().len());
+        assert_eq!(None, parse_info("depth 3 currmove e2e4 currmovenumber 1", Duration::ZERO));
+        assert_eq!(None, parse_info("string hello", Duration::ZERO));
+    }
+}
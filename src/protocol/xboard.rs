@@ -0,0 +1,369 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::bot::{self, Bot};
+use crate::bot::search::{time_budget, SearchLimits, SearchResult};
+use crate::game::{ChessEngine, Result as GameResult};
+use crate::game::board::color::Color;
+use crate::game::fen_parser::FenParser;
+
+use super::{format_move, parse_move, play_moves};
+
+const DEFAULT_BOT: &str = "alpha-beta";
+const PROTOCOL_VERSION: u32 = 2;
+// Used until the interface sets a time control
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(5);
+
+/// The Chess Engine Communication Protocol (version 2) of XBoard and
+/// WinBoard. Unlike UCI the engine keeps the game itself, and searches on
+/// the thread reading the commands
+pub(crate) struct XBoard<W: Write> {
+    output: W,
+    chess_game: ChessEngine,
+    bot: Box<dyn Bot>,
+    // None in force mode, where the engine only follows the moves it is told
+    engine_color: Option<Color>,
+    depth: Option<i16>,
+    movetime: Option<Duration>,
+    clock: Option<Duration>,
+    increment: Duration,
+    moves_per_session: Option<u32>,
+}
+
+impl<W: Write> XBoard<W> {
+    pub(crate) fn new(output: W) -> Result<Self> {
+        Ok(Self {
+            output,
+            chess_game: ChessEngine::new(),
+            bot: bot::from_name(DEFAULT_BOT)?,
+            engine_color: Some(Color::Black),
+            depth: None,
+            movetime: None,
+            clock: None,
+            increment: Duration::ZERO,
+            moves_per_session: None,
+        })
+    }
+
+    /// Reads commands until "quit" or the end of the input
+    pub(crate) fn run(&mut self, input: impl BufRead) -> Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns false once the engine has to quit
+    fn handle(&mut self, line: &str) -> Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+
+        let outcome: Result<()> = match *command {
+            "xboard" | "accepted" | "rejected" | "random" | "post" | "nopost" | "hard" | "easy" | "computer" | "otim" => Ok(()),
+            "protover" => self.protover(),
+            "ping" => self.send(&format!("pong {}", args.join(" "))),
+            "new" => {
+                self.chess_game = ChessEngine::new();
+                self.bot.new_game();
+                self.engine_color = Some(Color::Black);
+                self.depth = None;
+                self.movetime = None;
+                Ok(())
+            }
+            "setboard" => FenParser::parse(&args.join(" ")).map(|chess_game| self.chess_game = chess_game),
+            "force" | "result" => {
+                self.engine_color = None;
+                Ok(())
+            }
+            "go" => {
+                self.engine_color = Some(self.chess_game.current_player());
+                self.think()
+            }
+            "usermove" => self.user_move(args.first().copied().unwrap_or_default()),
+            "undo" => self.undo(1),
+            "remove" => self.undo(2),
+            "level" => self.level(args),
+            "st" => {
+                let seconds: u64 = number(args, "st")?;
+                self.movetime = Some(Duration::from_secs(seconds));
+                Ok(())
+            }
+            "sd" => {
+                self.depth = Some(number(args, "sd")?);
+                Ok(())
+            }
+            "time" => {
+                let centiseconds: u64 = number(args, "time")?;
+                self.clock = Some(Duration::from_millis(centiseconds.saturating_mul(10)));
+                Ok(())
+            }
+            "quit" => return Ok(false),
+            // Older interfaces send the moves without "usermove"
+            _ if parse_move(command).is_ok() => self.user_move(command),
+            _ => {
+                self.send(&format!("Error (unknown command): {command}"))?;
+                Ok(())
+            }
+        };
+
+        if let Err(error) = outcome {
+            self.send(&format!("Error ({error}): {line}"))?;
+        }
+
+        Ok(true)
+    }
+
+    fn protover(&mut self) -> Result<()> {
+        self.send(&format!(
+            "feature done=0 myname=\"chessterm {}\" protover={PROTOCOL_VERSION} usermove=1 setboard=1 ping=1 time=1 \
+             draw=0 sigint=0 sigterm=0 reuse=1 analyze=0 colors=0",
+            env!("CARGO_PKG_VERSION"),
+        ))?;
+        self.send("feature done=1")
+    }
+
+    fn user_move(&mut self, notation: &str) -> Result<()> {
+        if play_moves(&mut self.chess_game, &[notation]).is_err() {
+            return self.send(&format!("Illegal move: {notation}"));
+        }
+
+        if self.chess_game.is_end() {
+            return self.report_result();
+        }
+        if self.engine_color == Some(self.chess_game.current_player()) {
+            return self.think();
+        }
+
+        Ok(())
+    }
+
+    /// Searches the current position and plays the best move
+    fn think(&mut self) -> Result<()> {
+        if self.chess_game.is_end() {
+            return self.report_result();
+        }
+
+        let result: SearchResult = self.bot.choose_move(&self.chess_game, self.limits());
+        let Some((from, to)) = result.best_move() else {
+            bail!("no move found");
+        };
+
+        self.chess_game.try_move(Some(from), Some(to));
+        self.send(&format!("move {}", format_move((from, to))))?;
+
+        if self.chess_game.is_end() {
+            self.report_result()?;
+        }
+        Ok(())
+    }
+
+    fn limits(&self) -> SearchLimits {
+        let movetime: Duration = match (self.movetime, self.clock) {
+            (Some(movetime), _) => movetime,
+            (None, Some(clock)) => {
+                let moves_to_go: Option<u32> = self.moves_per_session.map(|moves| {
+                    let played: u32 = (self.chess_game.history().len() / 2) as u32;
+                    moves - played % moves
+                });
+                time_budget(clock, self.increment, moves_to_go)
+            }
+            (None, None) => DEFAULT_MOVETIME,
+        };
+
+        let limits: SearchLimits = SearchLimits::movetime(movetime);
+        match self.depth {
+            Some(depth) => limits.with_depth(depth),
+            None => limits,
+        }
+    }
+
+    fn undo(&mut self, moves: usize) -> Result<()> {
+        if self.chess_game.history().len() < moves {
+            bail!("not enough moves to undo");
+        }
+
+        for _ in 0..moves {
+            self.chess_game.undo_move();
+        }
+        Ok(())
+    }
+
+    /// `level <moves per session> <minutes[:seconds]> <increment seconds>`
+    fn level(&mut self, args: &[&str]) -> Result<()> {
+        let [moves, base, increment] = args else {
+            bail!("level expects 3 arguments");
+        };
+
+        let moves: u32 = moves.parse().context("invalid moves per session")?;
+        let (minutes, seconds): (&str, &str) = base.split_once(':').unwrap_or((base, "0"));
+        let base: Duration = minutes
+            .parse::<u64>()
+            .ok()
+            .and_then(|minutes| minutes.checked_mul(60))
+            .context("invalid minutes")?
+            .checked_add(seconds.parse::<u64>().context("invalid seconds")?)
+            .map(Duration::from_secs)
+            .context("invalid seconds")?;
+        // Negative, NaN and infinite increments come from stdin as well
+        let increment: Duration = increment
+            .parse::<f64>()
+            .ok()
+            .and_then(|increment| Duration::try_from_secs_f64(increment).ok())
+            .context("invalid increment")?;
+
+        self.moves_per_session = (moves > 0).then_some(moves);
+        self.clock = Some(base);
+        self.increment = increment;
+        self.movetime = None;
+        Ok(())
+    }
+
+    fn report_result(&mut self) -> Result<()> {
+        let result: &str = match (self.chess_game.result(), self.chess_game.current_player()) {
+            (GameResult::Checkmate, Color::Black) => "1-0 {White mates}",
+            (GameResult::Checkmate, _) => "0-1 {Black mates}",
+            (GameResult::Stalemate, _) => "1/2-1/2 {Stalemate}",
+            (GameResult::Draw, _) => "1/2-1/2 {Draw by repetition}",
+            (GameResult::None, _) => return Ok(()),
+        };
+
+        self.engine_color = None;
+        self.send(result)
+    }
+
+    fn send(&mut self, line: &str) -> Result<()> {
+        writeln!(self.output, "{line}")?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+fn number<T: std::str::FromStr>(args: &[&str], command: &str) -> Result<T> {
+    args
+        .first()
+        .and_then(|arg| arg.parse().ok())
+        .with_context(|| format!("{command} expects a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+
+    use super::XBoard;
+
+    fn lines(xboard: &XBoard<Vec<u8>>) -> Vec<String> {
+        String::from_utf8(xboard.output.clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_protover() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("xboard\nprotover 2\nping 7\nquit\nping 8\n".as_bytes())?;
+
+        let lines: Vec<String> = lines(&xboard);
+        assert!(lines[0].starts_with("feature done=0 myname=\"chessterm"));
+        assert!(lines[0].contains("usermove=1"));
+        assert_eq!(["feature done=1", "pong 7"], lines[1..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_replies() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("new\nsd 1\nusermove e2e4\n".as_bytes())?;
+
+        let lines: Vec<String> = lines(&xboard);
+        assert_eq!(1, lines.len());
+        assert!(lines[0].starts_with("move "), "{lines:?}");
+        assert_eq!(2, xboard.chess_game.history().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_force_undo_remove() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("new\nforce\nusermove e2e4\ne7e5\ng1f3\nundo\n".as_bytes())?;
+        assert!(lines(&xboard).is_empty());
+        assert_eq!(2, xboard.chess_game.history().len());
+
+        xboard.run("remove\n".as_bytes())?;
+        assert_eq!(ChessEngine::new().key(), xboard.chess_game.key());
+
+        xboard.run("undo\n".as_bytes())?;
+        assert_eq!(["Error (not enough moves to undo): undo"], lines(&xboard)[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_mates() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("new\nforce\nsetboard 6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1\nsd 2\ngo\n".as_bytes())?;
+
+        assert_eq!(["move e1e8", "1-0 {White mates}"], lines(&xboard)[..]);
+        assert_eq!(None, xboard.engine_color);
+        Ok(())
+    }
+
+    #[test]
+    fn test_illegal_and_unknown() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("usermove e2e5\nfoo\nsetboard 8/8\n".as_bytes())?;
+
+        let lines: Vec<String> = lines(&xboard);
+        assert_eq!("Illegal move: e2e5", lines[0]);
+        assert_eq!("Error (unknown command): foo", lines[1]);
+        assert!(lines[2].starts_with("Error (A FEN should have 6 parts"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_controls() -> anyhow::Result<()> {
+        let mut xboard: XBoard<Vec<u8>> = XBoard::new(Vec::new())?;
+
+        xboard.run("level 40 5 0\n".as_bytes())?;
+        assert_eq!(Some(Duration::from_millis(7_500)), xboard.limits().max_movetime());
+
+        xboard.run("level 0 2:30 1\ntime 6000\n".as_bytes())?;
+        assert_eq!(Some(Duration::from_millis(2_750)), xboard.limits().max_movetime());
+
+        // The time control is kept
+        xboard.run("level 40 5 -1\nlevel 40 5 NaN\nlevel 40 5 inf\nlevel 40 999999999999999999 0\n".as_bytes())?;
+        assert_eq!(
+            [
+                "Error (invalid increment): level 40 5 -1",
+                "Error (invalid increment): level 40 5 NaN",
+                "Error (invalid increment): level 40 5 inf",
+                "Error (invalid minutes): level 40 999999999999999999 0",
+            ],
+            lines(&xboard)[..]
+        );
+        assert_eq!(Some(Duration::from_millis(2_750)), xboard.limits().max_movetime());
+
+        xboard.run("st 3\nsd 4\n".as_bytes())?;
+        assert_eq!(Some(Duration::from_secs(3)), xboard.limits().max_movetime());
+        assert_eq!(4, xboard.limits().max_depth());
+        assert_eq!(Some(Color::Black), xboard.engine_color);
+        Ok(())
+    }
+}
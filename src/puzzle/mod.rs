@@ -0,0 +1,339 @@
+use anyhow::{bail, Context, Result};
+
+use crate::game::{ChessEngine, Result as GameResult};
+use crate::game::board::position::Position;
+use crate::game::fen_parser::FenParser;
+use crate::game::san::from_san;
+use crate::protocol::parse_move;
+
+pub(crate) mod stats;
+
+/// A position to solve, with the moves of both sides that solve it
+#[derive(Clone, Debug)]
+pub(crate) struct Puzzle {
+    id: String,
+    // The side to move is the player's
+    chess_game: ChessEngine,
+    // The player's moves and the replies, in turn
+    solution: Vec<(Position, Position)>,
+    rating: Option<u32>,
+    themes: Vec<String>,
+}
+
+impl Puzzle {
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) const fn chess_game(&self) -> &ChessEngine {
+        &self.chess_game
+    }
+
+    pub(crate) const fn rating(&self) -> Option<u32> {
+        self.rating
+    }
+
+    pub(crate) fn themes(&self) -> &[String] {
+        &self.themes
+    }
+
+    /// A line of Lichess' CSV export: `PuzzleId,FEN,Moves,Rating,...,Themes,...`.
+    /// The first move is the opponent's, the puzzle starts after it
+    fn from_csv(line: &str) -> Result<Self> {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [id, fen, moves, rating, ..] = fields[..] else {
+            bail!("A puzzle needs an id, a FEN, moves and a rating");
+        };
+
+        let mut chess_game: ChessEngine = FenParser::parse(fen)?;
+        let mut moves = moves.split_whitespace();
+        let setup: &str = moves.next().context("A puzzle needs moves")?;
+        let (from, to): (Position, Position) = parse_move(setup)?;
+        if !chess_game.try_move(Some(from), Some(to)) {
+            bail!("Illegal move \"{setup}\"");
+        }
+        let solution: Vec<(Position, Position)> = moves
+            .map(parse_move)
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            id: id.to_string(),
+            solution: checked(&chess_game, solution)?,
+            chess_game,
+            rating: rating.parse().ok(),
+            themes: fields.get(7).map(|themes| themes.split_whitespace().map(String::from).collect()).unwrap_or_default(),
+        })
+    }
+
+    /// An EPD line: 4 FEN fields then `id`, `bm` (the first move, in SAN)
+    /// or `pv` (the whole solution, in SAN), and optionally `rating` and
+    /// `themes`
+    fn from_epd(line: &str) -> Result<Self> {
+        let fields: Vec<&str> = line.splitn(5, char::is_whitespace).collect();
+        let [position @ .., operations] = &fields[..] else {
+            bail!("An EPD needs 4 fields and operations");
+        };
+        if position.len() != 4 {
+            bail!("An EPD needs 4 fields and operations");
+        }
+        let chess_game: ChessEngine = FenParser::parse(&format!("{} 0 1", position.join(" ")))?;
+
+        let mut puzzle: Self = Self {
+            id: String::new(),
+            chess_game,
+            solution: Vec::new(),
+            rating: None,
+            themes: Vec::new(),
+        };
+        for operation in operations.split(';').map(str::trim).filter(|operation| !operation.is_empty()) {
+            let (opcode, operand): (&str, &str) = operation.split_once(char::is_whitespace).unwrap_or((operation, ""));
+            let operand: &str = operand.trim().trim_matches('"');
+            match opcode {
+                "id" => puzzle.id = operand.to_string(),
+                "rating" => puzzle.rating = operand.parse().ok(),
+                "themes" => puzzle.themes = operand.split_whitespace().map(String::from).collect(),
+                // A complete line wins over the first of the best moves
+                "bm" if puzzle.solution.is_empty() => {
+                    let best_move: &str = operand.split_whitespace().next().unwrap_or_default();
+                    puzzle.solution = san_line(&puzzle.chess_game, best_move)?;
+                }
+                "pv" => puzzle.solution = san_line(&puzzle.chess_game, operand)?,
+                _ => {}
+            }
+        }
+        if puzzle.solution.is_empty() {
+            bail!("An EPD puzzle needs a bm or a pv operation");
+        }
+
+        Ok(puzzle)
+    }
+}
+
+/// The moves of a line in SAN, played from `chess_game`
+fn san_line(chess_game: &ChessEngine, line: &str) -> Result<Vec<(Position, Position)>> {
+    let mut replay: ChessEngine = chess_game.clone();
+
+    line
+        .split_whitespace()
+        .map(|san| {
+            let (from, to): (Position, Position) = from_san(&replay, san).with_context(|| format!("Illegal move \"{san}\""))?;
+            replay.try_move(Some(from), Some(to));
+            Ok((from, to))
+        })
+        .collect()
+}
+
+/// `solution`, once every move of it is known to be legal
+fn checked(chess_game: &ChessEngine, solution: Vec<(Position, Position)>) -> Result<Vec<(Position, Position)>> {
+    let mut replay: ChessEngine = chess_game.clone();
+
+    for &(from, to) in &solution {
+        if !replay.try_move(Some(from), Some(to)) {
+            bail!("Illegal move \"{}{}\"", from.to_notation(), to.to_notation());
+        }
+    }
+    if solution.is_empty() {
+        bail!("A puzzle needs a solution");
+    }
+
+    Ok(solution)
+}
+
+/// Puzzles from Lichess' CSV export or from EPD, one per line. Puzzles
+/// without an id are numbered after their line
+pub(crate) fn read_puzzles(content: &str) -> Result<Vec<Puzzle>> {
+    content
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#') && !line.starts_with("PuzzleId"))
+        .map(|(index, line)| {
+            let puzzle: Result<Puzzle> = if line.contains(',') { Puzzle::from_csv(line) } else { Puzzle::from_epd(line) };
+            let mut puzzle: Puzzle = puzzle.with_context(|| format!("Invalid puzzle on line {}: {line}", index + 1))?;
+            if puzzle.id.is_empty() {
+                puzzle.id = (index + 1).to_string();
+            }
+            Ok(puzzle)
+        })
+        .collect()
+}
+
+/// What a move of the player did to the puzzle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Verdict {
+    // The expected move, the opponent replies next
+    Correct,
+    Solved,
+    Wrong,
+    Illegal,
+}
+
+/// A puzzle being solved: the player's moves are checked against the
+/// solution, and the opponent's replies come from it
+pub(crate) struct Attempt<'a> {
+    puzzle: &'a Puzzle,
+    chess_game: ChessEngine,
+    // Moves of the solution played so far
+    played: usize,
+}
+
+impl<'a> Attempt<'a> {
+    pub(crate) fn new(puzzle: &'a Puzzle) -> Self {
+        Self {
+            puzzle,
+            chess_game: puzzle.chess_game.clone(),
+            played: 0,
+        }
+    }
+
+    pub(crate) const fn chess_game(&self) -> &ChessEngine {
+        &self.chess_game
+    }
+
+    /// The move the solution expects from the player
+    pub(crate) fn expected(&self) -> Option<(Position, Position)> {
+        self.remaining().first().copied()
+    }
+
+    /// The moves of the solution still to play, the player's first
+    pub(crate) fn remaining(&self) -> &[(Position, Position)] {
+        &self.puzzle.solution[self.played..]
+    }
+
+    /// Plays the player's move. Any mate solves the puzzle, even when the
+    /// solution mates otherwise
+    pub(crate) fn play(&mut self, (from, to): (Position, Position)) -> Verdict {
+        if !self.chess_game.try_move(Some(from), Some(to)) {
+            return Verdict::Illegal;
+        }
+
+        if self.chess_game.result() == GameResult::Checkmate {
+            self.played = self.puzzle.solution.len();
+            return Verdict::Solved;
+        }
+        if self.expected() != Some((from, to)) {
+            return Verdict::Wrong;
+        }
+
+        self.played += 1;
+        if self.played + 1 >= self.puzzle.solution.len() {
+            // The opponent's last move adds nothing to the solution
+            self.played = self.puzzle.solution.len();
+            Verdict::Solved
+        } else {
+            Verdict::Correct
+        }
+    }
+
+    /// Plays the opponent's reply from the solution
+    pub(crate) fn reply(&mut self) -> Option<(Position, Position)> {
+        let (from, to): (Position, Position) = self.expected()?;
+        self.chess_game.try_move(Some(from), Some(to));
+        self.played += 1;
+
+        Some((from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::board::position::Position;
+
+    use super::{read_puzzles, Attempt, Puzzle, Verdict};
+
+    // Lichess' format: the first move is the opponent's
+    const CSV: &str = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags\n\
+                       00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game";
+    const EPD: &str = "# Comments and blank lines are skipped\n\
+                       \n\
+                       6k1/5ppp/8/8/8/8/5PPP/4R1K1 w - - bm Re8#; id \"back rank\"; rating 900; themes mate mateIn1;\n\
+                       7k/8/6K1/8/8/8/8/R7 w - - pv Ra8#;";
+
+    fn moves(notations: &[&str]) -> Vec<(Position, Position)> {
+        notations
+            .iter()
+            .map(|notation| (Position::from_notation(&notation[..2]).unwrap(), Position::from_notation(&notation[2..]).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_csv() -> Result<()> {
+        let puzzles: Vec<Puzzle> = read_puzzles(CSV)?;
+
+        assert_eq!(1, puzzles.len());
+        assert_eq!("00sHx", puzzles[0].id());
+        assert_eq!(Some(1760), puzzles[0].rating());
+        assert_eq!(["mate", "mateIn2", "middlegame", "short"], puzzles[0].themes());
+        assert_eq!(moves(&["a2e6", "d7d8", "f7f8"]), puzzles[0].solution);
+        assert_eq!(1, puzzles[0].chess_game().history().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_epd() -> Result<()> {
+        let puzzles: Vec<Puzzle> = read_puzzles(EPD)?;
+
+        assert_eq!(2, puzzles.len());
+        assert_eq!("back rank", puzzles[0].id());
+        assert_eq!(Some(900), puzzles[0].rating());
+        assert_eq!(["mate", "mateIn1"], puzzles[0].themes());
+        assert_eq!(moves(&["e1e8"]), puzzles[0].solution);
+        // Numbered after its line
+        assert_eq!("4", puzzles[1].id());
+        assert_eq!(moves(&["a1a8"]), puzzles[1].solution);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("1,8/8/8/8/8/8/8/8 w - - 0 1,e2e4,1000")]
+    #[case("1,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a1a8,1000")]
+    // The engine can't promote
+    #[case("1,k7/8/8/8/8/8/4p3/K7 w - - 0 1,a1b1 e2e1q,1000")]
+    #[case("7k/8/8/8/8/8/8/R5K1 w - - id \"no solution\";")]
+    #[case("7k/8/8/8/8/8/8/R5K1 w - - bm Qa8#;")]
+    fn test_read_invalid(
+        #[case]
+        line: &str
+    ) {
+        assert!(read_puzzles(line).is_err());
+    }
+
+    #[test]
+    fn test_attempt_replies_until_solved() -> Result<()> {
+        let puzzles: Vec<Puzzle> = read_puzzles(CSV)?;
+        let mut attempt: Attempt = Attempt::new(&puzzles[0]);
+
+        assert_eq!(Verdict::Correct, attempt.play(moves(&["a2e6"])[0]));
+        assert_eq!(Some(moves(&["d7d8"])[0]), attempt.reply());
+        assert_eq!(Verdict::Solved, attempt.play(moves(&["f7f8"])[0]));
+        assert_eq!(None, attempt.expected());
+        Ok(())
+    }
+
+    #[test]
+    fn test_attempt_accepts_another_mate() -> Result<()> {
+        // Both rooks mate on the back rank
+        let puzzles: Vec<Puzzle> = read_puzzles("6k1/5ppp/8/8/8/8/5PPP/R3R1K1 w - - pv Re8#;")?;
+
+        let mut attempt: Attempt = Attempt::new(&puzzles[0]);
+        assert_eq!(Verdict::Solved, attempt.play(moves(&["a1a8"])[0]));
+
+        let mut attempt: Attempt = Attempt::new(&puzzles[0]);
+        assert_eq!(Verdict::Wrong, attempt.play(moves(&["a1a7"])[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_attempt_wrong_move() -> Result<()> {
+        let puzzles: Vec<Puzzle> = read_puzzles(CSV)?;
+        let mut attempt: Attempt = Attempt::new(&puzzles[0]);
+
+        assert_eq!(Verdict::Illegal, attempt.play(moves(&["a2a4"])[0]));
+        assert_eq!(Verdict::Wrong, attempt.play(moves(&["f7g7"])[0]));
+        Ok(())
+    }
+}
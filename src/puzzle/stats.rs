@@ -0,0 +1,117 @@
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::ErrorKind;
+
+use anyhow::{Context, Result};
+
+/// The puzzles solved and failed over every session, kept in a small text
+/// file of `name value` lines
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct PuzzleStats {
+    solved: u32,
+    failed: u32,
+    // Puzzles solved in a row, up to the last one
+    streak: u32,
+    best_streak: u32,
+}
+
+impl PuzzleStats {
+    /// The stats saved at `path`, none yet when there is no such file
+    pub(crate) fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).with_context(|| format!("Invalid puzzle stats in {path}")),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("Could not read {path}")),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, self.to_file()).with_context(|| format!("Could not write {path}"))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut stats: Self = Self::default();
+
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, value) = line.split_once(' ').with_context(|| format!("Expected a name and a value: {line}"))?;
+            let value: u32 = value.trim().parse().with_context(|| format!("Expected a number: {line}"))?;
+            match name {
+                "solved" => stats.solved = value,
+                "failed" => stats.failed = value,
+                "streak" => stats.streak = value,
+                "best_streak" => stats.best_streak = value,
+                // Written by a newer version
+                _ => {}
+            }
+        }
+
+        Ok(stats)
+    }
+
+    fn to_file(self) -> String {
+        format!(
+            "solved {}\nfailed {}\nstreak {}\nbest_streak {}\n",
+            self.solved, self.failed, self.streak, self.best_streak
+        )
+    }
+
+    pub(crate) fn record(&mut self, solved: bool) {
+        if solved {
+            self.solved += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.failed += 1;
+            self.streak = 0;
+        }
+    }
+}
+
+impl Display for PuzzleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Solved {}, failed {}, streak {} (best {})",
+            self.solved, self.failed, self.streak, self.best_streak
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::PuzzleStats;
+
+    #[test]
+    fn test_record() {
+        let mut stats: PuzzleStats = PuzzleStats::default();
+
+        for solved in [true, true, false, true] {
+            stats.record(solved);
+        }
+
+        assert_eq!("Solved 3, failed 1, streak 1 (best 2)", stats.to_string());
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let path: String = env::temp_dir().join(format!("chessterm-puzzles-{}.txt", std::process::id())).display().to_string();
+        assert_eq!(PuzzleStats::default(), PuzzleStats::load(&path)?);
+
+        let mut stats: PuzzleStats = PuzzleStats::default();
+        stats.record(true);
+        stats.record(false);
+        stats.save(&path)?;
+        let loaded: PuzzleStats = PuzzleStats::load(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(stats, loaded);
+        assert!(PuzzleStats::parse("solved many").is_err());
+        Ok(())
+    }
+}
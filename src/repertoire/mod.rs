@@ -0,0 +1,304 @@
+use anyhow::{bail, Result};
+
+use crate::bot::rng::Rng;
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::fen_writer::FenWriter;
+use crate::game::pgn::read_lines;
+use crate::protocol::format_move;
+
+use schedule::Schedule;
+
+pub(crate) mod schedule;
+
+/// One line of the repertoire, from its first move to its last
+#[derive(Clone, Debug)]
+struct Line {
+    start: ChessEngine,
+    moves: Vec<(Position, Position)>,
+    // Names the line in the schedule
+    id: String,
+}
+
+impl Line {
+    fn new(chess_game: &ChessEngine) -> Self {
+        let start: ChessEngine = chess_game.starting_position();
+        let moves: Vec<(Position, Position)> = chess_game.history();
+        let mut id: String = moves.iter().map(|&played| format_move(played)).collect::<Vec<String>>().join(" ");
+        let fen: String = FenWriter::write(&start);
+        if fen != FenWriter::write(&ChessEngine::new()) {
+            id = format!("{fen}: {id}");
+        }
+
+        Self {
+            start,
+            moves,
+            id,
+        }
+    }
+
+    /// The move of the line after `chess_game`, when the game follows it
+    fn next(&self, chess_game: &ChessEngine) -> Option<(Position, Position)> {
+        let history: Vec<(Position, Position)> = chess_game.history();
+
+        if chess_game.starting_position().key() == self.start.key() && self.moves.starts_with(&history) {
+            self.moves.get(history.len()).copied()
+        } else {
+            None
+        }
+    }
+}
+
+/// The lines prepared for one side, read from the variations of PGN games
+pub(crate) struct Repertoire {
+    lines: Vec<Line>,
+    color: Color,
+}
+
+impl Repertoire {
+    pub(crate) fn new(pgn: &str, color: Color) -> Result<Self> {
+        let all: Vec<Line> = read_lines(pgn)?.iter().map(Line::new).collect();
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, line) in all.iter().enumerate() {
+            // A line inside another adds nothing to drill
+            let inside = |other: &Line| other.start.key() == line.start.key() && other.moves.starts_with(&line.moves);
+            let known: bool = all[..index].iter().any(|other| other.moves == line.moves && inside(other));
+            let longer: bool = all.iter().any(|other| other.moves.len() > line.moves.len() && inside(other));
+            if !line.moves.is_empty() && !known && !longer {
+                lines.push(line.clone());
+            }
+        }
+        if lines.is_empty() {
+            bail!("The repertoire has no moves");
+        }
+
+        Ok(Self {
+            lines,
+            color,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The lines `chess_game` follows, that go on after it
+    fn following<'a>(&'a self, chess_game: &'a ChessEngine) -> impl Iterator<Item = (&'a Line, (Position, Position))> {
+        self.lines.iter().filter_map(move |line| line.next(chess_game).map(|next| (line, next)))
+    }
+
+    /// A drill from the start of a line, the lines due first being the
+    /// likeliest
+    pub(crate) fn drill(&self, schedule: &Schedule, rng: &mut Rng) -> Drill<'_> {
+        let weights: Vec<f64> = self.lines.iter().map(|line| schedule.weight(&line.id)).collect();
+
+        Drill {
+            repertoire: self,
+            chess_game: self.lines[pick(&weights, rng)].start.clone(),
+        }
+    }
+}
+
+/// An index of `weights` drawn at random, in proportion to its weight
+fn pick(weights: &[f64], rng: &mut Rng) -> usize {
+    let mut drawn: f64 = rng.unit() * weights.iter().sum::<f64>();
+
+    for (index, weight) in weights.iter().enumerate() {
+        drawn -= weight;
+        if drawn < 0.0 {
+            return index;
+        }
+    }
+
+    weights.len() - 1
+}
+
+/// What a move of the player did to the drill
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Verdict {
+    // A prepared move, the line goes on
+    Prepared,
+    // A prepared move ending the line
+    Complete,
+    // Not the prepared moves, listed
+    Deviation(Vec<(Position, Position)>),
+}
+
+/// A line of the repertoire being played: the player's moves are checked
+/// against the prepared ones, the opponent picks among the branches
+pub(crate) struct Drill<'a> {
+    repertoire: &'a Repertoire,
+    chess_game: ChessEngine,
+}
+
+impl Drill<'_> {
+    pub(crate) const fn chess_game(&self) -> &ChessEngine {
+        &self.chess_game
+    }
+
+    /// Whether the player is the one to move
+    pub(crate) fn players_turn(&self) -> bool {
+        self.chess_game.current_player() == self.repertoire.color
+    }
+
+    /// The moves prepared in the position, each once
+    pub(crate) fn prepared(&self) -> Vec<(Position, Position)> {
+        let mut prepared: Vec<(Position, Position)> = Vec::new();
+        for (_, next) in self.repertoire.following(&self.chess_game) {
+            if !prepared.contains(&next) {
+                prepared.push(next);
+            }
+        }
+
+        prepared
+    }
+
+    /// Whether the lines played are over
+    pub(crate) fn is_complete(&self) -> bool {
+        self.repertoire.following(&self.chess_game).next().is_none()
+    }
+
+    /// The opponent's move, one of the branches of the repertoire, the
+    /// branches with the lines due being the likeliest
+    pub(crate) fn reply(&mut self, schedule: &Schedule, rng: &mut Rng) -> Option<(Position, Position)> {
+        let branches: Vec<(&Line, (Position, Position))> = self.repertoire.following(&self.chess_game).collect();
+        if branches.is_empty() {
+            return None;
+        }
+
+        let weights: Vec<f64> = branches.iter().map(|(line, _)| schedule.weight(&line.id)).collect();
+        let (_, (from, to)) = branches[pick(&weights, rng)];
+        self.chess_game.try_move(Some(from), Some(to));
+        Some((from, to))
+    }
+
+    /// Plays the player's move. A deviation sends the lines the player
+    /// should have followed back to the first box, a completed line goes up
+    /// one box
+    pub(crate) fn play(&mut self, played: (Position, Position), schedule: &mut Schedule) -> Verdict {
+        let following: Vec<(&Line, (Position, Position))> = self.repertoire.following(&self.chess_game).collect();
+        if !following.iter().any(|&(_, next)| next == played) {
+            for (line, _) in &following {
+                schedule.demote(&line.id);
+            }
+            let prepared: Vec<(Position, Position)> = self.prepared();
+            self.chess_game.try_move(Some(played.0), Some(played.1));
+            return Verdict::Deviation(prepared);
+        }
+
+        self.chess_game.try_move(Some(played.0), Some(played.1));
+        if self.is_complete() {
+            self.complete(schedule);
+            Verdict::Complete
+        } else {
+            Verdict::Prepared
+        }
+    }
+
+    /// Moves up the lines that end in the position
+    pub(crate) fn complete(&self, schedule: &mut Schedule) {
+        let history: Vec<(Position, Position)> = self.chess_game.history();
+
+        for line in &self.repertoire.lines {
+            if line.moves == history && line.start.key() == self.chess_game.starting_position().key() {
+                schedule.promote(&line.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::rng::Rng;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::protocol::parse_move;
+
+    use super::schedule::Schedule;
+    use super::{Drill, Repertoire, Verdict};
+
+    // Black answers 1. e4 with the Sicilian and 1. d4 with the Nimzo-Indian
+    const REPERTOIRE: &str = "1. e4 c5 2. Nf3 (2. c3 d5) d6 (2... Nc6) * \n\n1. d4 Nf6 2. c4 e6 3. Nc3 Bb4 *\n";
+
+    fn played(notation: &str) -> (Position, Position) {
+        parse_move(notation).expect("A valid move")
+    }
+
+    #[test]
+    fn test_repertoire_lines() -> Result<()> {
+        let repertoire: Repertoire = Repertoire::new(REPERTOIRE, Color::Black)?;
+
+        assert_eq!(4, repertoire.len());
+        // 1. e4 alone is inside the other lines
+        assert_eq!(2, Repertoire::new("1. e4 e5 (1... c5) * 1. e4 *", Color::White)?.len());
+        assert!(Repertoire::new("*", Color::White).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_follows_the_branches() -> Result<()> {
+        let repertoire: Repertoire = Repertoire::new(REPERTOIRE, Color::Black)?;
+        let mut schedule: Schedule = Schedule::default();
+        let mut rng: Rng = Rng::new(11);
+
+        for _ in 0..10 {
+            let mut drill: Drill = repertoire.drill(&schedule, &mut rng);
+            while !drill.is_complete() {
+                if drill.players_turn() {
+                    let prepared: (Position, Position) = drill.prepared()[0];
+                    assert!(matches!(drill.play(prepared, &mut schedule), Verdict::Prepared | Verdict::Complete));
+                } else {
+                    assert!(drill.reply(&schedule, &mut rng).is_some());
+                }
+            }
+        }
+
+        // Every line played was moved up
+        assert!(["e2e4 c7c5 g1f3 d7d6", "e2e4 c7c5 c2c3 d7d5"].iter().any(|line| schedule.weight(line) < 1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deviation_shows_the_prepared_moves() -> Result<()> {
+        let repertoire: Repertoire = Repertoire::new(REPERTOIRE, Color::Black)?;
+        let mut schedule: Schedule = Schedule::default();
+        schedule.promote("e2e4 c7c5 g1f3 d7d6");
+        schedule.promote("e2e4 c7c5 g1f3 b8c6");
+        let mut drill: Drill = repertoire.drill(&schedule, &mut Rng::new(1));
+        drill.chess_game.try_move(Some(played("e2e4").0), Some(played("e2e4").1));
+        drill.chess_game.try_move(Some(played("c7c5").0), Some(played("c7c5").1));
+        drill.chess_game.try_move(Some(played("g1f3").0), Some(played("g1f3").1));
+
+        let verdict: Verdict = drill.play(played("e7e6"), &mut schedule);
+
+        assert_eq!(Verdict::Deviation(vec![played("b8c6"), played("d7d6")]), verdict);
+        assert_eq!(1.0, schedule.weight("e2e4 c7c5 g1f3 d7d6"));
+        assert_eq!(1.0, schedule.weight("e2e4 c7c5 g1f3 b8c6"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_weak_lines_come_up_more_often() -> Result<()> {
+        let repertoire: Repertoire = Repertoire::new("1. e4 (1. d4) *", Color::Black)?;
+        let mut schedule: Schedule = Schedule::default();
+        for _ in 0..5 {
+            schedule.promote("e2e4");
+        }
+        let mut rng: Rng = Rng::new(3);
+        let mut d4: usize = 0;
+
+        for _ in 0..200 {
+            let mut drill: Drill = repertoire.drill(&schedule, &mut rng);
+            if drill.reply(&schedule, &mut rng) == Some(played("d2d4")) {
+                d4 += 1;
+            }
+        }
+
+        assert!(d4 > 150, "{d4}");
+        Ok(())
+    }
+}
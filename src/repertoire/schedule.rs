@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+
+use anyhow::{Context, Result};
+
+// A line known this many times in a row is drilled the least
+const MAX_BOX: u32 = 5;
+
+/// Spaced repetition of the lines, Leitner style: each line sits in a box,
+/// moved up when played right and back to the first one on a mistake.
+/// A line comes up half as often for each box up. Saved as `box line` lines
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Schedule {
+    boxes: HashMap<String, u32>,
+}
+
+impl Schedule {
+    /// The schedule saved at `path`, every line in the first box when there
+    /// is no such file
+    pub(crate) fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).with_context(|| format!("Invalid schedule in {path}")),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("Could not read {path}")),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &str) -> Result<()> {
+        let mut lines: Vec<String> = self.boxes.iter().map(|(line, level)| format!("{level} {line}")).collect();
+        lines.sort();
+
+        fs::write(path, lines.join("\n") + "\n").with_context(|| format!("Could not write {path}"))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut boxes: HashMap<String, u32> = HashMap::new();
+
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let (level, id) = line.split_once(' ').with_context(|| format!("Expected a box and a line: {line}"))?;
+            boxes.insert(id.to_string(), level.parse().with_context(|| format!("Expected a box number: {line}"))?);
+        }
+
+        Ok(Self {
+            boxes,
+        })
+    }
+
+    /// How often the line comes up, relative to the lines never played
+    pub(crate) fn weight(&self, line: &str) -> f64 {
+        0.5f64.powi(self.boxes.get(line).copied().unwrap_or_default() as i32)
+    }
+
+    /// The line was played without a mistake
+    pub(crate) fn promote(&mut self, line: &str) {
+        let level: &mut u32 = self.boxes.entry(line.to_string()).or_default();
+        *level = (*level + 1).min(MAX_BOX);
+    }
+
+    /// The player got the line wrong
+    pub(crate) fn demote(&mut self, line: &str) {
+        self.boxes.insert(line.to_string(), 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::Schedule;
+
+    #[test]
+    fn test_weight() {
+        let mut schedule: Schedule = Schedule::default();
+
+        for _ in 0..10 {
+            schedule.promote("e2e4 e7e5");
+        }
+        schedule.promote("d2d4");
+
+        assert_eq!(1.0, schedule.weight("c2c4"));
+        assert_eq!(0.5, schedule.weight("d2d4"));
+        assert_eq!(1.0 / 32.0, schedule.weight("e2e4 e7e5"));
+
+        schedule.demote("e2e4 e7e5");
+        assert_eq!(1.0, schedule.weight("e2e4 e7e5"));
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let path: String = env::temp_dir().join(format!("chessterm-schedule-{}.txt", std::process::id())).display().to_string();
+        assert_eq!(Schedule::default(), Schedule::load(&path)?);
+
+        let mut schedule: Schedule = Schedule::default();
+        schedule.promote("e2e4 e7e5");
+        schedule.demote("d2d4");
+        schedule.save(&path)?;
+        let loaded: Schedule = Schedule::load(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(schedule, loaded);
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt::{self, Display};
+
+use crate::bot::Bot;
+use crate::bot::score::Score;
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::{ChessEngine, Result};
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::pgn::{Annotation, Pgn};
+use crate::game::san::line_to_san;
+
+// Beyond this, a worse score doesn't lose the game more
+const MAX_SCORE: i32 = 1_000;
+// Plies of the better line written as a variation
+const VARIATION_PLIES: usize = 6;
+
+/// How bad a move is, by the centipawns it loses against the best move
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Judgement {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    fn from_loss(loss: i32) -> Option<Self> {
+        match loss {
+            300.. => Some(Self::Blunder),
+            100.. => Some(Self::Mistake),
+            50.. => Some(Self::Inaccuracy),
+            _ => None,
+        }
+    }
+
+    /// The annotation symbol of the judgement
+    pub(crate) const fn suffix(&self) -> &'static str {
+        match self {
+            Self::Inaccuracy => "?!",
+            Self::Mistake => "?",
+            Self::Blunder => "??",
+        }
+    }
+}
+
+impl Display for Judgement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inaccuracy => write!(f, "Inaccuracy"),
+            Self::Mistake => write!(f, "Mistake"),
+            Self::Blunder => write!(f, "Blunder"),
+        }
+    }
+}
+
+/// One move of the game, as judged by the bot
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct MoveReview {
+    played: (Position, Position),
+    player: Color,
+    // From white's point of view
+    score_before: Score,
+    score_after: Score,
+    // The bot's own choice: no loss, whatever the two searches say
+    best: bool,
+    // From the point of view of the player, in centipawns
+    loss: i32,
+    judgement: Option<Judgement>,
+    // The better line in SAN, numbered, when the move was not the best
+    variation: Option<String>,
+}
+
+impl MoveReview {
+    pub(crate) const fn played(&self) -> (Position, Position) {
+        self.played
+    }
+
+    pub(crate) const fn score_after(&self) -> Score {
+        self.score_after
+    }
+
+    pub(crate) const fn judgement(&self) -> Option<Judgement> {
+        self.judgement
+    }
+
+    pub(crate) fn variation(&self) -> Option<&str> {
+        self.variation.as_deref()
+    }
+
+    /// Lichess' accuracy of a move, from the winning chances it throws away.
+    /// The best move is 100% even when the search after it scores lower
+    fn accuracy(&self) -> f64 {
+        if self.best {
+            return 100.0;
+        }
+        let before: f64 = win_percent(self.score_before.relative(self.player));
+        let after: f64 = win_percent(self.score_after.relative(self.player));
+
+        (103.1668 * (-0.04354 * (before - after).max(0.0)).exp() - 3.1669).clamp(0.0, 100.0)
+    }
+}
+
+/// Every move of a game evaluated by a bot: what each one lost, and the
+/// better moves
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Review {
+    moves: Vec<MoveReview>,
+}
+
+impl Review {
+    /// Searches every position of `chess_game` within `limits`,
+    /// `on_progress` gets the positions searched and their total
+    pub(crate) fn new(
+        chess_game: &ChessEngine,
+        bot: &mut dyn Bot,
+        limits: &SearchLimits,
+        on_progress: &mut dyn FnMut(usize, usize),
+    ) -> Self {
+        let history: Vec<(Position, Position)> = chess_game.history();
+        let mut replay: ChessEngine = chess_game.starting_position();
+        // The search of each position, from the point of view of its side
+        // to move
+        let mut searches: Vec<(Score, Vec<(Position, Position)>)> = Vec::new();
+        let mut positions: Vec<ChessEngine> = Vec::new();
+
+        bot.new_game();
+        for ply in 0..=history.len() {
+            on_progress(ply, history.len() + 1);
+            searches.push(match replay.result() {
+                Result::Checkmate => (Score::mated_in(0), Vec::new()),
+                Result::Stalemate | Result::Draw => (Score::ZERO, Vec::new()),
+                Result::None => {
+                    let result: SearchResult = bot.choose_move(&replay, limits.clone());
+                    (result.score(), result.pv().to_vec())
+                }
+            });
+            positions.push(replay.clone());
+            if let Some(&(from, to)) = history.get(ply) {
+                replay.try_move(Some(from), Some(to));
+            }
+        }
+
+        let moves: Vec<MoveReview> = history
+            .iter()
+            .enumerate()
+            .map(|(ply, &played)| {
+                let position: &ChessEngine = &positions[ply];
+                let player: Color = position.current_player();
+                let (best, line): &(Score, Vec<(Position, Position)>) = &searches[ply];
+                let after: Score = -searches[ply + 1].0;
+                let loss: i32 = (clamp(*best) - clamp(after)).max(0);
+                let better: bool = line.first().is_some_and(|&best_move| best_move != played);
+
+                MoveReview {
+                    played,
+                    player,
+                    score_before: best.relative(player),
+                    score_after: after.relative(player),
+                    best: !better,
+                    loss: if better { loss } else { 0 },
+                    judgement: if better { Judgement::from_loss(loss) } else { None },
+                    variation: better.then(|| line_to_san(position, &line[..line.len().min(VARIATION_PLIES)])),
+                }
+            })
+            .collect();
+
+        Self {
+            moves,
+        }
+    }
+
+    pub(crate) fn moves(&self) -> &[MoveReview] {
+        &self.moves
+    }
+
+    fn moves_of(&self, color: Color) -> impl Iterator<Item = &MoveReview> {
+        self.moves.iter().filter(move |reviewed| reviewed.player == color)
+    }
+
+    /// Average centipawn loss of `color`
+    pub(crate) fn acpl(&self, color: Color) -> f64 {
+        let losses: Vec<i32> = self.moves_of(color).map(|reviewed| reviewed.loss).collect();
+
+        f64::from(losses.iter().sum::<i32>()) / losses.len().max(1) as f64
+    }
+
+    /// Average accuracy of the moves of `color`, in percent
+    pub(crate) fn accuracy(&self, color: Color) -> f64 {
+        let accuracies: Vec<f64> = self.moves_of(color).map(MoveReview::accuracy).collect();
+
+        if accuracies.is_empty() {
+            100.0
+        } else {
+            accuracies.iter().sum::<f64>() / accuracies.len() as f64
+        }
+    }
+
+    /// The number of moves of `color` judged `judgement`
+    pub(crate) fn count(&self, color: Color, judgement: Judgement) -> usize {
+        self.moves_of(color).filter(|reviewed| reviewed.judgement == Some(judgement)).count()
+    }
+
+    /// One side's figures: "White: 35 ACPL, 87.2% accuracy, ..."
+    pub(crate) fn summary(&self, color: Color) -> String {
+        let name: &str = if color == Color::White { "White" } else { "Black" };
+
+        format!(
+            "{name}: {:.0} ACPL, {:.1}% accuracy, inaccuracies {}, mistakes {}, blunders {}",
+            self.acpl(color),
+            self.accuracy(color),
+            self.count(color, Judgement::Inaccuracy),
+            self.count(color, Judgement::Mistake),
+            self.count(color, Judgement::Blunder),
+        )
+    }
+
+    /// `chess_game`, the game reviewed, with the judgements, the better
+    /// lines and a summary. The result is the one on the board, a game lost
+    /// on time or by resignation is unfinished
+    pub(crate) fn annotate(&self, chess_game: &ChessEngine) -> Pgn {
+        let result: &str = match chess_game.result() {
+            Result::Checkmate if chess_game.current_player() == Color::White => "0-1",
+            Result::Checkmate => "1-0",
+            Result::Stalemate | Result::Draw => "1/2-1/2",
+            Result::None => "*",
+        };
+        let mut pgn: Pgn = Pgn::new(chess_game)
+            .with_tag("Result", result)
+            .with_tag("Annotator", "chessterm")
+            .with_comment(&self.to_string());
+
+        for (ply, reviewed) in self.moves.iter().enumerate() {
+            if let Some(judgement) = reviewed.judgement {
+                let annotation: Annotation = Annotation {
+                    suffix: judgement.suffix(),
+                    comment: Some(format!("{judgement} ({} → {})", reviewed.score_before, reviewed.score_after)),
+                    variation: reviewed.variation.clone(),
+                };
+                pgn = pgn.with_annotation(ply, annotation);
+            }
+        }
+
+        pgn
+    }
+}
+
+impl Display for Review {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. {}", self.summary(Color::White), self.summary(Color::Black))
+    }
+}
+
+fn clamp(score: Score) -> i32 {
+    score.value().clamp(-MAX_SCORE, MAX_SCORE)
+}
+
+/// Lichess' winning chances, in percent
+fn win_percent(score: Score) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.003_682_08 * f64::from(clamp(score))).exp()) - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::alpha_beta_bot::AlphaBetaBot;
+    use crate::bot::score::Score;
+    use crate::bot::search::SearchLimits;
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::{parse_move, play_moves};
+
+    use super::{Judgement, MoveReview, Review};
+
+    #[rstest]
+    #[case(20, None)]
+    #[case(60, Some(Judgement::Inaccuracy))]
+    #[case(150, Some(Judgement::Mistake))]
+    #[case(900, Some(Judgement::Blunder))]
+    fn test_judgement(
+        #[case]
+        loss: i32,
+        #[case]
+        expected: Option<Judgement>
+    ) {
+        assert_eq!(expected, Judgement::from_loss(loss));
+    }
+
+    #[test]
+    fn test_accuracy_of_the_best_move() {
+        // The search after the move found a lost position, the one before
+        // a won one
+        let mut move_review: MoveReview = MoveReview {
+            played: parse_move("e2e4").unwrap(),
+            player: Color::White,
+            score_before: Score::centipawns(500),
+            score_after: Score::centipawns(-500),
+            best: true,
+            loss: 0,
+            judgement: None,
+            variation: None,
+        };
+        assert_eq!(100.0, move_review.accuracy());
+
+        move_review.best = false;
+        assert!(move_review.accuracy() < 10.0);
+    }
+
+    #[test]
+    fn test_review_finds_the_blunder() -> Result<()> {
+        // Black leaves the queen en prise instead of taking the rook
+        let mut chess_game: ChessEngine = FenParser::parse("6k1/6p1/8/3q4/8/8/8/3R2K1 b - - 0 1")?;
+        play_moves(&mut chess_game, &["g8h8", "d1d5"])?;
+        let mut progress: Vec<usize> = Vec::new();
+
+        let review: Review = Review::new(&chess_game, &mut AlphaBetaBot::new(), &SearchLimits::depth(2), &mut |done, _| progress.push(done));
+
+        assert_eq!(vec![0, 1, 2], progress);
+        assert_eq!(Some(Judgement::Blunder), review.moves()[0].judgement());
+        assert!(review.moves()[0].variation().is_some_and(|variation| variation.starts_with("1... Qxd1+")));
+        assert_eq!(None, review.moves()[1].judgement());
+        assert_eq!(0.0, review.acpl(Color::White));
+        assert_eq!(100.0, review.accuracy(Color::White));
+        assert!(review.acpl(Color::Black) >= 300.0);
+        assert!(review.accuracy(Color::Black) < review.accuracy(Color::White));
+
+        let pgn: String = review.annotate(&chess_game).to_string();
+        assert!(pgn.contains("1... Kh8??"), "{pgn}");
+        assert!(pgn.contains("{Blunder (-"), "{pgn}");
+        assert!(pgn.contains("(1... Qxd1+"), "{pgn}");
+        Ok(())
+    }
+}
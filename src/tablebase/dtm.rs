@@ -0,0 +1,689 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::game::ChessEngine;
+use crate::game::board::Board;
+use crate::game::board::board_builder::BoardBuilder;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::pieces::Piece;
+use crate::game::pieces::bishop::Bishop;
+use crate::game::pieces::king::King;
+use crate::game::pieces::knight::Knight;
+use crate::game::pieces::pawn::Pawn;
+use crate::game::pieces::piece_kind::PieceKind;
+use crate::game::pieces::queen::Queen;
+use crate::game::pieces::rook::Rook;
+
+use super::{castling_rights, Wdl};
+
+// Starts every table file, followed by the version and the ending
+const MAGIC: &[u8] = b"CTDTM";
+const VERSION: u8 = 1;
+// The white king is folded into a1-d1-d4 by the symmetries of a board
+// without pawns
+const TRIANGLE: [u8; 10] = [56, 57, 58, 59, 49, 50, 51, 42, 43, 35];
+// The pawn is folded into the files a to d
+const PAWN_SLOTS: usize = 32;
+
+/// The endings the generator knows, named by the pieces next to the white
+/// king, the lone king being black's
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Ending {
+    Queen,
+    Rook,
+    Pawn,
+    BishopKnight,
+}
+
+pub(crate) const ENDINGS: [Ending; 4] = [
+    Ending::Queen,
+    Ending::Rook,
+    Ending::Pawn,
+    Ending::BishopKnight,
+];
+
+impl Ending {
+    pub(crate) const fn name(&self) -> &'static str {
+        match self {
+            Self::Queen => "kqk",
+            Self::Rook => "krk",
+            Self::Pawn => "kpk",
+            Self::BishopKnight => "kbnk",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Result<Self> {
+        match ENDINGS.into_iter().find(|ending| ending.name() == name) {
+            Some(ending) => Ok(ending),
+            None => bail!(
+                "Unknown ending \"{name}\", expected one of {}",
+                ENDINGS.map(|ending| ending.name()).join(", ")
+            ),
+        }
+    }
+
+    /// The white pieces besides the king, as in FEN
+    const fn letters(&self) -> &'static [char] {
+        match self {
+            Self::Queen => &['Q'],
+            Self::Rook => &['R'],
+            Self::Pawn => &['P'],
+            Self::BishopKnight => &['B', 'N'],
+        }
+    }
+
+    const fn has_pawn(&self) -> bool {
+        matches!(self, Self::Pawn)
+    }
+
+    /// The entries of the table, both sides to move
+    fn len(&self) -> usize {
+        let anchors: usize = if self.has_pawn() { PAWN_SLOTS } else { TRIANGLE.len() };
+
+        2 * anchors * 64usize.pow(self.letters().len() as u32 + 1)
+    }
+
+    /// The table index of `placement`, folded by the symmetries: the same
+    /// for every mirror image of the position
+    fn index(&self, placement: &Placement) -> usize {
+        let squares: &[u8] = placement.squares();
+        let black_to_move: usize = usize::from(!placement.white_to_move);
+
+        if self.has_pawn() {
+            let flip: bool = column(squares[2]) > 3;
+            let folded: [u8; 4] = placement.squares.map(|square| transform(square, flip, false, false));
+            let slot: usize = row(folded[2]) * 4 + column(folded[2]);
+            return ((black_to_move * PAWN_SLOTS + slot) * 64 + folded[0] as usize) * 64 + folded[1] as usize;
+        }
+
+        let flip_files: bool = column(squares[0]) > 3;
+        let flip_ranks: bool = row(squares[0]) < 4;
+        let king: u8 = transform(squares[0], flip_files, flip_ranks, false);
+        let fold = |transpose: bool| {
+            let folded: [u8; 4] = placement.squares.map(|square| transform(square, flip_files, flip_ranks, transpose));
+            let slot: usize = TRIANGLE.iter().position(|&square| square == folded[0]).expect("The king is folded into the triangle");
+            folded[1..placement.len].iter().fold(black_to_move * TRIANGLE.len() + slot, |index, &square| index * 64 + square as usize)
+        };
+
+        // On the diagonal, both sides of it are folded the same
+        match (7 - row(king)).cmp(&column(king)) {
+            Ordering::Equal => fold(false).min(fold(true)),
+            Ordering::Greater => fold(true),
+            Ordering::Less => fold(false),
+        }
+    }
+
+    /// The position of a table index, not always legal
+    fn placement(&self, index: usize) -> Placement {
+        let mut squares: [u8; 4] = [0; 4];
+        let len: usize = self.letters().len() + 2;
+        let mut rest: usize = index;
+
+        if self.has_pawn() {
+            squares[1] = (rest % 64) as u8;
+            squares[0] = (rest / 64 % 64) as u8;
+            rest /= 64 * 64;
+            squares[2] = ((rest % PAWN_SLOTS) / 4 * 8 + rest % 4) as u8;
+            rest /= PAWN_SLOTS;
+        } else {
+            for square in squares[1..len].iter_mut().rev() {
+                *square = (rest % 64) as u8;
+                rest /= 64;
+            }
+            squares[0] = TRIANGLE[rest % TRIANGLE.len()];
+            rest /= TRIANGLE.len();
+        }
+
+        Placement {
+            squares,
+            len,
+            white_to_move: rest == 0,
+        }
+    }
+}
+
+/// The distance to mate of the side to move in plies, with perfect play
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Dtm {
+    Win(usize),
+    Draw,
+    Loss(usize),
+}
+
+impl Dtm {
+    pub(crate) const fn wdl(self) -> Wdl {
+        match self {
+            Self::Win(_) => Wdl::Win,
+            Self::Draw => Wdl::Draw,
+            Self::Loss(_) => Wdl::Loss,
+        }
+    }
+
+    /// The result one move earlier, for the other side
+    pub(crate) const fn before(self) -> Self {
+        match self {
+            Self::Win(plies) => Self::Loss(plies + 1),
+            Self::Draw => Self::Draw,
+            Self::Loss(plies) => Self::Win(plies + 1),
+        }
+    }
+
+    /// Higher for a better result: the fastest win, the slowest loss
+    pub(crate) const fn rank(self) -> i32 {
+        match self {
+            Self::Win(plies) => 1_000 - plies as i32,
+            Self::Draw => 0,
+            Self::Loss(plies) => -1_000 + plies as i32,
+        }
+    }
+}
+
+impl Display for Dtm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Win(plies) => write!(f, "mate in {}", plies.div_ceil(2)),
+            Self::Draw => write!(f, "draw"),
+            Self::Loss(plies) => write!(f, "mated in {}", plies.div_ceil(2)),
+        }
+    }
+}
+
+/// The pieces of an ending on squares 0 (a8) to 63 (h1): the white king,
+/// the black king and the other white pieces in the order of the ending
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Placement {
+    squares: [u8; 4],
+    len: usize,
+    white_to_move: bool,
+}
+
+impl Placement {
+    fn squares(&self) -> &[u8] {
+        &self.squares[..self.len]
+    }
+
+    fn with(mut self, piece: usize, square: u8) -> Self {
+        self.squares[piece] = square;
+        self.white_to_move = !self.white_to_move;
+        self
+    }
+
+    const fn side_to_move(&self) -> Color {
+        if self.white_to_move { Color::White } else { Color::Black }
+    }
+
+    /// The board of the engine, white being the strong side. The king has
+    /// moved: the tables leave castling out
+    fn board(&self, ending: Ending) -> Board {
+        let mut builder: BoardBuilder = BoardBuilder::new()
+            .with(PieceKind::King(King::new(position(self.squares[0]), Color::White).with_has_moved()))
+            .with(PieceKind::King(King::new(position(self.squares[1]), Color::Black)));
+
+        for (&letter, &square) in ending.letters().iter().zip(&self.squares[2..self.len]) {
+            let position: Position = position(square);
+            builder.add(match letter {
+                'Q' => PieceKind::Queen(Queen::new(position, Color::White)),
+                'R' => PieceKind::Rook(Rook::new(position, Color::White)),
+                'B' => PieceKind::Bishop(Bishop::new(position, Color::White)),
+                'N' => PieceKind::Knight(Knight::new(position, Color::White)),
+                _ => PieceKind::Pawn(Pawn::new(position, Color::White)),
+            });
+        }
+
+        builder.build()
+    }
+
+    fn is_legal(&self, ending: Ending) -> bool {
+        let squares: &[u8] = self.squares();
+        let distinct: bool = (1..squares.len()).all(|piece| !squares[..piece].contains(&squares[piece]));
+
+        distinct && self.board(ending).is_legal(self.side_to_move())
+    }
+
+    /// The legal moves of the engine, as the piece moved and the square it
+    /// goes to
+    fn moves(&self, ending: Ending) -> Vec<(usize, u8)> {
+        let chess_game: ChessEngine = ChessEngine::from_board(self.board(ending), self.side_to_move());
+
+        chess_game
+            .possible_moves()
+            .values()
+            .flatten()
+            .map(|found| {
+                let from: u8 = square(found.from());
+                let piece: usize = self.squares().iter().position(|&square| square == from).expect("A piece moves");
+                (piece, square(found.to()))
+            })
+            .collect()
+    }
+}
+
+/// The distances to mate of an ending, generated backwards from the mates
+pub(crate) struct DtmTable {
+    ending: Ending,
+    // 0 for a draw or an illegal position, the plies to mate plus one
+    // otherwise
+    values: Vec<u8>,
+}
+
+impl DtmTable {
+    /// Retrograde analysis over the moves of the engine: the mates first,
+    /// then the positions one ply further at each step. The KPK table needs
+    /// the KQK one, the pawn promoting to a queen
+    pub(crate) fn generate(ending: Ending, queen: Option<&Self>) -> Result<Self> {
+        let queen: Option<&Self> = match queen {
+            Some(queen) if queen.ending != Ending::Queen => bail!("Promotions need the kqk table, not {}", queen.ending.name()),
+            queen if ending.has_pawn() => Some(queen.context("The kpk table needs the kqk one")?),
+            _ => None,
+        };
+        let mut values: Vec<u8> = vec![0; ending.len()];
+        let mut frontier: Vec<usize> = Vec::new();
+        // The positions won by promoting, by plies to mate
+        let mut promotions: Vec<Vec<usize>> = Vec::new();
+        // The moves of black not known to lose yet, the captures included
+        let mut remaining: Vec<u8> = vec![0; ending.len()];
+        // The moves staying in the table of each position, the piece and the
+        // square it goes to packed in a byte
+        let mut counts: Vec<u8> = vec![0; ending.len()];
+        let mut packed: Vec<u8> = Vec::new();
+
+        for index in 0..ending.len() {
+            let placement: Placement = ending.placement(index);
+            if ending.index(&placement) != index || !placement.is_legal(ending) {
+                continue;
+            }
+            let moves: Vec<(usize, u8)> = placement.moves(ending);
+            if !placement.white_to_move {
+                remaining[index] = moves.len() as u8;
+                if moves.is_empty() && placement.board(ending).checked(Color::Black) {
+                    values[index] = 1;
+                    frontier.push(index);
+                }
+            }
+
+            let mut promoted: Option<usize> = None;
+            for (piece, to) in moves {
+                let captures: bool = !placement.white_to_move && placement.squares()[2..].contains(&to);
+                if ending.has_pawn() && piece == 2 && row(to) == 0 {
+                    let won: Option<usize> = queen.and_then(|queen| queen.lookup(&placement.with(piece, to))).and_then(|dtm| match dtm.before() {
+                        Dtm::Win(plies) => Some(plies),
+                        _ => None,
+                    });
+                    promoted = promoted.into_iter().chain(won).min();
+                } else if !captures {
+                    counts[index] += 1;
+                    packed.push((piece as u8) << 6 | to);
+                }
+            }
+            if let Some(plies) = promoted {
+                promotions.resize(promotions.len().max(plies + 1), Vec::new());
+                promotions[plies].push(index);
+            }
+        }
+
+        // The other way round: the positions one move before each one,
+        // from its offset on
+        let mut offsets: Vec<u32> = vec![0; ending.len() + 1];
+        for_each_move(ending, &counts, &packed, |_, next| offsets[next + 1] += 1);
+        for index in 0..ending.len() {
+            offsets[index + 1] += offsets[index];
+        }
+        let mut previous: Vec<u32> = vec![0; offsets[ending.len()] as usize];
+        let mut filled: Vec<u32> = offsets.clone();
+        for_each_move(ending, &counts, &packed, |index, next| {
+            previous[filled[next] as usize] = index as u32;
+            filled[next] += 1;
+        });
+        drop(packed);
+
+        let mut plies: usize = 0;
+        while !frontier.is_empty() || plies < promotions.len() {
+            for &index in promotions.get(plies).into_iter().flatten() {
+                if values[index] == 0 {
+                    values[index] = plies as u8 + 1;
+                    frontier.push(index);
+                }
+            }
+
+            let mut next: Vec<usize> = Vec::new();
+            for index in frontier {
+                for &before in &previous[offsets[index] as usize..offsets[index + 1] as usize] {
+                    let before: usize = before as usize;
+                    if values[before] != 0 {
+                        continue;
+                    }
+                    // Black is lost once every move is
+                    let lost: bool = ending.placement(before).white_to_move || {
+                        remaining[before] -= 1;
+                        remaining[before] == 0
+                    };
+                    if lost {
+                        values[before] = plies as u8 + 2;
+                        next.push(before);
+                    }
+                }
+            }
+            frontier = next;
+            plies += 1;
+        }
+
+        Ok(Self {
+            ending,
+            values,
+        })
+    }
+
+    pub(crate) const fn ending(&self) -> Ending {
+        self.ending
+    }
+
+    /// The most plies to mate
+    pub(crate) fn longest(&self) -> usize {
+        self.values.iter().max().map_or(0, |&value| usize::from(value).saturating_sub(1))
+    }
+
+    fn lookup(&self, placement: &Placement) -> Option<Dtm> {
+        let value: usize = usize::from(*self.values.get(self.ending.index(placement))?);
+
+        Some(match value {
+            0 => Dtm::Draw,
+            _ if placement.white_to_move => Dtm::Win(value - 1),
+            _ => Dtm::Loss(value - 1),
+        })
+    }
+
+    /// The distance to mate of `chess_game`, None when it is not this
+    /// ending
+    pub(crate) fn probe(&self, chess_game: &ChessEngine) -> Option<Dtm> {
+        let (ending, placement): (Ending, Placement) = locate(chess_game)?;
+
+        if ending == self.ending { self.lookup(&placement) } else { None }
+    }
+
+    /// Writes the table as runs of equal values: the illegal and the drawn
+    /// positions come in long runs
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let mut bytes: Vec<u8> = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(ENDINGS.iter().position(|&ending| ending == self.ending).expect("A known ending") as u8);
+
+        for run in self.values.chunk_by(|first, second| first == second) {
+            bytes.push(run[0]);
+            // The length in 7 bit groups, the lowest first
+            let mut length: usize = run.len();
+            while length >= 0x80 {
+                bytes.push((length & 0x7f) as u8 | 0x80);
+                length >>= 7;
+            }
+            bytes.push(length as u8);
+        }
+
+        fs::write(path, bytes).with_context(|| format!("Could not write {}", path.display()))
+    }
+
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let bytes: Vec<u8> = fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+        let Some((header, mut runs)) = bytes.split_at_checked(MAGIC.len() + 2) else {
+            bail!("{} is too short for a DTM table", path.display());
+        };
+        if &header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != VERSION {
+            bail!("{} is not a DTM table of this version", path.display());
+        }
+        let Some(&ending) = ENDINGS.get(usize::from(header[MAGIC.len() + 1])) else {
+            bail!("{} is the table of an unknown ending", path.display());
+        };
+
+        let mut values: Vec<u8> = Vec::with_capacity(ending.len());
+        while let [value, rest @ ..] = runs {
+            let mut length: usize = 0;
+            let mut shift: u32 = 0;
+            let mut read: usize = 0;
+            loop {
+                let Some(&byte) = rest.get(read) else {
+                    bail!("{} ends in the middle of a run", path.display());
+                };
+                length |= usize::from(byte & 0x7f).checked_shl(shift).context("A run is too long")?;
+                shift += 7;
+                read += 1;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            if values.len() + length > ending.len() {
+                bail!("{} has more values than positions", path.display());
+            }
+            values.resize(values.len() + length, *value);
+            runs = &rest[read..];
+        }
+        if values.len() != ending.len() {
+            bail!("{} has {} values for {} positions", path.display(), values.len(), ending.len());
+        }
+
+        Ok(Self {
+            ending,
+            values,
+        })
+    }
+}
+
+/// Calls `visit` with every move of the packed moves of `generate`: the
+/// position before and the one after
+fn for_each_move(ending: Ending, counts: &[u8], packed: &[u8], mut visit: impl FnMut(usize, usize)) {
+    let mut packed = packed.iter();
+
+    for (index, &count) in counts.iter().enumerate().filter(|&(_, &count)| count > 0) {
+        let placement: Placement = ending.placement(index);
+        for &byte in packed.by_ref().take(count.into()) {
+            visit(index, ending.index(&placement.with(usize::from(byte >> 6), byte & 0x3f)));
+        }
+    }
+}
+
+/// The ending of `chess_game`, None for the positions no table holds
+pub(super) fn ending(chess_game: &ChessEngine) -> Option<Ending> {
+    locate(chess_game).map(|(ending, _)| ending)
+}
+
+/// The ending of `chess_game` and its pieces, the colors swapped when
+/// black has them. The engine doesn't promote: a pawn on the last row is
+/// counted as the queen it would be
+fn locate(chess_game: &ChessEngine) -> Option<(Ending, Placement)> {
+    let all: Vec<&PieceKind> = chess_game.board().pieces(Color::Any);
+    if castling_rights(&all) {
+        return None;
+    }
+    let strong: Color = [Color::White, Color::Black]
+        .into_iter()
+        .find(|&color| chess_game.board().pieces(color.other()).len() == 1 && chess_game.board().pieces(color).len() > 1)?;
+    let square = |position: Position| {
+        let row: usize = if strong == Color::White { position.row() } else { 7 - position.row() };
+        (row * 8 + position.column()) as u8
+    };
+
+    let mut pieces: Vec<(char, u8)> = chess_game
+        .board()
+        .pieces(strong)
+        .iter()
+        .filter(|piece| !matches!(piece, PieceKind::King(_)))
+        .map(|piece| {
+            let promoted: bool = matches!(piece, PieceKind::Pawn(_)) && square(piece.position()) < 8;
+            (if promoted { 'Q' } else { piece.letter() }, square(piece.position()))
+        })
+        .collect();
+    let ending: Ending = ENDINGS.into_iter().find(|ending| {
+        ending.letters().len() == pieces.len() && ending.letters().iter().all(|&letter| pieces.iter().any(|&(piece, _)| piece == letter))
+    })?;
+    pieces.sort_by_key(|&(letter, _)| ending.letters().iter().position(|&known| known == letter));
+
+    let mut squares: [u8; 4] = [0; 4];
+    squares[0] = square(chess_game.board().king_position(strong)?);
+    squares[1] = square(chess_game.board().king_position(strong.other())?);
+    for (slot, &(_, placed)) in squares[2..].iter_mut().zip(&pieces) {
+        *slot = placed;
+    }
+
+    Some((
+        ending,
+        Placement {
+            squares,
+            len: pieces.len() + 2,
+            white_to_move: chess_game.current_player() == strong,
+        },
+    ))
+}
+
+const fn row(square: u8) -> usize {
+    square as usize / 8
+}
+
+const fn column(square: u8) -> usize {
+    square as usize % 8
+}
+
+fn square(position: Position) -> u8 {
+    (position.row() * 8 + position.column()) as u8
+}
+
+fn position(square: u8) -> Position {
+    (row(square), column(square)).into()
+}
+
+/// `square` mirrored across the middle files, the middle ranks, then the
+/// a1-h8 diagonal
+const fn transform(square: u8, flip_files: bool, flip_ranks: bool, transpose: bool) -> u8 {
+    let mut row: usize = row(square);
+    let mut column: usize = column(square);
+    if flip_files {
+        column = 7 - column;
+    }
+    if flip_ranks {
+        row = 7 - row;
+    }
+    if transpose {
+        (row, column) = (7 - column, 7 - row);
+    }
+
+    (row * 8 + column) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::rng::Rng;
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+    use crate::game::fen_writer::FenWriter;
+
+    use super::{locate, Dtm, DtmTable, Ending, Placement, ENDINGS};
+
+    fn kqk() -> &'static DtmTable {
+        static TABLE: OnceLock<DtmTable> = OnceLock::new();
+        TABLE.get_or_init(|| DtmTable::generate(Ending::Queen, None).expect("No table needed"))
+    }
+
+    fn kpk() -> &'static DtmTable {
+        static TABLE: OnceLock<DtmTable> = OnceLock::new();
+        TABLE.get_or_init(|| DtmTable::generate(Ending::Pawn, Some(kqk())).expect("The kqk table is given"))
+    }
+
+    #[test]
+    fn test_longest_mates() -> Result<()> {
+        let krk: DtmTable = DtmTable::generate(Ending::Rook, None)?;
+
+        // Mated in 10 against the queen, 16 against the rook
+        assert_eq!(20, kqk().longest());
+        assert_eq!(32, krk.longest());
+        assert_eq!(56, kpk().longest());
+        assert!(DtmTable::generate(Ending::Pawn, None).is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("k7/8/1K6/8/8/8/7Q/8 w - - 0 1", Dtm::Win(1))]
+    #[case("k6Q/8/1K6/8/8/8/8/8 b - - 0 1", Dtm::Loss(0))]
+    #[case("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Dtm::Draw)]
+    // The colors swapped
+    #[case("8/7q/8/8/8/1k6/8/K7 b - - 0 1", Dtm::Win(1))]
+    // The king on the sixth in front of its pawn wins whoever moves
+    #[case("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Dtm::Win(21))]
+    #[case("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Dtm::Loss(24))]
+    #[case("4k3/8/4P3/4K3/8/8/8/8 w - - 0 1", Dtm::Draw)]
+    #[case("k7/8/8/8/8/8/P7/K7 w - - 0 1", Dtm::Draw)]
+    fn test_probe(
+        #[case]
+        fen: &str,
+        #[case]
+        expected: Dtm
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+        let table: &DtmTable = if fen.contains(['P', 'p']) { kpk() } else { kqk() };
+
+        assert_eq!(Some(expected), table.probe(&chess_game));
+        Ok(())
+    }
+
+    #[test]
+    fn test_locate_finds_the_placement() {
+        let mut rng: Rng = Rng::new(5);
+
+        for ending in ENDINGS {
+            let mut checked: usize = 0;
+            while checked < 50 {
+                let placement: Placement = ending.placement(rng.below(ending.len()));
+                if !placement.is_legal(ending) {
+                    continue;
+                }
+                let chess_game: ChessEngine = ChessEngine::from_board(placement.board(ending), placement.side_to_move());
+
+                assert_eq!(
+                    Some((ending, ending.index(&placement))),
+                    locate(&chess_game).map(|(ending, found)| (ending, ending.index(&found))),
+                    "{}",
+                    FenWriter::write(&chess_game)
+                );
+                checked += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let path: PathBuf = env::temp_dir().join(format!("chessterm-kqk-{}.dtm", std::process::id()));
+
+        kqk().save(&path)?;
+        let loaded: Result<DtmTable> = DtmTable::load(&path);
+        let size: u64 = fs::metadata(&path)?.len();
+        fs::write(&path, b"CTDTM\x01\x00\x05\x80")?;
+        let truncated: Result<DtmTable> = DtmTable::load(&path);
+        fs::remove_file(&path)?;
+
+        assert_eq!(kqk().values, loaded?.values);
+        assert!(size < kqk().values.len() as u64, "{size}");
+        assert!(truncated.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_locate_folds_the_mirror_images() -> Result<()> {
+        let first: ChessEngine = FenParser::parse("8/8/8/8/8/1k6/8/K6Q b - - 0 1")?;
+        let mirrored: ChessEngine = FenParser::parse("Q6K/8/6k1/8/8/8/8/8 b - - 0 1")?;
+        let index = |chess_game: &ChessEngine| locate(chess_game).map(|(ending, placement)| ending.index(&placement));
+
+        assert_eq!(index(&first), index(&mirrored));
+        assert_eq!(None, locate(&FenParser::parse("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")?).map(|(ending, _)| ending));
+        Ok(())
+    }
+}
@@ -0,0 +1,702 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::ops::Neg;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+
+use crate::game::{ChessEngine, Result as GameResult};
+use crate::game::board::color::Color;
+use crate::game::board::move_kind::MoveKind;
+use crate::game::board::position::Position;
+use crate::game::pieces::Piece;
+use crate::game::pieces::piece_kind::PieceKind;
+
+use dtm::{Dtm, DtmTable, Ending};
+use table::{Kind, Table};
+
+pub(crate) mod dtm;
+mod table;
+
+/// The biggest tables read, kings included
+pub(crate) const MAX_PIECES: usize = 5;
+// The piece letters of the table names, strongest first
+const LETTERS: &str = "KQRBNP";
+// A cursed win or a blessed loss can't be zeroed within the 50 moves
+const CURSED_DTZ: i32 = 100;
+const DTM_EXTENSION: &str = "dtm";
+
+/// The result of the side to move with perfect play. The cursed wins and
+/// the blessed losses are the ones the fifty-move rule would draw
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    const fn from_value(value: i32) -> Self {
+        match value {
+            ..=-2 => Self::Loss,
+            -1 => Self::BlessedLoss,
+            0 => Self::Draw,
+            1 => Self::CursedWin,
+            _ => Self::Win,
+        }
+    }
+
+    const fn value(self) -> i32 {
+        self as i32 - 2
+    }
+
+    const fn is_cursed(self) -> bool {
+        matches!(self, Self::CursedWin | Self::BlessedLoss)
+    }
+
+    /// The DTZ of a position whose best move captures
+    const fn before_zeroing(self) -> i32 {
+        match self {
+            Self::Loss => -1,
+            Self::BlessedLoss => -CURSED_DTZ - 1,
+            Self::Draw => 0,
+            Self::CursedWin => CURSED_DTZ + 1,
+            Self::Win => 1,
+        }
+    }
+}
+
+impl Display for Wdl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text: &str = match self {
+            Self::Loss => "loss",
+            Self::BlessedLoss => "blessed loss",
+            Self::Draw => "draw",
+            Self::CursedWin => "cursed win",
+            Self::Win => "win",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::from_value(-self.value())
+    }
+}
+
+/// The Syzygy tables of a directory, up to `MAX_PIECES` pieces, and the
+/// DTM tables generated there. A table is read the first time a position
+/// needs it. The engine doesn't promote: a pawn on the last row is probed
+/// as the queen it would be
+pub(crate) struct Tablebase {
+    directory: PathBuf,
+    // None once a table failed to load
+    wdl: HashMap<String, OnceLock<Option<Table>>>,
+    dtz: HashMap<String, OnceLock<Option<Table>>>,
+    dtm: HashMap<Ending, OnceLock<Option<DtmTable>>>,
+}
+
+impl Tablebase {
+    pub(crate) fn open(directory: &str) -> Result<Self> {
+        let mut tablebase: Self = Self {
+            directory: PathBuf::from(directory),
+            wdl: HashMap::new(),
+            dtz: HashMap::new(),
+            dtm: HashMap::new(),
+        };
+
+        let entries = fs::read_dir(directory).with_context(|| format!("Could not read the directory {directory}"))?;
+        for entry in entries {
+            let path: PathBuf = entry?.path();
+            let (Some(material), Some(extension)) = (path.file_stem().and_then(|stem| stem.to_str()), path.extension()) else {
+                continue;
+            };
+            if extension == DTM_EXTENSION {
+                if let Ok(ending) = Ending::from_name(material) {
+                    tablebase.dtm.insert(ending, OnceLock::new());
+                }
+                continue;
+            }
+            if !is_supported(material) {
+                continue;
+            }
+            if extension == Kind::Wdl.extension() {
+                tablebase.wdl.insert(material.to_string(), OnceLock::new());
+            } else if extension == Kind::Dtz.extension() {
+                tablebase.dtz.insert(material.to_string(), OnceLock::new());
+            }
+        }
+        if tablebase.wdl.is_empty() && tablebase.dtm.is_empty() {
+            bail!("No Syzygy nor DTM table in {directory}");
+        }
+
+        Ok(tablebase)
+    }
+
+    /// The number of WDL and DTM tables found
+    pub(crate) fn len(&self) -> usize {
+        self.wdl.len() + self.dtm.len()
+    }
+
+    fn dtm_table(&self, ending: Ending) -> Option<&DtmTable> {
+        let path: PathBuf = self.directory.join(format!("{}.{DTM_EXTENSION}", ending.name()));
+
+        self.dtm.get(&ending)?.get_or_init(|| DtmTable::load(&path).ok()).as_ref()
+    }
+
+    /// The distance to mate of the side to move, None when no DTM table
+    /// holds the position
+    pub(crate) fn probe_dtm(&self, chess_game: &ChessEngine) -> Option<Dtm> {
+        self.dtm_table(dtm::ending(chess_game)?)?.probe(chess_game)
+    }
+
+    fn table(&self, kind: Kind, material: &str) -> Option<&Table> {
+        let tables: &HashMap<String, OnceLock<Option<Table>>> = if kind == Kind::Wdl { &self.wdl } else { &self.dtz };
+        let path: PathBuf = self.directory.join(format!("{material}.{}", kind.extension()));
+
+        tables.get(material)?.get_or_init(|| Table::load(&path, material, kind).ok()).as_ref()
+    }
+
+    /// The table holding the position and whether its colors are swapped
+    fn locate(&self, kind: Kind, chess_game: &ChessEngine) -> Option<(&Table, bool)> {
+        let white: String = material(chess_game, Color::White);
+        let black: String = material(chess_game, Color::Black);
+
+        match self.table(kind, &format!("{white}v{black}")) {
+            Some(table) => Some((table, false)),
+            None => self.table(kind, &format!("{black}v{white}")).map(|table| (table, true)),
+        }
+    }
+
+    /// The result stored in the tables, without looking at the captures
+    fn stored_wdl(&self, chess_game: &ChessEngine) -> Option<Wdl> {
+        let pieces: Vec<(u8, usize)> = pieces(chess_game)?;
+        if pieces.len() == 2 {
+            return Some(Wdl::Draw);
+        }
+        let (table, flip) = self.locate(Kind::Wdl, chess_game)?;
+
+        table.wdl(&pieces, flip, chess_game.current_player() == Color::Black).ok().map(Wdl::from_value)
+    }
+
+    /// The result of the side to move. The tables don't say when a capture
+    /// wins, so the captures are played first. Also tells whether the best
+    /// move is a capture
+    fn search(&self, chess_game: &mut ChessEngine) -> Option<(Wdl, bool)> {
+        let moves: Vec<(Position, Position, MoveKind)> = legal_moves(chess_game);
+        if moves.is_empty() {
+            let mated: bool = chess_game.checked_king().is_some();
+            return Some((if mated { Wdl::Loss } else { Wdl::Draw }, false));
+        }
+
+        let mut best: Wdl = Wdl::Loss;
+        let mut captures: usize = 0;
+        for &(from, to, _) in moves.iter().filter(|&&(_, _, kind)| is_capture(kind)) {
+            captures += 1;
+            chess_game.try_move(Some(from), Some(to));
+            let value: Option<(Wdl, bool)> = self.search(chess_game);
+            chess_game.undo_move();
+
+            let value: Wdl = -value?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        let only_captures: bool = captures > 0 && captures == moves.len();
+        let stored: Wdl = if only_captures { best } else { self.stored_wdl(chess_game)? };
+        if best >= stored {
+            Some((best, best > Wdl::Draw || only_captures))
+        } else {
+            Some((stored, false))
+        }
+    }
+
+    /// The result of the side to move, None when the position is not in
+    /// the tables
+    pub(crate) fn probe_wdl(&self, chess_game: &ChessEngine) -> Option<Wdl> {
+        if let Some(dtm) = self.probe_dtm(chess_game) {
+            return Some(dtm.wdl());
+        }
+        pieces(chess_game)?;
+
+        self.search(&mut chess_game.clone()).map(|(wdl, _)| wdl)
+    }
+
+    /// The plies before the next capture, pawn move or mate with perfect
+    /// play, positive when the side to move wins, 0 for a draw
+    pub(crate) fn probe_dtz(&self, chess_game: &ChessEngine) -> Option<i32> {
+        pieces(chess_game)?;
+
+        self.dtz(&mut chess_game.clone())
+    }
+
+    /// The result of the side to move and the moves to mate, or to the next
+    /// capture or mate without a DTM table, in words
+    pub(crate) fn status(&self, chess_game: &ChessEngine) -> Option<String> {
+        if let Some(dtm) = self.probe_dtm(chess_game) {
+            return Some(dtm.to_string());
+        }
+        let wdl: Wdl = self.probe_wdl(chess_game)?;
+        match self.probe_dtz(chess_game) {
+            Some(dtz) if wdl != Wdl::Draw => Some(format!("{wdl}, capture or mate in {}", (dtz.abs() + 1) / 2)),
+            _ => Some(wdl.to_string()),
+        }
+    }
+
+    fn dtz(&self, chess_game: &mut ChessEngine) -> Option<i32> {
+        let (wdl, capture_first): (Wdl, bool) = self.search(chess_game)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if capture_first {
+            return Some(wdl.before_zeroing());
+        }
+        // A pawn move keeping the win zeroes too
+        if wdl > Wdl::Draw {
+            for (from, to, _) in legal_moves(chess_game).into_iter().filter(|&(_, _, kind)| is_zeroing(kind) && !is_capture(kind)) {
+                chess_game.try_move(Some(from), Some(to));
+                let child: Option<(Wdl, bool)> = self.search(chess_game);
+                chess_game.undo_move();
+
+                if child.is_some_and(|(child, _)| -child == wdl) {
+                    return Some(wdl.before_zeroing());
+                }
+            }
+        }
+
+        let pieces: Vec<(u8, usize)> = pieces(chess_game)?;
+        let (table, flip) = self.locate(Kind::Dtz, chess_game)?;
+        let black_to_move: bool = chess_game.current_player() == Color::Black;
+        if let Some(dtz) = table.dtz(&pieces, flip, black_to_move, wdl.value()).ok()? {
+            let cursed: i32 = if wdl.is_cursed() { CURSED_DTZ } else { 0 };
+            return Some((dtz + cursed) * wdl.value().signum());
+        }
+
+        // Only the other side to move is stored: one move deeper
+        let mut best: Option<i32> = None;
+        for (from, to, kind) in legal_moves(chess_game) {
+            chess_game.try_move(Some(from), Some(to));
+            let child: Option<i32> = if is_zeroing(kind) {
+                self.search(chess_game).map(|(child, _)| -child.before_zeroing())
+            } else {
+                self.dtz(chess_game).map(|child| -child + (-child).signum())
+            };
+            let mates: bool = chess_game.result() == GameResult::Checkmate;
+            chess_game.undo_move();
+
+            let dtz: i32 = if mates { 1 } else { child? };
+            if dtz.signum() == wdl.value().signum() && best.is_none_or(|best| dtz < best) {
+                best = Some(dtz);
+            }
+        }
+
+        Some(best.unwrap_or(-1))
+    }
+
+    /// The move keeping the best result: the fastest to a capture or mate
+    /// when winning, the slowest when losing. The fastest to mate with a
+    /// DTM table
+    pub(crate) fn best_move(&self, chess_game: &ChessEngine) -> Option<((Position, Position), Wdl)> {
+        if self.probe_dtm(chess_game).is_some() {
+            return self.fastest_mate(chess_game);
+        }
+        pieces(chess_game)?;
+        let mut chess_game: ChessEngine = chess_game.clone();
+        let mut scored: Vec<((Position, Position), Wdl, bool, i32)> = Vec::new();
+
+        for (from, to, kind) in legal_moves(&chess_game) {
+            chess_game.try_move(Some(from), Some(to));
+            let mates: bool = chess_game.result() == GameResult::Checkmate;
+            let wdl: Option<Wdl> = self.search(&mut chess_game).map(|(child, _)| -child);
+            let dtz: Option<i32> = if is_zeroing(kind) {
+                wdl.map(Wdl::before_zeroing)
+            } else {
+                self.dtz(&mut chess_game).map(|child| -child + (-child).signum())
+            };
+            chess_game.undo_move();
+
+            scored.push(((from, to), wdl?, mates, if mates { 1 } else { dtz? }));
+        }
+
+        scored
+            .into_iter()
+            .min_by_key(|&(played, wdl, mates, dtz)| (Reverse(wdl), !mates, dtz, played.0.row(), played.0.column(), played.1.row(), played.1.column()))
+            .map(|(played, wdl, _, _)| (played, wdl))
+    }
+
+    fn fastest_mate(&self, chess_game: &ChessEngine) -> Option<((Position, Position), Wdl)> {
+        let mut chess_game: ChessEngine = chess_game.clone();
+        let mut scored: Vec<((Position, Position), Dtm)> = Vec::new();
+
+        for (from, to, _) in legal_moves(&chess_game) {
+            chess_game.try_move(Some(from), Some(to));
+            // The captures leave a lone king against a king and a minor
+            // piece at most
+            let dtm: Dtm = self.probe_dtm(&chess_game).unwrap_or(Dtm::Draw).before();
+            chess_game.undo_move();
+
+            scored.push(((from, to), dtm));
+        }
+
+        scored
+            .into_iter()
+            .min_by_key(|&(played, dtm)| (-dtm.rank(), played.0.row(), played.0.column(), played.1.row(), played.1.column()))
+            .map(|(played, dtm)| (played, dtm.wdl()))
+    }
+}
+
+/// Whether a table of this name is read: not too many pieces
+fn is_supported(material: &str) -> bool {
+    let Some((white, black)) = material.split_once('v') else {
+        return false;
+    };
+
+    material.len() - 1 <= MAX_PIECES
+        && [white, black]
+            .iter()
+            .all(|side| side.starts_with('K') && side.chars().all(|letter| LETTERS.contains(letter)))
+}
+
+/// The pieces of `color`, as in the table names: "KRNP"
+fn material(chess_game: &ChessEngine, color: Color) -> String {
+    let mut letters: Vec<char> = chess_game.board().pieces(color).iter().map(|piece| letter(piece)).collect();
+    letters.sort_by_key(|&letter| LETTERS.find(letter));
+
+    letters.into_iter().collect()
+}
+
+/// The letter of a piece in the table names, the pawns on the last row
+/// counted as queens
+fn letter(piece: &PieceKind) -> char {
+    let last_row: usize = if piece.color() == Color::White { 0 } else { 7 };
+
+    match piece {
+        PieceKind::Pawn(_) if piece.position().row() == last_row => 'Q',
+        piece => piece.letter(),
+    }
+}
+
+/// The pieces as the tables code them: 1 to 6 for pawn to king, +8 for
+/// black, on squares from 0 (a1) to 63 (h8). None when the position can't
+/// be in the tables: too many pieces or castling rights
+fn pieces(chess_game: &ChessEngine) -> Option<Vec<(u8, usize)>> {
+    let all: Vec<&PieceKind> = chess_game.board().pieces(Color::Any);
+    if all.len() > MAX_PIECES || castling_rights(&all) {
+        return None;
+    }
+
+    Some(
+        all.iter()
+            .map(|piece| {
+                let code: u8 = 1 + "PNBRQK".find(letter(piece)).expect("A piece letter") as u8;
+                let position: Position = piece.position();
+                let square: usize = (7 - position.row()) * 8 + position.column();
+
+                (if piece.color() == Color::Black { code + 8 } else { code }, square)
+            })
+            .collect(),
+    )
+}
+
+/// A king and a rook that never moved, from their starting squares
+fn castling_rights(pieces: &[&PieceKind]) -> bool {
+    pieces.iter().any(|king| {
+        matches!(king, PieceKind::King(king) if !king.has_moved() && king.position() == king.original_position())
+            && pieces.iter().any(|rook| {
+                matches!(rook, PieceKind::Rook(rook) if !rook.has_moved()
+                    && rook.color() == king.color()
+                    && rook.position().row() == king.position().row()
+                    && [0, 7].contains(&rook.position().column()))
+            })
+    })
+}
+
+/// The legal moves and their kinds
+fn legal_moves(chess_game: &ChessEngine) -> Vec<(Position, Position, MoveKind)> {
+    chess_game
+        .possible_moves()
+        .values()
+        .flatten()
+        .map(|possible_move| (possible_move.from(), possible_move.to(), possible_move.kind()))
+        .collect()
+}
+
+const fn is_capture(kind: MoveKind) -> bool {
+    matches!(kind, MoveKind::Attack(Some(_)) | MoveKind::EnPassant(_))
+}
+
+/// Whether the move starts the fifty moves again: a capture or a pawn move
+const fn is_zeroing(kind: MoveKind) -> bool {
+    is_capture(kind) || matches!(kind, MoveKind::PawnSimpleMove | MoveKind::PawnDoubleMove)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::Bot;
+    use crate::bot::alpha_beta_bot::AlphaBetaBot;
+    use crate::bot::score::Score;
+    use crate::bot::search::{SearchLimits, SearchResult};
+    use crate::game::{ChessEngine, Result as GameResult};
+    use crate::game::board::Board;
+    use crate::game::board::board_builder::BoardBuilder;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+    use crate::game::fen_writer::FenWriter;
+    use crate::game::pieces::Piece;
+    use crate::game::pieces::king::King;
+    use crate::game::pieces::pawn::Pawn;
+    use crate::game::pieces::piece_kind::PieceKind;
+    use crate::game::pieces::queen::Queen;
+    use crate::game::pieces::rook::Rook;
+
+    use super::dtm::{Dtm, DtmTable, Ending};
+    use super::table::fixture::write;
+    use super::table::Kind;
+    use super::{is_supported, pieces, Tablebase, Wdl};
+
+    /// A directory with a KQvK table: every position won with white to move
+    /// and lost with black to move, 5 moves from zeroing. Only white to move
+    /// is in the DTZ table. The KPvK table is the same, 3 moves from zeroing,
+    /// but for the rook pawns that draw
+    fn kqvk(name: &str) -> Result<PathBuf> {
+        let directory: PathBuf = env::temp_dir().join(format!("chessterm-syzygy-{name}-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        fs::write(directory.join("KQvK.rtbw"), write("KQvK", Kind::Wdl, 0, &|_, side, _| if side == 0 { 4 } else { 0 }))?;
+        fs::write(directory.join("KQvK.rtbz"), write("KQvK", Kind::Dtz, 0, &|_, _, _| 5))?;
+        let pawn = |file: usize, side: usize| match (file, side) {
+            (0, _) => 2,
+            (_, 0) => 4,
+            _ => 0,
+        };
+        fs::write(directory.join("KPvK.rtbw"), write("KPvK", Kind::Wdl, 0, &|file, side, _| pawn(file, side)))?;
+        fs::write(directory.join("KPvK.rtbz"), write("KPvK", Kind::Dtz, 0, &|file, _, _| if file == 0 { 0 } else { 3 }))?;
+        // Not read: too many pieces
+        fs::write(directory.join("KRRRvKRR.rtbw"), [0u8; 16])?;
+
+        Ok(directory)
+    }
+
+    #[rstest]
+    #[case("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", Some(Wdl::Win), Some(11))]
+    // Black only has the white-to-move values one move deeper
+    #[case("4k3/8/8/8/8/8/8/Q3K3 b - - 0 1", Some(Wdl::Loss), Some(-12))]
+    // The colors swapped
+    #[case("4K3/8/8/8/8/8/8/q3k3 b - - 0 1", Some(Wdl::Win), Some(11))]
+    // The king takes the queen: a draw the table doesn't know
+    #[case("4k3/3Q4/8/8/8/8/8/4K3 b - - 0 1", Some(Wdl::Draw), Some(0))]
+    #[case("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", None, None)]
+    // The pawn move zeroes
+    #[case("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", Some(Wdl::Win), Some(1))]
+    #[case("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1", Some(Wdl::Loss), Some(-2))]
+    // The pawn is blocked: the DTZ of the table
+    #[case("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1", Some(Wdl::Win), Some(7))]
+    #[case("4k3/8/8/8/8/8/P7/4K3 w - - 0 1", Some(Wdl::Draw), Some(0))]
+    #[case("4k3/4p3/8/8/8/8/8/4K3 b - - 0 1", Some(Wdl::Win), Some(1))]
+    // The engine doesn't promote: the pawn counts as a queen
+    #[case("4P3/8/8/k7/8/8/8/4K3 b - - 0 1", Some(Wdl::Loss), Some(-12))]
+    fn test_probe(
+        #[case]
+        fen: &str,
+        #[case]
+        wdl: Option<Wdl>,
+        #[case]
+        dtz: Option<i32>
+    ) -> Result<()> {
+        let directory: PathBuf = kqvk(&fen.replace(['/', ' '], ""))?;
+        let tablebase: Tablebase = Tablebase::open(&directory.display().to_string())?;
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let probed: (Option<Wdl>, Option<i32>) = (tablebase.probe_wdl(&chess_game), tablebase.probe_dtz(&chess_game));
+        fs::remove_dir_all(&directory)?;
+
+        assert_eq!(2, tablebase.len());
+        assert_eq!((wdl, dtz), probed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_best_move_mates() -> Result<()> {
+        let directory: PathBuf = kqvk("mate")?;
+        let tablebase: Tablebase = Tablebase::open(&directory.display().to_string())?;
+        let mut chess_game: ChessEngine = FenParser::parse("k7/8/1K6/8/8/8/7Q/8 w - - 0 1")?;
+
+        let best = tablebase.best_move(&chess_game);
+        let status: Option<String> = tablebase.status(&chess_game);
+        fs::remove_dir_all(&directory)?;
+
+        let ((from, to), wdl) = best.expect("A move");
+        assert_eq!(Some("win, capture or mate in 6".to_string()), status);
+        assert_eq!(Wdl::Win, wdl);
+        assert!(chess_game.try_move(Some(from), Some(to)));
+        assert_eq!(GameResult::Checkmate, chess_game.result());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bot_plays_from_the_tables() -> Result<()> {
+        let directory: PathBuf = kqvk("bot")?;
+        let mut chess_game: ChessEngine = FenParser::parse("k7/8/1K6/8/8/8/7Q/8 w - - 0 1")?;
+        let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+        bot.set_tablebase(Arc::new(Tablebase::open(&directory.display().to_string())?));
+
+        let result: SearchResult = bot.choose_move(&chess_game, SearchLimits::depth(1));
+        fs::remove_dir_all(&directory)?;
+
+        let (from, to) = result.best_move().expect("A move");
+        assert!(chess_game.try_move(Some(from), Some(to)));
+        assert_eq!(GameResult::Checkmate, chess_game.result());
+        assert!(result.score() > Score::centipawns(10_000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dtm_tables() -> Result<()> {
+        let directory: PathBuf = env::temp_dir().join(format!("chessterm-dtm-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        DtmTable::generate(Ending::Queen, None)?.save(&directory.join("kqk.dtm"))?;
+        let tablebase: Tablebase = Tablebase::open(&directory.display().to_string())?;
+        let mut chess_game: ChessEngine = FenParser::parse("8/8/8/8/8/2k5/8/K6Q b - - 0 1")?;
+
+        let probed: (Option<Dtm>, Option<Wdl>) = (tablebase.probe_dtm(&chess_game), tablebase.probe_wdl(&chess_game));
+        let status: Option<String> = tablebase.status(&chess_game);
+        chess_game.try_move(Some((5usize, 2usize).into()), Some((6usize, 2usize).into()));
+        let Some(Dtm::Win(plies)) = tablebase.probe_dtm(&chess_game) else {
+            panic!("White wins after Kc2");
+        };
+        let ((from, to), wdl) = tablebase.best_move(&chess_game).expect("A move");
+        chess_game.try_move(Some(from), Some(to));
+        let after: Option<Dtm> = tablebase.probe_dtm(&chess_game);
+        fs::remove_dir_all(&directory)?;
+
+        assert_eq!(1, tablebase.len());
+        assert_eq!((Some(Dtm::Loss(16)), Some(Wdl::Loss)), probed);
+        assert_eq!(Some("mated in 8".to_string()), status);
+        assert_eq!(Wdl::Win, wdl);
+        assert_eq!(Some(Dtm::Loss(plies - 1)), after);
+        Ok(())
+    }
+
+    /// The white king, a white `piece` and the black king on squares from 0
+    /// (a8) to 63 (h1)
+    fn chess_game(squares: [usize; 3], piece: char, to_move: Color) -> ChessEngine {
+        let position = |square: usize| Position::from((square / 8, square % 8));
+        let other: PieceKind = match piece {
+            'Q' => PieceKind::Queen(Queen::new(position(squares[1]), Color::White)),
+            'R' => PieceKind::Rook(Rook::new(position(squares[1]), Color::White)),
+            _ => PieceKind::Pawn(Pawn::new(position(squares[1]), Color::White)),
+        };
+        let board: Board = BoardBuilder::new()
+            .with(PieceKind::King(King::new(position(squares[0]), Color::White).with_has_moved()))
+            .with(other)
+            .with(PieceKind::King(King::new(position(squares[2]), Color::Black)))
+            .build();
+
+        ChessEngine::from_board(board, to_move)
+    }
+
+    /// Probes the real KQvK, KRvK and KPvK files of the directory in
+    /// SYZYGY_PATH and checks them against the DTM generator, which owes
+    /// nothing to the Syzygy format. The files are not checked in, run with
+    /// `SYZYGY_PATH=<dir> cargo test --release -- --ignored real_syzygy`
+    #[rstest]
+    #[case("KQvK", 'Q', Ending::Queen)]
+    #[case("KRvK", 'R', Ending::Rook)]
+    #[case("KPvK", 'P', Ending::Pawn)]
+    #[ignore = "needs the real Syzygy files in SYZYGY_PATH"]
+    fn test_real_syzygy_files(
+        #[case] material: &str,
+        #[case] piece: char,
+        #[case] ending: Ending,
+    ) -> Result<()> {
+        let source: PathBuf = PathBuf::from(env::var("SYZYGY_PATH")?);
+        // Only the Syzygy files, so that no DTM table answers in their place
+        let directory: PathBuf = env::temp_dir().join(format!("chessterm-real-{material}-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        for extension in ["rtbw", "rtbz"] {
+            let name: String = format!("{material}.{extension}");
+            fs::copy(source.join(&name), directory.join(&name))?;
+        }
+        let tablebase: Tablebase = Tablebase::open(&directory.display().to_string())?;
+        let queen: Option<DtmTable> = (ending == Ending::Pawn).then(|| DtmTable::generate(Ending::Queen, None)).transpose()?;
+        let dtm: DtmTable = DtmTable::generate(ending, queen.as_ref())?;
+
+        let mut probed: usize = 0;
+        // Without pawns, the white king on the a1-d1-d4 triangle: the other
+        // positions are its symmetries
+        let kings: Vec<usize> = if ending == Ending::Pawn { (0..64).collect() } else { vec![56, 57, 58, 59, 49, 50, 51, 42, 43, 35] };
+        let others: Vec<usize> = if ending == Ending::Pawn { (8..56).collect() } else { (0..64).collect() };
+        for king in kings {
+            for (&other, black_king) in others.iter().flat_map(|other| (0..64_usize).map(move |black_king| (other, black_king))) {
+                let adjacent: bool = (king / 8).abs_diff(black_king / 8) <= 1 && (king % 8).abs_diff(black_king % 8) <= 1;
+                if other == king || other == black_king || adjacent {
+                    continue;
+                }
+                let black: ChessEngine = chess_game([king, other, black_king], piece, Color::Black);
+                let white: ChessEngine = chess_game([king, other, black_king], piece, Color::White);
+                // White to move with black in check can't happen
+                let chess_games: Vec<&ChessEngine> = if black.checked_king().is_some() { vec![&black] } else { vec![&black, &white] };
+
+                for chess_game in chess_games {
+                    let expected: Dtm = dtm.probe(chess_game).expect("The ending of the DTM table");
+                    let wdl: Option<Wdl> = tablebase.probe_wdl(chess_game);
+                    let dtz: i32 = tablebase.probe_dtz(chess_game).expect("A DTZ");
+                    let position: String = FenWriter::write(chess_game);
+
+                    assert_eq!(Some(expected.wdl()), wdl, "{position}");
+                    // The mated positions have no DTZ convention to hold to
+                    if chess_game.possible_moves().values().all(|moves| moves.is_empty()) {
+                        probed += 1;
+                        continue;
+                    }
+                    assert_eq!(expected.rank().signum(), dtz.signum(), "{position}: DTZ {dtz}");
+                    // Without pawns only the mate zeroes for the winner, the
+                    // DTZ is the DTM but for the rounding of the stored values
+                    if let (Dtm::Win(plies) | Dtm::Loss(plies), false) = (expected, ending == Ending::Pawn) {
+                        assert!(dtz.unsigned_abs().abs_diff(plies as u32) <= 1, "{position}: DTZ {dtz}, DTM {expected:?}");
+                    }
+                    probed += 1;
+                }
+            }
+        }
+        fs::remove_dir_all(&directory)?;
+
+        assert!(probed > 50_000, "{probed}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported() -> Result<()> {
+        assert!(is_supported("KRBvKN"));
+        assert!(is_supported("KPvKP"));
+        assert!(!is_supported("KXvK"));
+        assert!(!is_supported("KRRvKRR"));
+        assert!(!is_supported("README"));
+        // Castling rights
+        assert_eq!(None, pieces(&FenParser::parse("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")?));
+        assert!(Tablebase::open("/nonexistent/syzygy").is_err());
+        assert!(Tablebase::open(&env::temp_dir().display().to_string()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,998 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{ensure, Context, Result};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+// Flags of the file header
+const HAS_PAWNS: u8 = 2;
+// Flags of the table of one side
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+// A symbol of the Huffman tree standing for itself
+const LEAF: u16 = 0xfff;
+// Placements of three unique pieces once the board symmetries are removed,
+// and of the two kings
+const UNIQUE_PIECES_PLACEMENTS: u64 = 31_332;
+const KINGS_PLACEMENTS: u64 = 462;
+// The most pawns of one color in a table of 5 pieces
+const MAX_PAWNS: usize = 3;
+// The order of a group the table doesn't have
+const NO_ORDER: usize = 0xf;
+// Bytes of an entry of the sparse index: a block (u32) and an offset (u16)
+const SPARSE_ENTRY_SIZE: usize = 6;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Kind {
+    Wdl,
+    Dtz,
+}
+
+impl Kind {
+    pub(super) const fn extension(self) -> &'static str {
+        match self {
+            Self::Wdl => "rtbw",
+            Self::Dtz => "rtbz",
+        }
+    }
+
+    const fn magic(self) -> [u8; 4] {
+        match self {
+            Self::Wdl => WDL_MAGIC,
+            Self::Dtz => DTZ_MAGIC,
+        }
+    }
+}
+
+/// The squares are indexed from 0 (a1) to 63 (h8), the mappings folding
+/// the board symmetries away are computed once
+struct Encoding {
+    // Ways to choose k squares among n
+    binomial: [[u64; 64]; 6],
+    // The a1-d1-d4 triangle to 0..9, the diagonal last
+    a1_d1_d4: [u64; 64],
+    // The squares under the a1-h8 diagonal to 0..27
+    b1_h1_h7: [u64; 64],
+    // The placements of the two kings, the first one in the a1-d1-d4 triangle
+    kings: [[u64; 64]; 10],
+    // The pawn squares from 47 on a2 down, the edge files and the low ranks
+    // first: the leading pawn is the one mapped the highest
+    pawns: [u64; 64],
+    // The index of the leading pawn on its square and the placements of
+    // the leading pawns on a file, by their number
+    lead_pawn: [[u64; 64]; MAX_PAWNS + 1],
+    lead_pawns: [[u64; 4]; MAX_PAWNS + 1],
+}
+
+impl Encoding {
+    fn get() -> &'static Self {
+        static ENCODING: OnceLock<Encoding> = OnceLock::new();
+
+        ENCODING.get_or_init(Self::new)
+    }
+
+    fn new() -> Self {
+        let mut encoding: Self = Self {
+            binomial: [[0; 64]; 6],
+            a1_d1_d4: [0; 64],
+            b1_h1_h7: [0; 64],
+            kings: [[0; 64]; 10],
+            pawns: [0; 64],
+            lead_pawn: [[0; 64]; MAX_PAWNS + 1],
+            lead_pawns: [[0; 4]; MAX_PAWNS + 1],
+        };
+
+        let mut code: u64 = 0;
+        for square in (0..64).filter(|&square| off_diagonal(square) < 0) {
+            encoding.b1_h1_h7[square] = code;
+            code += 1;
+        }
+
+        code = 0;
+        let triangle: Vec<usize> = (0..64).filter(|&square| file(square) <= 3 && rank(square) <= 3).collect();
+        let (below, diagonal): (Vec<usize>, Vec<usize>) = triangle
+            .into_iter()
+            .filter(|&square| off_diagonal(square) <= 0)
+            .partition(|&square| off_diagonal(square) < 0);
+        for square in below.into_iter().chain(diagonal) {
+            encoding.a1_d1_d4[square] = code;
+            code += 1;
+        }
+
+        code = 0;
+        let mut both_on_diagonal: Vec<(usize, usize)> = Vec::new();
+        for index in 0..10 {
+            let Some(first) = (0..64).find(|&square| in_triangle(square) && encoding.a1_d1_d4[square] == index as u64) else {
+                continue;
+            };
+            for second in 0..64 {
+                if file(first).abs_diff(file(second)) <= 1 && rank(first).abs_diff(rank(second)) <= 1 {
+                    continue;
+                }
+                if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                    continue;
+                }
+                if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                    both_on_diagonal.push((index, second));
+                } else {
+                    encoding.kings[index][second] = code;
+                    code += 1;
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            encoding.kings[index][second] = code;
+            code += 1;
+        }
+
+        encoding.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6usize.min(n + 1) {
+                let with: u64 = if k > 0 { encoding.binomial[k - 1][n - 1] } else { 0 };
+                let without: u64 = if k < n { encoding.binomial[k][n - 1] } else { 0 };
+                encoding.binomial[k][n] = with + without;
+            }
+        }
+
+        code = 48;
+        for count in 1..=MAX_PAWNS {
+            for file in 0..4 {
+                let mut index: u64 = 0;
+                for rank in 1..7 {
+                    let square: usize = rank * 8 + file;
+                    if count == 1 {
+                        code -= 1;
+                        encoding.pawns[square] = code;
+                        code -= 1;
+                        encoding.pawns[square ^ 7] = code;
+                    }
+                    encoding.lead_pawn[count][square] = index;
+                    // The other leading pawns on the squares mapped lower
+                    index += encoding.binomial[count - 1][encoding.pawns[square] as usize];
+                }
+                encoding.lead_pawns[count][file] = index;
+            }
+        }
+
+        encoding
+    }
+}
+
+const fn file(square: usize) -> usize {
+    square % 8
+}
+
+const fn rank(square: usize) -> usize {
+    square / 8
+}
+
+/// Above the a1-h8 diagonal when positive, under it when negative
+const fn off_diagonal(square: usize) -> isize {
+    rank(square) as isize - file(square) as isize
+}
+
+const fn in_triangle(square: usize) -> bool {
+    file(square) <= 3 && rank(square) <= 3 && off_diagonal(square) <= 0
+}
+
+/// The compressed values of one side of a table, and how the positions are
+/// grouped into an index
+#[derive(Default)]
+struct Pairs {
+    // The pieces in the order of the index, 1 to 6 for pawn to king, +8 for
+    // black
+    pieces: Vec<u8>,
+    // The pieces are indexed by groups of the same piece, the first group
+    // being the kings and a third unique piece when there is one
+    group_lengths: Vec<usize>,
+    // The factor of each group in the index, then the size of the table
+    group_factors: Vec<u64>,
+    flags: u8,
+    block_size: u64,
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    blocks: usize,
+    blocks_count: usize,
+    // The single value of the table when flagged so
+    min_symbol_length: u8,
+    lowest_symbols: usize,
+    // The lowest canonical Huffman code of each length, left aligned
+    base: Vec<u64>,
+    // Values a symbol stands for, minus one
+    symbol_lengths: Vec<u32>,
+    tree: usize,
+    // Where the DTZ values of each result start in the map
+    map_index: [usize; 4],
+}
+
+/// What the first group of the index holds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Lead {
+    Kings,
+    UniquePieces,
+    // The leading pawns, the first one on this file from a to d
+    Pawns(usize),
+}
+
+/// A Syzygy table file. The tables with pawns are split in four by the file
+/// of the leading pawn
+pub(super) struct Table {
+    data: Vec<u8>,
+    kind: Kind,
+    // The same pieces on both sides: only white to move is stored
+    symmetric: bool,
+    has_pawns: bool,
+    // By file of the leading pawn, then by side to move
+    files: Vec<Vec<Pairs>>,
+    // Start of the DTZ maps
+    map: usize,
+}
+
+impl Table {
+    /// The table of `kind` at `path`, for the material named like "KRvKN"
+    pub(super) fn load(path: &Path, material: &str, kind: Kind) -> Result<Self> {
+        let data: Vec<u8> = fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+        Self::parse(data, material, kind).with_context(|| format!("Invalid table {}", path.display()))
+    }
+
+    fn parse(data: Vec<u8>, material: &str, kind: Kind) -> Result<Self> {
+        let (white, black) = material.split_once('v').context("Expected the material as <white>v<black>")?;
+        ensure!(data.len() > 5 && data[..4] == kind.magic(), "Not a {} file", kind.extension());
+        let has_pawns: bool = data[4] & HAS_PAWNS != 0;
+        ensure!(has_pawns == material.contains('P'), "The pawns of the file don't match {material}");
+
+        let symmetric: bool = white == black;
+        let count: usize = material.len() - 1;
+        let unique_pieces: bool = [white, black]
+            .iter()
+            .any(|side| "QRBN".chars().any(|piece| side.matches(piece).count() == 1));
+        let pawns_on_both_sides: bool = white.contains('P') && black.contains('P');
+        let sides_count: usize = if kind == Kind::Wdl && !symmetric { 2 } else { 1 };
+        let files_count: usize = if has_pawns { 4 } else { 1 };
+        let mut files: Vec<Vec<Pairs>> = Vec::new();
+
+        let mut offset: usize = 5;
+        for file in 0..files_count {
+            let mut sides: Vec<Pairs> = (0..sides_count).map(|_| Pairs::default()).collect();
+            let first: u8 = byte(&data, offset)?;
+            let second: u8 = if pawns_on_both_sides { byte(&data, offset + 1)? } else { 0xff };
+            let orders: [[usize; 2]; 2] = [
+                [usize::from(first & 0xf), usize::from(second & 0xf)],
+                [usize::from(first >> 4), usize::from(second >> 4)],
+            ];
+            offset += 1 + usize::from(pawns_on_both_sides);
+            let lead: Lead = match (has_pawns, unique_pieces) {
+                (true, _) => Lead::Pawns(file),
+                (false, true) => Lead::UniquePieces,
+                (false, false) => Lead::Kings,
+            };
+
+            for (side, pairs) in sides.iter_mut().enumerate() {
+                for k in 0..count {
+                    let pieces: u8 = byte(&data, offset + k)?;
+                    pairs.pieces.push(if side == 0 { pieces & 0xf } else { pieces >> 4 });
+                }
+                set_groups(pairs, orders[side], lead)?;
+            }
+            offset += count;
+            files.push(sides);
+        }
+        offset += offset & 1;
+
+        for pairs in files.iter_mut().flatten() {
+            offset = set_sizes(pairs, &data, offset)?;
+        }
+
+        let map: usize = offset;
+        if kind == Kind::Dtz {
+            for pairs in files.iter_mut().map(|sides| &mut sides[0]) {
+                if pairs.flags & MAPPED == 0 {
+                    continue;
+                }
+                if pairs.flags & WIDE != 0 {
+                    offset += offset & 1;
+                    for index in &mut pairs.map_index {
+                        *index = (offset - map) / 2 + 1;
+                        offset += 2 * usize::from(u16_le(&data, offset)?) + 2;
+                    }
+                } else {
+                    for index in &mut pairs.map_index {
+                        *index = offset - map + 1;
+                        offset += usize::from(byte(&data, offset)?) + 1;
+                    }
+                }
+            }
+            offset += offset & 1;
+        }
+
+        for pairs in files.iter_mut().flatten() {
+            pairs.sparse_index = offset;
+            offset += pairs.sparse_index_size * SPARSE_ENTRY_SIZE;
+        }
+        for pairs in files.iter_mut().flatten() {
+            pairs.block_lengths = offset;
+            offset += pairs.block_lengths_size * 2;
+        }
+        for pairs in files.iter_mut().flatten() {
+            offset = offset.next_multiple_of(64);
+            pairs.blocks = offset;
+            offset += pairs.blocks_count * pairs.block_size as usize;
+        }
+        ensure!(offset <= data.len(), "The file is truncated");
+
+        Ok(Self {
+            data,
+            kind,
+            symmetric,
+            has_pawns,
+            files,
+            map,
+        })
+    }
+
+    /// The file of the leading pawn, the side of the table and the index
+    /// of the position. `pieces` are (piece, square) pairs, `flip` when
+    /// black has the material of the table's white side
+    fn locate(&self, pieces: &[(u8, usize)], flip: bool, black_to_move: bool) -> (usize, usize, u64) {
+        let flip: bool = flip || (self.symmetric && black_to_move);
+        let side: usize = usize::from(flip ^ black_to_move);
+        let encoding: &Encoding = Encoding::get();
+
+        let (mut codes, mut squares): (Vec<u8>, Vec<usize>) = pieces
+            .iter()
+            .map(|&(piece, square)| if flip { (piece ^ 8, square ^ 56) } else { (piece, square) })
+            .unzip();
+        // The leading pawns first, the one nearest the edge and the first
+        // rank in front
+        let mut leading: usize = 0;
+        if self.has_pawns {
+            let pawn: u8 = self.files[0][0].pieces[0];
+            for i in 0..squares.len() {
+                if codes[i] == pawn {
+                    codes.swap(leading, i);
+                    squares.swap(leading, i);
+                    leading += 1;
+                }
+            }
+            let lead: usize = (0..leading).max_by_key(|&i| encoding.pawns[squares[i]]).expect("A leading pawn");
+            squares.swap(0, lead);
+        }
+        let file_index: usize = match file(squares[0]) {
+            _ if !self.has_pawns => 0,
+            lead_file @ 0..=3 => lead_file,
+            lead_file => 7 - lead_file,
+        };
+        let pairs: &Pairs = self.pairs(file_index, side);
+
+        // The same order as the pieces of the table
+        for i in leading..squares.len().saturating_sub(1) {
+            if let Some(j) = (i + 1..squares.len()).find(|&j| pairs.pieces.get(i) == Some(&codes[j])) {
+                codes.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // The first piece goes to the a-d files, then without pawns to the
+        // a1-d1-d4 triangle, the first one off the diagonal under it
+        if file(squares[0]) > 3 {
+            squares.iter_mut().for_each(|square| *square ^= 7);
+        }
+        let mut index: u64 = if self.has_pawns {
+            let mut index: u64 = encoding.lead_pawn[leading][squares[0]];
+            squares[1..leading].sort_by_key(|&square| encoding.pawns[square]);
+            for (i, &square) in squares.iter().enumerate().take(leading).skip(1) {
+                index += encoding.binomial[i][encoding.pawns[square] as usize];
+            }
+            index
+        } else {
+            if rank(squares[0]) > 3 {
+                squares.iter_mut().for_each(|square| *square ^= 56);
+            }
+            if let Some(first) = squares[..pairs.group_lengths[0]].iter().position(|&square| off_diagonal(square) != 0) {
+                if off_diagonal(squares[first]) > 0 {
+                    squares.iter_mut().for_each(|square| *square = ((*square >> 3) | (*square << 3)) & 63);
+                }
+            }
+            first_group_index(&squares, pairs.group_lengths[0] == 3)
+        };
+        index *= pairs.group_factors[0];
+
+        // The other groups, each square counted among the ones left free. The
+        // pawns of the other side come first, off the first and last ranks
+        let mut other_pawns: bool = self.has_pawns && pairs.pieces[leading] & 7 == 1;
+        let mut start: usize = pairs.group_lengths[0];
+        for (group, &length) in pairs.group_lengths.iter().enumerate().skip(1) {
+            squares[start..start + length].sort_unstable();
+            let mut group_index: u64 = 0;
+            for i in 0..length {
+                let square: usize = squares[start + i];
+                let adjust: usize = squares[..start].iter().filter(|&&other| square > other).count();
+                let below: usize = if other_pawns { 8 } else { 0 };
+                group_index += encoding.binomial[i + 1][square - adjust - below];
+            }
+            other_pawns = false;
+            index += group_index * pairs.group_factors[group];
+            start += length;
+        }
+
+        (file_index, side, index)
+    }
+
+    /// The compressed values of a file of the leading pawn and a side
+    fn pairs(&self, file: usize, side: usize) -> &Pairs {
+        &self.files[file][side % self.files[file].len()]
+    }
+
+    /// The result for the side to move, from -2 (loss) to 2 (win)
+    pub(super) fn wdl(&self, pieces: &[(u8, usize)], flip: bool, black_to_move: bool) -> Result<i32> {
+        let (file, side, index): (usize, usize, u64) = self.locate(pieces, flip, black_to_move);
+
+        Ok(i32::from(self.decompress(self.pairs(file, side), index)?) - 2)
+    }
+
+    /// The plies to the next capture, pawn move or mate, for a position of
+    /// result `wdl`. None when the table only holds the other side to move
+    pub(super) fn dtz(&self, pieces: &[(u8, usize)], flip: bool, black_to_move: bool, wdl: i32) -> Result<Option<i32>> {
+        debug_assert_eq!(Kind::Dtz, self.kind);
+        let (file, side, index): (usize, usize, u64) = self.locate(pieces, flip, black_to_move);
+        let pairs: &Pairs = self.pairs(file, 0);
+        // With pawns, a side of the same pieces is not the other side mirrored
+        let both_sides: bool = self.symmetric && !self.has_pawns;
+        if usize::from(pairs.flags & STM) != side && !both_sides {
+            return Ok(None);
+        }
+
+        let mut value: i32 = i32::from(self.decompress(pairs, index)?);
+        if pairs.flags & MAPPED != 0 {
+            // Loss, blessed loss, draw, cursed win, win
+            let map: usize = pairs.map_index[[1, 3, 0, 2, 0][(wdl + 2) as usize]] + value as usize;
+            value = if pairs.flags & WIDE != 0 {
+                i32::from(u16_le(&self.data, self.map + 2 * map)?)
+            } else {
+                i32::from(byte(&self.data, self.map + map)?)
+            };
+        }
+        let in_moves: bool = match wdl {
+            2 => pairs.flags & WIN_PLIES == 0,
+            -2 => pairs.flags & LOSS_PLIES == 0,
+            _ => true,
+        };
+        if in_moves {
+            value *= 2;
+        }
+
+        Ok(Some(value + 1))
+    }
+
+    /// The value at `index`: the block holding it is found from the sparse
+    /// index, then its canonical Huffman codes are read up to the symbol
+    /// covering the index, and the pairs of that symbol are expanded
+    fn decompress(&self, pairs: &Pairs, index: u64) -> Result<u16> {
+        if pairs.flags & SINGLE_VALUE != 0 {
+            return Ok(u16::from(pairs.min_symbol_length));
+        }
+        let data: &[u8] = &self.data;
+
+        let entry: usize = pairs.sparse_index + (index / pairs.span) as usize * SPARSE_ENTRY_SIZE;
+        let mut block: usize = u32_le(data, entry)? as usize;
+        let mut offset: i64 = i64::from(u16_le(data, entry + 4)?) + (index % pairs.span) as i64 - (pairs.span / 2) as i64;
+        let block_length = |block: usize| -> Result<i64> { Ok(i64::from(u16_le(data, pairs.block_lengths + 2 * block)?)) };
+        while offset < 0 {
+            block = block.checked_sub(1).context("Corrupted sparse index")?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let mut cursor: usize = pairs.blocks + block * pairs.block_size as usize;
+        let mut buffer: u64 = u64::from(u32_be(data, cursor)) << 32 | u64::from(u32_be(data, cursor + 4));
+        cursor += 8;
+        let mut buffer_size: u32 = 64;
+        let min_length: u32 = u32::from(pairs.min_symbol_length);
+        let mut symbol: u16;
+        loop {
+            let mut length: usize = 0;
+            while buffer < pairs.base[length] {
+                length += 1;
+            }
+            symbol = ((buffer - pairs.base[length]) >> (64 - length as u32 - min_length)) as u16;
+            symbol = symbol.wrapping_add(u16_le(data, pairs.lowest_symbols + 2 * length)?);
+            let covered: i64 = i64::from(*pairs.symbol_lengths.get(usize::from(symbol)).context("Corrupted block")?) + 1;
+            if offset < covered {
+                break;
+            }
+            offset -= covered;
+
+            let consumed: u32 = length as u32 + min_length;
+            buffer <<= consumed;
+            buffer_size -= consumed;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= u64::from(u32_be(data, cursor)) << (64 - buffer_size);
+                cursor += 4;
+            }
+        }
+
+        while pairs.symbol_lengths[usize::from(symbol)] != 0 {
+            let (left, right): (u16, u16) = children(data, pairs.tree, symbol)?;
+            let covered: i64 = i64::from(pairs.symbol_lengths[usize::from(left)]) + 1;
+            if offset < covered {
+                symbol = left;
+            } else {
+                offset -= covered;
+                symbol = right;
+            }
+        }
+
+        Ok(children(data, pairs.tree, symbol)?.0)
+    }
+}
+
+/// The index of the first group of a table without pawns: three unique
+/// pieces or the two kings, the first one in the a1-d1-d4 triangle
+fn first_group_index(squares: &[usize], unique_pieces: bool) -> u64 {
+    let encoding: &Encoding = Encoding::get();
+    if !unique_pieces {
+        return encoding.kings[encoding.a1_d1_d4[squares[0]] as usize][squares[1]];
+    }
+
+    let adjust1: usize = usize::from(squares[1] > squares[0]);
+    let adjust2: usize = usize::from(squares[2] > squares[0]) + usize::from(squares[2] > squares[1]);
+    let diagonal_rank = |square: usize| rank(square) as u64;
+
+    if off_diagonal(squares[0]) != 0 {
+        (encoding.a1_d1_d4[squares[0]] * 63 + (squares[1] - adjust1) as u64) * 62 + (squares[2] - adjust2) as u64
+    } else if off_diagonal(squares[1]) != 0 {
+        (6 * 63 + diagonal_rank(squares[0]) * 28 + encoding.b1_h1_h7[squares[1]]) * 62 + (squares[2] - adjust2) as u64
+    } else if off_diagonal(squares[2]) != 0 {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + diagonal_rank(squares[0]) * 7 * 28
+            + (diagonal_rank(squares[1]) - adjust1 as u64) * 28
+            + encoding.b1_h1_h7[squares[2]]
+    } else {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + 4 * 7 * 28
+            + diagonal_rank(squares[0]) * 7 * 6
+            + (diagonal_rank(squares[1]) - adjust1 as u64) * 6
+            + (diagonal_rank(squares[2]) - adjust2 as u64)
+    }
+}
+
+/// Splits the pieces into groups: the first one of the leading pawns, of 3
+/// unique pieces or of the kings, then the pieces of the same kind
+/// together. Every group multiplies the index by its placements, in the
+/// `order` of the table: the first group's, then the other side's pawns'
+fn set_groups(pairs: &mut Pairs, order: [usize; 2], lead: Lead) -> Result<()> {
+    let encoding: &Encoding = Encoding::get();
+    let mut first_length: usize = match lead {
+        Lead::Kings => 2,
+        Lead::UniquePieces => 3,
+        Lead::Pawns(_) => 0,
+    };
+
+    pairs.group_lengths = vec![1];
+    for i in 1..pairs.pieces.len() {
+        first_length = first_length.saturating_sub(1);
+        if first_length > 0 || pairs.pieces[i] == pairs.pieces[i - 1] {
+            *pairs.group_lengths.last_mut().expect("The first group") += 1;
+        } else {
+            pairs.group_lengths.push(1);
+        }
+    }
+
+    let groups: usize = pairs.group_lengths.len();
+    ensure!(!matches!(lead, Lead::Pawns(_)) || pairs.group_lengths[0] <= MAX_PAWNS, "Too many leading pawns");
+    let other_pawns: bool = order[1] != NO_ORDER;
+    pairs.group_factors = vec![0; groups + 1];
+    let mut free_squares: usize = 64 - pairs.group_lengths[0] - if other_pawns { pairs.group_lengths[1] } else { 0 };
+    let mut factor: u64 = 1;
+    let mut next: usize = if other_pawns { 2 } else { 1 };
+    let mut k: usize = 0;
+    while next < groups || k == order[0] || k == order[1] {
+        if k == order[0] {
+            pairs.group_factors[0] = factor;
+            factor *= match lead {
+                Lead::Kings => KINGS_PLACEMENTS,
+                Lead::UniquePieces => UNIQUE_PIECES_PLACEMENTS,
+                Lead::Pawns(file) => encoding.lead_pawns[pairs.group_lengths[0]][file],
+            };
+        } else if k == order[1] {
+            // Off the first and last ranks, and the leading pawns' squares
+            pairs.group_factors[1] = factor;
+            factor *= encoding.binomial[pairs.group_lengths[1]][48 - pairs.group_lengths[0]];
+        } else {
+            pairs.group_factors[next] = factor;
+            factor *= encoding.binomial[pairs.group_lengths[next]][free_squares];
+            free_squares -= pairs.group_lengths[next];
+            next += 1;
+        }
+        k += 1;
+    }
+    pairs.group_factors[groups] = factor;
+    Ok(())
+}
+
+/// Reads the sizes of the compressed table of one side and its Huffman
+/// codes, returns the offset after them
+fn set_sizes(pairs: &mut Pairs, data: &[u8], mut offset: usize) -> Result<usize> {
+    pairs.flags = byte(data, offset)?;
+    offset += 1;
+    if pairs.flags & SINGLE_VALUE != 0 {
+        pairs.min_symbol_length = byte(data, offset)?;
+        return Ok(offset + 1);
+    }
+
+    let size: u64 = *pairs.group_factors.last().expect("The size of the table");
+    pairs.block_size = 1 << byte(data, offset)?;
+    pairs.span = 1 << byte(data, offset + 1)?;
+    pairs.sparse_index_size = size.div_ceil(pairs.span) as usize;
+    let padding: usize = usize::from(byte(data, offset + 2)?);
+    pairs.blocks_count = u32_le(data, offset + 3)? as usize;
+    pairs.block_lengths_size = pairs.blocks_count + padding;
+    let max_length: u8 = byte(data, offset + 7)?;
+    pairs.min_symbol_length = byte(data, offset + 8)?;
+    ensure!(pairs.min_symbol_length > 0 && max_length >= pairs.min_symbol_length, "Invalid symbol lengths");
+    offset += 9;
+
+    pairs.lowest_symbols = offset;
+    let lengths: usize = usize::from(max_length - pairs.min_symbol_length) + 1;
+    pairs.base = vec![0; lengths];
+    for i in (0..lengths - 1).rev() {
+        let lowest: u64 = u64::from(u16_le(data, offset + 2 * i)?);
+        let next_lowest: u64 = u64::from(u16_le(data, offset + 2 * (i + 1))?);
+        pairs.base[i] = pairs.base[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+    }
+    for (i, base) in pairs.base.iter_mut().enumerate() {
+        *base = base.checked_shl(64 - i as u32 - u32::from(pairs.min_symbol_length)).unwrap_or(0);
+    }
+    offset += 2 * lengths;
+
+    let symbols: usize = usize::from(u16_le(data, offset)?);
+    offset += 2;
+    pairs.tree = offset;
+    pairs.symbol_lengths = vec![0; symbols];
+    let mut visited: Vec<bool> = vec![false; symbols];
+    for symbol in 0..symbols {
+        if !visited[symbol] {
+            pairs.symbol_lengths[symbol] = symbol_length(pairs, data, symbol as u16, &mut visited)?;
+        }
+    }
+
+    Ok(offset + 3 * symbols + (symbols & 1))
+}
+
+/// The values a symbol stands for minus one: a symbol is a pair of
+/// symbols, down to the leaves
+fn symbol_length(pairs: &mut Pairs, data: &[u8], symbol: u16, visited: &mut [bool]) -> Result<u32> {
+    visited[usize::from(symbol)] = true;
+    let (left, right): (u16, u16) = children(data, pairs.tree, symbol)?;
+    if right == LEAF {
+        return Ok(0);
+    }
+    ensure!(usize::from(left.max(right)) < visited.len(), "Invalid symbol tree");
+
+    for child in [left, right] {
+        if !visited[usize::from(child)] {
+            pairs.symbol_lengths[usize::from(child)] = symbol_length(pairs, data, child, visited)?;
+        }
+    }
+
+    Ok(pairs.symbol_lengths[usize::from(left)] + pairs.symbol_lengths[usize::from(right)] + 1)
+}
+
+/// The two symbols of a pair, 12 bits each
+fn children(data: &[u8], tree: usize, symbol: u16) -> Result<(u16, u16)> {
+    let offset: usize = tree + 3 * usize::from(symbol);
+    let [a, b, c] = [byte(data, offset)?, byte(data, offset + 1)?, byte(data, offset + 2)?].map(u16::from);
+
+    Ok(((b & 0xf) << 8 | a, c << 4 | b >> 4))
+}
+
+fn byte(data: &[u8], offset: usize) -> Result<u8> {
+    data.get(offset).copied().context("Unexpected end of the file")
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes([byte(data, offset)?, byte(data, offset + 1)?]))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes([byte(data, offset)?, byte(data, offset + 1)?, byte(data, offset + 2)?, byte(data, offset + 3)?]))
+}
+
+/// The codes are read ahead of the last symbol of a block, past the end of
+/// the file there is nothing to read
+fn u32_be(data: &[u8], offset: usize) -> u32 {
+    let bytes: [u8; 4] = std::array::from_fn(|i| data.get(offset + i).copied().unwrap_or_default());
+
+    u32::from_be_bytes(bytes)
+}
+
+/// Writes tables in the format read above, with a fixed length code per
+/// value: enough to check the reading against known values
+#[cfg(test)]
+pub(super) mod fixture {
+    use super::{Kind, Lead, Pairs, DTZ_MAGIC, HAS_PAWNS, LEAF, NO_ORDER, WDL_MAGIC};
+
+    // A value takes 3 bits, 2048 of them fit in a block of 1024 bytes
+    const BITS: u32 = 3;
+    const BLOCK_SIZE_LOG: u8 = 10;
+    const VALUES_PER_BLOCK: usize = 2048;
+    const SPAN_LOG: u8 = 10;
+
+    /// The file of the leading pawn and the index of the pieces of
+    /// `material` as they come, for the tests to find the values they stored
+    pub(crate) fn index(material: &str, pieces: &[(u8, usize)], flip: bool, black_to_move: bool) -> (usize, u64) {
+        let data: Vec<u8> = write(material, Kind::Wdl, 0, &|_, _, _| 2);
+        let table: super::Table = super::Table::parse(data, material, Kind::Wdl).expect("A valid table");
+        let (file, _, index): (usize, usize, u64) = table.locate(pieces, flip, black_to_move);
+
+        (file, index)
+    }
+
+    /// A table of `material` with the value of each file of the leading
+    /// pawn, side and index given by `value`. `flags` go to every side,
+    /// values above 7 don't fit
+    pub(crate) fn write(material: &str, kind: Kind, flags: u8, value: &dyn Fn(usize, usize, u64) -> u8) -> Vec<u8> {
+        let (white, black) = material.split_once('v').expect("<white>v<black>");
+        let code = |letter: char| 1 + "PNBRQK".find(letter).expect("A piece letter") as u8;
+        // Kings first, then the unique pieces, as the generator orders them
+        let order = |side: &str, black: bool| -> Vec<u8> {
+            let mut letters: Vec<char> = side.chars().filter(|&letter| letter != 'P').collect();
+            letters.sort_by_key(|&letter| (letter != 'K', side.matches(letter).count(), "KQRBN".find(letter)));
+            letters.into_iter().map(|letter| code(letter) + if black { 8 } else { 0 }).collect()
+        };
+        let (white_order, black_order): (Vec<u8>, Vec<u8>) = (order(white, false), order(black, true));
+        let unique: bool = [white, black].iter().any(|side| "QRBN".chars().any(|piece| side.matches(piece).count() == 1));
+        // The pawns lead, white's when it has some, then the other side's
+        let pawns = |side: &str, black: bool| vec![code('P') + if black { 8 } else { 0 }; side.matches('P').count()];
+        let (lead, other_pawns): (Vec<u8>, Vec<u8>) = if white.contains('P') {
+            (pawns(white, false), pawns(black, true))
+        } else {
+            (pawns(black, true), pawns(white, false))
+        };
+        let has_pawns: bool = !lead.is_empty();
+        let kings: usize = lead.len() + other_pawns.len();
+        // The two kings, then the unique pieces
+        let mut rest: Vec<u8> = [&white_order[1..], &black_order[1..]].concat();
+        let counts: Vec<usize> = rest.iter().map(|piece| rest.iter().filter(|&other| other == piece).count()).collect();
+        let mut counted: Vec<(usize, u8)> = counts.into_iter().zip(rest.drain(..)).collect();
+        counted.sort_by_key(|&(count, _)| count > 1);
+        let pieces: Vec<u8> = [&lead[..], &other_pawns[..], &[white_order[0], black_order[0]]]
+            .concat()
+            .into_iter()
+            .chain(counted.into_iter().map(|(_, piece)| piece))
+            .collect();
+        // The other side to move has its own order, the black king first
+        let mut second: Vec<u8> = pieces.clone();
+        second.swap(kings, kings + 1);
+        let symmetric: bool = white == black;
+        let sides: Vec<Vec<u8>> = if kind == Kind::Wdl && !symmetric { vec![pieces, second] } else { vec![pieces] };
+        let files: usize = if has_pawns { 4 } else { 1 };
+
+        let mut data: Vec<u8> = match kind {
+            Kind::Wdl => WDL_MAGIC.to_vec(),
+            Kind::Dtz => DTZ_MAGIC.to_vec(),
+        };
+        data.push(u8::from(!symmetric) | if has_pawns { HAS_PAWNS } else { 0 });
+        for _ in 0..files {
+            // The first group first, then the other side's pawns
+            data.push(0);
+            if !other_pawns.is_empty() {
+                data.push(0x11);
+            }
+            for k in 0..sides[0].len() {
+                data.push(sides[0][k] | sides.get(1).map_or(0, |second| second[k] << 4));
+            }
+        }
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+
+        // The file of the leading pawn, the side and the size of each table
+        let mut sizes: Vec<(usize, usize, u64)> = Vec::new();
+        for file in 0..files {
+            for (side, pieces) in sides.iter().enumerate() {
+                let mut pairs: Pairs = Pairs {
+                    pieces: pieces.clone(),
+                    ..Pairs::default()
+                };
+                let lead: Lead = match (has_pawns, unique) {
+                    (true, _) => Lead::Pawns(file),
+                    (false, true) => Lead::UniquePieces,
+                    (false, false) => Lead::Kings,
+                };
+                let order: [usize; 2] = [0, if other_pawns.is_empty() { NO_ORDER } else { 1 }];
+                super::set_groups(&mut pairs, order, lead).expect("Valid groups");
+                sizes.push((file, side, *pairs.group_factors.last().expect("A size")));
+            }
+        }
+
+        for &(_, _, size) in &sizes {
+            let blocks: u32 = size.div_ceil(VALUES_PER_BLOCK as u64) as u32;
+            data.push(flags);
+            data.extend([BLOCK_SIZE_LOG, SPAN_LOG, 0]);
+            data.extend(blocks.to_le_bytes());
+            // One length of code, the symbols 0 to 7 from 0
+            data.extend([BITS as u8, BITS as u8]);
+            data.extend(0u16.to_le_bytes());
+            data.extend(8u16.to_le_bytes());
+            for symbol in 0..8u16 {
+                data.extend([symbol as u8, ((LEAF & 0xf) << 4) as u8, (LEAF >> 4) as u8]);
+            }
+            // 8 symbols, no padding
+        }
+        if kind == Kind::Dtz && data.len() % 2 == 1 {
+            data.push(0);
+        }
+
+        for &(_, _, size) in &sizes {
+            let span: u64 = 1 << SPAN_LOG;
+            for k in 0..size.div_ceil(span) {
+                let middle: u64 = k * span + span / 2;
+                let block: u64 = middle.min(size - 1) / VALUES_PER_BLOCK as u64;
+                let offset: u64 = middle - block * VALUES_PER_BLOCK as u64;
+                data.extend((block as u32).to_le_bytes());
+                data.extend((offset as u16).to_le_bytes());
+            }
+        }
+        for &(_, _, size) in &sizes {
+            for block in 0..size.div_ceil(VALUES_PER_BLOCK as u64) {
+                let values: u64 = (size - block * VALUES_PER_BLOCK as u64).min(VALUES_PER_BLOCK as u64);
+                data.extend(((values - 1) as u16).to_le_bytes());
+            }
+        }
+        for &(file, side, size) in &sizes {
+            data.resize(data.len().next_multiple_of(64), 0);
+            for block in 0..size.div_ceil(VALUES_PER_BLOCK as u64) {
+                let mut bits: Vec<bool> = Vec::new();
+                let start: u64 = block * VALUES_PER_BLOCK as u64;
+                for index in start..(start + VALUES_PER_BLOCK as u64).min(size) {
+                    let value: u8 = value(file, side, index);
+                    bits.extend((0..BITS).rev().map(|bit| value >> bit & 1 == 1));
+                }
+                let mut bytes: Vec<u8> =
+                    bits.chunks(8).map(|chunk| chunk.iter().enumerate().fold(0, |byte, (i, &bit)| byte | u8::from(bit) << (7 - i))).collect();
+                bytes.resize(1 << BLOCK_SIZE_LOG, 0);
+                data.extend(bytes);
+            }
+        }
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::fixture::{index, write};
+    use super::{file, in_triangle, off_diagonal, rank, Encoding, Kind, Table, KINGS_PLACEMENTS, UNIQUE_PIECES_PLACEMENTS};
+
+    // White king, white queen, black king
+    const KQVK: [u8; 3] = [6, 5, 14];
+
+    #[test]
+    fn test_kings_placements() {
+        let encoding: &Encoding = Encoding::get();
+        let mut codes: Vec<u64> = Vec::new();
+
+        for first in (0..64).filter(|&square| in_triangle(square)) {
+            for second in 0..64 {
+                let apart: bool = file(first).abs_diff(file(second)) > 1 || rank(first).abs_diff(rank(second)) > 1;
+                if apart && (off_diagonal(first) != 0 || off_diagonal(second) <= 0) {
+                    codes.push(encoding.kings[encoding.a1_d1_d4[first] as usize][second]);
+                }
+            }
+        }
+        codes.sort_unstable();
+
+        assert_eq!((0..KINGS_PLACEMENTS).collect::<Vec<u64>>(), codes);
+        assert_eq!(10, encoding.binomial[2][5]);
+    }
+
+    #[test]
+    fn test_index_folds_the_symmetries() {
+        let squares: [usize; 3] = [12, 45, 58];
+        let transformations: [fn(usize) -> usize; 4] = [|s| s, |s| s ^ 7, |s| s ^ 56, |s| ((s >> 3) | (s << 3)) & 63];
+        let indexes: HashSet<(usize, u64)> = transformations
+            .iter()
+            .map(|transform| {
+                let pieces: Vec<(u8, usize)> = KQVK.iter().zip(squares).map(|(&piece, square)| (piece, transform(square))).collect();
+                index("KQvK", &pieces, false, false)
+            })
+            .collect();
+
+        assert_eq!(1, indexes.len());
+        assert!(indexes.iter().all(|&(file, index)| file == 0 && index < UNIQUE_PIECES_PLACEMENTS));
+        // Another queen square, another index
+        assert!(!indexes.contains(&index("KQvK", &[(6, 12), (5, 44), (14, 58)], false, false)));
+    }
+
+    #[test]
+    fn test_decompress() -> anyhow::Result<()> {
+        let table: Table = Table::parse(write("KQvK", Kind::Wdl, 0, &|_, side, index| (index % 5) as u8 + side as u8), "KQvK", Kind::Wdl)?;
+
+        for index in (0..UNIQUE_PIECES_PLACEMENTS).step_by(97).chain([0, 1023, 2047, 2048, UNIQUE_PIECES_PLACEMENTS - 1]) {
+            assert_eq!((index % 5) as u16 + 1, table.decompress(table.pairs(0, 1), index)?, "{index}");
+        }
+        assert!(Table::parse(vec![0; 64], "KQvK", Kind::Wdl).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pawns_encoding() {
+        let encoding: &Encoding = Encoding::get();
+
+        // a2, h2, b2, e7
+        assert_eq!([47, 46, 35, 0], [8, 15, 9, 52].map(|square| encoding.pawns[square]));
+        assert_eq!([6; 4], encoding.lead_pawns[1]);
+        // The other leading pawn on a square mapped lower than a2, a3...
+        assert_eq!(47 + 45 + 43 + 41 + 39 + 37, encoding.lead_pawns[2][0]);
+    }
+
+    #[test]
+    fn test_pawn_index_covers_the_table() -> anyhow::Result<()> {
+        let table: Table = Table::parse(write("KPvK", Kind::Wdl, 0, &|_, _, _| 2), "KPvK", Kind::Wdl)?;
+        let size: u64 = 6 * 63 * 62;
+        let mut found: Vec<Vec<u8>> = vec![vec![0; size as usize]; 4];
+
+        for pawn in 8..56 {
+            for (king, black_king) in (0..64).flat_map(|king: usize| (0..64_usize).map(move |black_king| (king, black_king))) {
+                if king == pawn || black_king == pawn || king == black_king {
+                    continue;
+                }
+                let (file, _, index): (usize, usize, u64) = table.locate(&[(6, king), (1, pawn), (14, black_king)], false, false);
+                assert_eq!(file, [0, 1, 2, 3, 3, 2, 1, 0][pawn % 8]);
+                found[file][index as usize] += 1;
+            }
+        }
+
+        // Every index once for a position and once for its mirror image
+        assert!(found.iter().flatten().all(|&count| count == 2));
+        assert_eq!(size, *table.pairs(0, 0).group_factors.last().expect("A size"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pawn_index_folds_the_files() {
+        // A white pawn on b2 leads the one on g3: nearer the edge than c2,
+        // lower than b3
+        let pieces: [(u8, usize); 5] = [(6, 4), (1, 9), (1, 22), (9, 50), (14, 60)];
+        let mirrored: Vec<(u8, usize)> = pieces.iter().map(|&(piece, square)| (piece, square ^ 7)).collect();
+
+        assert_eq!(index("KPPvKP", &pieces, false, false), index("KPPvKP", &mirrored, false, false));
+        assert_eq!(1, index("KPPvKP", &pieces, false, false).0);
+        // The colors swapped: the black pawns lead from the seventh rank
+        let swapped: Vec<(u8, usize)> = pieces.iter().map(|&(piece, square)| (piece ^ 8, square ^ 56)).collect();
+        assert_eq!(index("KPPvKP", &pieces, false, false), index("KPPvKP", &swapped, true, true));
+    }
+
+    #[test]
+    fn test_decompress_by_file() -> anyhow::Result<()> {
+        let table: Table = Table::parse(write("KPvKN", Kind::Wdl, 0, &|file, side, _| (file * 2 + side) as u8), "KPvKN", Kind::Wdl)?;
+
+        for (file, pawn) in [(0, 8), (1, 49), (2, 18), (3, 36), (3, 12), (0, 47)] {
+            let pieces: [(u8, usize); 4] = [(6, 4), (1, pawn), (10, 55 - pawn % 8), (14, 60)];
+            assert_eq!(2 * file - 2, table.wdl(&pieces, false, false)?, "{pawn}");
+            assert_eq!(2 * file - 1, table.wdl(&pieces, false, true)?, "{pawn}");
+        }
+        assert!(Table::parse(write("KPvK", Kind::Wdl, 0, &|_, _, _| 2), "KQvK", Kind::Wdl).is_err());
+        Ok(())
+    }
+}
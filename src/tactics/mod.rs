@@ -0,0 +1,471 @@
+use std::fmt::{self, Display};
+
+use crate::game::ChessEngine;
+use crate::game::board::{Board, ROWS};
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::pieces::piece_kind::PieceKind;
+
+const ORTHOGONALS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const DIAGONALS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum MotifKind {
+    Fork,
+    Pin,
+    Skewer,
+    DiscoveredAttack,
+    DoubleCheck,
+    BackRank,
+    Hanging,
+    Overloaded,
+}
+
+impl Display for MotifKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fork => write!(f, "Fork"),
+            Self::Pin => write!(f, "Pin"),
+            Self::Skewer => write!(f, "Skewer"),
+            Self::DiscoveredAttack => write!(f, "Discovered attack"),
+            Self::DoubleCheck => write!(f, "Double check"),
+            Self::BackRank => write!(f, "Back rank"),
+            Self::Hanging => write!(f, "Hanging piece"),
+            Self::Overloaded => write!(f, "Overloaded defender"),
+        }
+    }
+}
+
+/// A tactical motif found on the board
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Motif {
+    kind: MotifKind,
+    // The side the motif plays for
+    color: Color,
+    // The piece making the motif first, then the ones it involves
+    squares: Vec<Position>,
+    explanation: String,
+}
+
+impl Motif {
+    pub(crate) const fn kind(&self) -> MotifKind {
+        self.kind
+    }
+
+    pub(crate) const fn color(&self) -> Color {
+        self.color
+    }
+
+    pub(crate) fn squares(&self) -> &[Position] {
+        &self.squares
+    }
+}
+
+impl Display for Motif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.explanation)
+    }
+}
+
+/// The motifs on the board of `chess_game`, for both sides
+pub(crate) fn find_motifs(chess_game: &ChessEngine) -> Vec<Motif> {
+    let board: &Board = chess_game.board();
+    let mut motifs: Vec<Motif> = Vec::new();
+
+    for color in [Color::White, Color::Black] {
+        motifs.extend(double_check(board, color));
+        motifs.extend(forks(board, color));
+        motifs.extend(lines(board, color));
+        motifs.extend(back_rank(board, color));
+        motifs.extend(hanging(board, color));
+        motifs.extend(overloaded(board, color));
+    }
+
+    motifs
+}
+
+/// The motifs `played` creates for the side playing it: the new ones on the
+/// board and the attacks it uncovers. None when the move is illegal
+pub(crate) fn motifs_after(chess_game: &ChessEngine, (from, to): (Position, Position)) -> Option<Vec<Motif>> {
+    let color: Color = chess_game.current_player();
+    let mut after: ChessEngine = chess_game.clone();
+    if !after.try_move(Some(from), Some(to)) {
+        return None;
+    }
+
+    let before: Vec<Motif> = find_motifs(chess_game);
+    let mut motifs: Vec<Motif> = discovered_attacks(chess_game.board(), after.board(), (from, to), color);
+    motifs.extend(
+        find_motifs(&after)
+            .into_iter()
+            .filter(|motif| motif.color == color && !before.contains(motif)),
+    );
+
+    Some(motifs)
+}
+
+fn describe(board: &Board, square: Position) -> String {
+    let name: &str = match board.piece(square, Color::Any) {
+        Some(PieceKind::Bishop(_)) => "bishop",
+        Some(PieceKind::King(_)) => "king",
+        Some(PieceKind::Knight(_)) => "knight",
+        Some(PieceKind::Pawn(_)) => "pawn",
+        Some(PieceKind::Queen(_)) => "queen",
+        Some(PieceKind::Rook(_)) => "rook",
+        None => "square",
+    };
+
+    format!("{name} on {}", square.to_notation())
+}
+
+fn describe_all(board: &Board, squares: &[Position]) -> String {
+    let described: Vec<String> = squares.iter().map(|&square| format!("the {}", describe(board, square))).collect();
+
+    match described.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, first)) => format!("{} and {last}", first.join(", ")),
+        None => String::new(),
+    }
+}
+
+fn value(board: &Board, square: Position) -> i16 {
+    board.piece(square, Color::Any).map_or(0, |piece| piece.points())
+}
+
+fn is_king(board: &Board, square: Position) -> bool {
+    matches!(board.piece(square, Color::Any), Some(PieceKind::King(_)))
+}
+
+/// Whether attacking the piece on `target` with the one on `attacker`
+/// threatens anything: the king, a more valuable piece or an undefended one
+fn threatens(board: &Board, attacker: Position, target: Position, color: Color) -> bool {
+    is_king(board, target)
+        || value(board, target) > value(board, attacker)
+        || board.attackers_of(target, color.other()).is_empty()
+}
+
+fn positions(board: &Board, color: Color) -> Vec<Position> {
+    board.pieces(color).iter().map(|piece| piece.position()).collect()
+}
+
+/// A piece of `color` attacking two enemy pieces or more at once
+fn forks(board: &Board, color: Color) -> Vec<Motif> {
+    let targets: Vec<Position> = positions(board, color.other());
+    let mut motifs: Vec<Motif> = Vec::new();
+
+    for attacker in positions(board, color) {
+        let forked: Vec<Position> = targets
+            .iter()
+            .copied()
+            .filter(|&target| board.attackers_of(target, color).contains(&attacker) && threatens(board, attacker, target, color))
+            .collect();
+        if forked.len() >= 2 {
+            motifs.push(Motif {
+                kind: MotifKind::Fork,
+                color,
+                explanation: format!("the {} attacks {}", describe(board, attacker), describe_all(board, &forked)),
+                squares: [vec![attacker], forked].concat(),
+            });
+        }
+    }
+
+    motifs
+}
+
+/// The pieces met from `square` in `direction`, in order
+fn ray(board: &Board, square: Position, direction: (isize, isize)) -> Vec<Position> {
+    let mut pieces: Vec<Position> = Vec::new();
+    let mut position: Position = square + direction;
+
+    while let Some(on_square) = board.square(position) {
+        if on_square.piece(Color::Any).is_some() {
+            pieces.push(position);
+        }
+        position = position + direction;
+    }
+
+    pieces
+}
+
+/// The pins and the skewers of the rooks, bishops and queens of `color`: two
+/// enemy pieces on the line of one, the most valuable behind for a pin,
+/// in front for a skewer
+fn lines(board: &Board, color: Color) -> Vec<Motif> {
+    let mut motifs: Vec<Motif> = Vec::new();
+
+    for slider in positions(board, color) {
+        let directions: Vec<(isize, isize)> = match board.piece(slider, color) {
+            Some(PieceKind::Queen(_)) => [ORTHOGONALS, DIAGONALS].concat(),
+            Some(PieceKind::Rook(_)) => ORTHOGONALS.to_vec(),
+            Some(PieceKind::Bishop(_)) => DIAGONALS.to_vec(),
+            _ => continue,
+        };
+
+        for direction in directions {
+            let [front, behind, ..] = ray(board, slider, direction)[..] else {
+                continue;
+            };
+            if board.piece(front, color.other()).is_none() || board.piece(behind, color.other()).is_none() {
+                continue;
+            }
+
+            let (kind, explanation): (MotifKind, String) = if is_king(board, behind) || value(board, behind) > value(board, front) {
+                (
+                    MotifKind::Pin,
+                    format!("the {} pins the {} to the {}", describe(board, slider), describe(board, front), describe(board, behind)),
+                )
+            } else if is_king(board, front) || value(board, front) > value(board, behind) {
+                (
+                    MotifKind::Skewer,
+                    format!("the {} skewers the {} and the {}", describe(board, slider), describe(board, front), describe(board, behind)),
+                )
+            } else {
+                continue;
+            };
+            motifs.push(Motif {
+                kind,
+                color,
+                squares: vec![slider, front, behind],
+                explanation,
+            });
+        }
+    }
+
+    motifs
+}
+
+/// The king of `color.other()` in check from two pieces
+fn double_check(board: &Board, color: Color) -> Option<Motif> {
+    let checkers: Vec<Position> = board.checkers(color.other());
+    if checkers.len() < 2 {
+        return None;
+    }
+    let king: Position = board.king_position(color.other())?;
+
+    Some(Motif {
+        kind: MotifKind::DoubleCheck,
+        color,
+        explanation: format!("{} check the king, only a king move helps", describe_all(board, &checkers)),
+        squares: [vec![king], checkers].concat(),
+    })
+}
+
+/// The king of `color.other()` stuck on its first row behind its own pieces
+/// or attacked squares, while `color` has a rook or a queen to check it there
+fn back_rank(board: &Board, color: Color) -> Option<Motif> {
+    let defender: Color = color.other();
+    let king: Position = board.king_position(defender)?;
+    let (back_row, forward): (usize, isize) = if defender == Color::White { (ROWS - 1, -1) } else { (0, 1) };
+    let heavy_pieces: bool = board
+        .pieces(color)
+        .iter()
+        .any(|piece| matches!(piece, PieceKind::Rook(_) | PieceKind::Queen(_)));
+    // A check along the row needs a way in
+    let open: bool = [-1isize, 1].into_iter().any(|side| board.piece(king + (0, side), Color::Any).is_none() && board.square(king + (0, side)).is_some());
+    if king.row() != back_row || !heavy_pieces || !open {
+        return None;
+    }
+
+    let escapes: Vec<Position> = [-1isize, 0, 1]
+        .into_iter()
+        .map(|side| king + (forward, side))
+        .filter(|&escape| board.square(escape).is_some())
+        .collect();
+    let blocked: bool = escapes
+        .iter()
+        .all(|&escape| board.piece(escape, defender).is_some() || board.is_square_attacked(escape, color));
+    if !blocked {
+        return None;
+    }
+
+    Some(Motif {
+        kind: MotifKind::BackRank,
+        color,
+        explanation: format!("the {} can't leave its first row, a check along it could mate", describe(board, king)),
+        squares: [vec![king], escapes].concat(),
+    })
+}
+
+/// The pieces of `color.other()` attacked by `color` and not defended
+fn hanging(board: &Board, color: Color) -> Vec<Motif> {
+    positions(board, color.other())
+        .into_iter()
+        .filter(|&target| !is_king(board, target) && board.attackers_of(target, color.other()).is_empty())
+        .filter_map(|target| {
+            let attackers: Vec<Position> = board.attackers_of(target, color);
+            (!attackers.is_empty()).then(|| Motif {
+                kind: MotifKind::Hanging,
+                color,
+                explanation: format!("the {} is attacked by {} and not defended", describe(board, target), describe_all(board, &attackers)),
+                squares: [vec![target], attackers].concat(),
+            })
+        })
+        .collect()
+}
+
+/// The pieces of `color.other()` that alone defend two attacked pieces or
+/// more: taking one leaves the others
+fn overloaded(board: &Board, color: Color) -> Vec<Motif> {
+    let defender: Color = color.other();
+    let attacked: Vec<(Position, Vec<Position>)> = positions(board, defender)
+        .into_iter()
+        .filter(|&target| !is_king(board, target) && board.is_square_attacked(target, color))
+        .map(|target| (target, board.attackers_of(target, defender)))
+        .collect();
+    let mut motifs: Vec<Motif> = Vec::new();
+
+    for guard in positions(board, defender) {
+        let duties: Vec<Position> = attacked
+            .iter()
+            .filter(|(_, defenders)| defenders == &[guard])
+            .map(|&(target, _)| target)
+            .collect();
+        if duties.len() >= 2 {
+            motifs.push(Motif {
+                kind: MotifKind::Overloaded,
+                color,
+                explanation: format!("the {} alone defends {}, all attacked", describe(board, guard), describe_all(board, &duties)),
+                squares: [vec![guard], duties].concat(),
+            });
+        }
+    }
+
+    motifs
+}
+
+/// The attacks of the pieces of `color` behind the one moved from `from`,
+/// uncovered by the move
+fn discovered_attacks(before: &Board, after: &Board, (from, to): (Position, Position), color: Color) -> Vec<Motif> {
+    let mut motifs: Vec<Motif> = Vec::new();
+
+    for target in positions(after, color.other()) {
+        let uncovered: Vec<Position> = after
+            .attackers_of(target, color)
+            .into_iter()
+            .filter(|&attacker| attacker != to && between(attacker, target, from))
+            .filter(|attacker| !before.attackers_of(target, color).contains(attacker))
+            .filter(|&attacker| threatens(after, attacker, target, color))
+            .collect();
+
+        for attacker in uncovered {
+            let check: &str = if is_king(after, target) { "check" } else { "attack" };
+            motifs.push(Motif {
+                kind: MotifKind::DiscoveredAttack,
+                color,
+                explanation: format!(
+                    "the {}, leaving {}, uncovers the {} against the {}, a discovered {check}",
+                    describe(after, to),
+                    from.to_notation(),
+                    describe(after, attacker),
+                    describe(after, target)
+                ),
+                squares: vec![to, attacker, target],
+            });
+        }
+    }
+
+    motifs
+}
+
+/// Whether `square` lies on the line from `from` to `to`, strictly between
+fn between(from: Position, to: Position, square: Position) -> bool {
+    let offset = |a: Position, b: Position| (b.row() as isize - a.row() as isize, b.column() as isize - a.column() as isize);
+    let (rows, columns): (isize, isize) = offset(from, to);
+    let (square_rows, square_columns): (isize, isize) = offset(from, square);
+
+    // Collinear, same way, closer
+    rows * square_columns == columns * square_rows
+        && rows.signum() == square_rows.signum()
+        && columns.signum() == square_columns.signum()
+        && square_rows.abs().max(square_columns.abs()) < rows.abs().max(columns.abs())
+        && (square_rows, square_columns) != (0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::game::fen_parser::FenParser;
+    use crate::protocol::parse_move;
+
+    use super::{find_motifs, motifs_after, Motif, MotifKind};
+
+    fn squares(notations: &[&str]) -> Vec<Position> {
+        notations.iter().filter_map(|notation| Position::from_notation(notation)).collect()
+    }
+
+    fn find(motifs: &[Motif], kind: MotifKind) -> Option<&Motif> {
+        motifs.iter().find(|motif| motif.kind() == kind)
+    }
+
+    #[rstest]
+    // The knight forks the king and the rook
+    #[case("8/8/2r3k1/4N3/8/8/8/4K3 b - - 0 1", MotifKind::Fork, Color::White, &["e5", "c6", "g6"])]
+    // The bishop pins the knight to the king
+    #[case("4k3/8/2n5/1B6/8/8/8/4K3 b - - 0 1", MotifKind::Pin, Color::White, &["b5", "c6", "e8"])]
+    // The rook skewers the king and the queen
+    #[case("q7/8/8/k7/8/8/8/R3K3 b - - 0 1", MotifKind::Skewer, Color::White, &["a1", "a5", "a8"])]
+    // The king behind its pawns, a rook on the board
+    #[case("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", MotifKind::BackRank, Color::White, &["g8", "f7", "g7", "h7"])]
+    #[case("4k3/8/8/3r4/8/8/8/3RK3 w - - 0 1", MotifKind::Hanging, Color::White, &["d5", "d1"])]
+    // The queen alone defends both knights
+    #[case("4k3/8/3q4/2n1n3/3P4/8/8/4K3 w - - 0 1", MotifKind::Overloaded, Color::White, &["d6", "c5", "e5"])]
+    #[case("4k3/8/8/1B6/8/8/8/4RK2 b - - 0 1", MotifKind::DoubleCheck, Color::White, &["e8"])]
+    fn test_find_motifs(
+        #[case]
+        fen: &str,
+        #[case]
+        kind: MotifKind,
+        #[case]
+        color: Color,
+        #[case]
+        expected: &[&str]
+    ) -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse(fen)?;
+
+        let motifs: Vec<Motif> = find_motifs(&chess_game);
+
+        let motif: &Motif = find(&motifs, kind).unwrap_or_else(|| panic!("No {kind} in {motifs:?}"));
+        assert_eq!(color, motif.color());
+        assert_eq!(squares(expected), motif.squares()[..expected.len()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_quiet_position() {
+        assert_eq!(Vec::<Motif>::new(), find_motifs(&ChessEngine::new()));
+    }
+
+    #[test]
+    fn test_discovered_double_check() -> Result<()> {
+        // The knight leaves the e-file with check: both pieces check
+        let chess_game: ChessEngine = FenParser::parse("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1")?;
+
+        let motifs: Vec<Motif> = motifs_after(&chess_game, parse_move("e4d6").expect("A move")).expect("A legal move");
+
+        let discovered: &Motif = find(&motifs, MotifKind::DiscoveredAttack).expect("A discovered attack");
+        assert_eq!(squares(&["d6", "e1", "e8"]), discovered.squares());
+        assert!(discovered.to_string().contains("discovered check"), "{discovered}");
+        assert!(find(&motifs, MotifKind::DoubleCheck).is_some());
+        assert!(motifs_after(&chess_game, parse_move("e4e5").expect("A move")).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explanation() -> Result<()> {
+        let chess_game: ChessEngine = FenParser::parse("8/8/2r3k1/4N3/8/8/8/4K3 b - - 0 1")?;
+
+        let motifs: Vec<Motif> = find_motifs(&chess_game);
+
+        assert_eq!(
+            Some("the knight on e5 attacks the rook on c6 and the king on g6".to_string()),
+            find(&motifs, MotifKind::Fork).map(Motif::to_string)
+        );
+        Ok(())
+    }
+}
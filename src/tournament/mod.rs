@@ -0,0 +1,449 @@
+use std::fmt::{self, Display};
+
+use anyhow::{Context, Result};
+
+use crate::bot::Bot;
+use crate::bot::score::Score;
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::{ChessEngine, Result as GameResult};
+use crate::game::board::color::Color;
+use crate::game::fen_parser::FenParser;
+use crate::game::pgn::Pgn;
+use crate::game::pieces::piece_kind::PieceKind;
+use crate::protocol::play_moves;
+
+use stats::{Sprt, SprtStatus, Tally};
+
+pub(crate) mod stats;
+
+// Short and balanced, so that the engines don't replay the same game
+const DEFAULT_OPENINGS: [&str; 8] = [
+    "e2e4 e7e5 g1f3 b8c6",
+    "e2e4 c7c5 g1f3 d7d6",
+    "e2e4 e7e6 d2d4 d7d5",
+    "e2e4 c7c6 d2d4 d7d5",
+    "d2d4 d7d5 c2c4 e7e6",
+    "d2d4 g8f6 c2c4 g7g6",
+    "c2c4 e7e5 b1c3 g8f6",
+    "g1f3 d7d5 g2g3 g8f6",
+];
+const FIFTY_MOVES_PLIES: usize = 100;
+const DEFAULT_MAX_PLIES: usize = 400;
+const DEFAULT_RESIGN_SCORE: Score = Score::centipawns(1_000);
+const DEFAULT_RESIGN_MOVES: usize = 3;
+
+/// How a game ended, from white's point of view
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Outcome {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl Outcome {
+    fn win(color: Color) -> Self {
+        match color {
+            Color::White => Self::WhiteWins,
+            _ => Self::BlackWins,
+        }
+    }
+
+    /// Points scored by `color`
+    pub(crate) fn points(self, color: Color) -> f64 {
+        match (self, color) {
+            (Self::Draw, _) => 0.5,
+            (Self::WhiteWins, Color::White) | (Self::BlackWins, Color::Black) => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WhiteWins => write!(f, "1-0"),
+            Self::BlackWins => write!(f, "0-1"),
+            Self::Draw => write!(f, "1/2-1/2"),
+        }
+    }
+}
+
+/// When a game is stopped before the engines finish it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Adjudication {
+    max_plies: usize,
+    resign_score: Score,
+    resign_moves: usize,
+}
+
+impl Adjudication {
+    /// Draw after `max_plies` plies played from the opening
+    pub(crate) const fn with_max_plies(mut self, max_plies: usize) -> Self {
+        self.max_plies = max_plies;
+        self
+    }
+
+    /// A side loses once it scores its position `resign_score` or worse for
+    /// `resign_moves` moves in a row
+    pub(crate) const fn with_resign(mut self, resign_score: Score, resign_moves: usize) -> Self {
+        self.resign_score = resign_score;
+        self.resign_moves = resign_moves;
+        self
+    }
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Self {
+            max_plies: DEFAULT_MAX_PLIES,
+            resign_score: DEFAULT_RESIGN_SCORE,
+            resign_moves: DEFAULT_RESIGN_MOVES,
+        }
+    }
+}
+
+/// A finished game of a match
+#[derive(Clone, Debug)]
+pub(crate) struct Game {
+    round: usize,
+    white: String,
+    black: String,
+    chess_game: ChessEngine,
+    outcome: Outcome,
+    reason: String,
+    adjudicated: bool,
+}
+
+impl Game {
+    pub(crate) fn pgn(&self) -> Pgn {
+        let termination: &str = if self.adjudicated { "adjudication" } else { "normal" };
+
+        Pgn::new(&self.chess_game)
+            .with_tag("Event", "chessterm match")
+            .with_tag("Site", "chessterm")
+            .with_tag("Round", &self.round.to_string())
+            .with_tag("White", &self.white)
+            .with_tag("Black", &self.black)
+            .with_tag("Result", &self.outcome.to_string())
+            .with_tag("Termination", termination)
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Game {}: {} vs {} {} ({}, {} plies)",
+            self.round,
+            self.white,
+            self.black,
+            self.outcome,
+            self.reason,
+            self.chess_game.history().len(),
+        )
+    }
+}
+
+/// Plays round `round` from `opening`, to the end or until it is adjudicated
+pub(crate) fn play_game(
+    round: usize,
+    white: &mut dyn Bot,
+    black: &mut dyn Bot,
+    opening: &ChessEngine,
+    limits: &SearchLimits,
+    adjudication: &Adjudication,
+) -> Game {
+    let (chess_game, outcome, reason, adjudicated) = play(white, black, opening, limits, adjudication);
+
+    Game {
+        round,
+        white: white.name().to_string(),
+        black: black.name().to_string(),
+        chess_game,
+        outcome,
+        reason,
+        adjudicated,
+    }
+}
+
+/// The final position, the outcome, why the game ended, and whether it was
+/// adjudicated
+fn play(
+    white: &mut dyn Bot,
+    black: &mut dyn Bot,
+    opening: &ChessEngine,
+    limits: &SearchLimits,
+    adjudication: &Adjudication,
+) -> (ChessEngine, Outcome, String, bool) {
+    let mut chess_game: ChessEngine = opening.clone();
+    let opening_plies: usize = opening.history().len();
+    // Plies since the last capture or pawn move
+    let mut quiet_plies: usize = 0;
+    let mut losing_moves: [usize; 2] = [0, 0];
+    white.new_game();
+    black.new_game();
+
+    loop {
+        let player: Color = chess_game.current_player();
+        match chess_game.result() {
+            GameResult::Checkmate => return (chess_game, Outcome::win(player.other()), "checkmate".to_string(), false),
+            GameResult::Stalemate => return (chess_game, Outcome::Draw, "stalemate".to_string(), false),
+            GameResult::Draw => return (chess_game, Outcome::Draw, "threefold repetition".to_string(), false),
+            GameResult::None => {}
+        }
+        if insufficient_material(&chess_game) {
+            return (chess_game, Outcome::Draw, "insufficient material".to_string(), true);
+        }
+        if quiet_plies >= FIFTY_MOVES_PLIES {
+            return (chess_game, Outcome::Draw, "fifty-move rule".to_string(), true);
+        }
+        if chess_game.history().len() - opening_plies >= adjudication.max_plies {
+            return (chess_game, Outcome::Draw, "move limit".to_string(), true);
+        }
+
+        let bot: &mut dyn Bot = if player == Color::White { &mut *white } else { &mut *black };
+        let result: SearchResult = bot.choose_move(&chess_game, limits.clone());
+        let Some((from, to)) = result.best_move() else {
+            let reason: String = format!("{} forfeits: {}", bot.name(), bot.failure().unwrap_or("no move"));
+            return (chess_game, Outcome::win(player.other()), reason, true);
+        };
+
+        let resets_clock: bool = chess_game.square(to).is_some_and(|square| square.piece(Color::Any).is_some())
+            || chess_game
+                .square(from)
+                .and_then(|square| square.piece(player))
+                .is_some_and(|piece| matches!(piece, PieceKind::Pawn(_)));
+        if !chess_game.try_move(Some(from), Some(to)) {
+            let reason: String = format!("{} played an illegal move", bot.name());
+            return (chess_game, Outcome::win(player.other()), reason, true);
+        }
+        quiet_plies = if resets_clock { 0 } else { quiet_plies + 1 };
+
+        let losing: &mut usize = &mut losing_moves[usize::from(player == Color::Black)];
+        *losing = if result.score() <= -adjudication.resign_score { *losing + 1 } else { 0 };
+        if *losing >= adjudication.resign_moves {
+            let reason: String = format!("{} resigns", bot.name());
+            return (chess_game, Outcome::win(player.other()), reason, true);
+        }
+    }
+}
+
+/// Neither side can checkmate: bare kings, or a single minor piece left
+fn insufficient_material(chess_game: &ChessEngine) -> bool {
+    let board = chess_game.board();
+    let pieces: Vec<&PieceKind> = board
+        .pieces(Color::White)
+        .into_iter()
+        .chain(board.pieces(Color::Black))
+        .filter(|piece| !matches!(piece, PieceKind::King(_)))
+        .collect();
+
+    match pieces[..] {
+        [] => true,
+        [piece] => matches!(piece, PieceKind::Bishop(_) | PieceKind::Knight(_)),
+        _ => false,
+    }
+}
+
+/// Openings from a file with one FEN (or 4-field EPD) per line
+pub(crate) fn read_openings(content: &str) -> Result<Vec<ChessEngine>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(index, line)| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let fen: String = if fields.len() >= 6 {
+                fields[..6].join(" ")
+            } else {
+                format!("{} 0 1", fields.join(" "))
+            };
+            FenParser::parse(&fen).with_context(|| format!("Invalid opening {}: {line}", index + 1))
+        })
+        .collect()
+}
+
+fn default_openings() -> Vec<ChessEngine> {
+    DEFAULT_OPENINGS
+        .iter()
+        .map(|line| {
+            let mut chess_game: ChessEngine = ChessEngine::new();
+            let moves: Vec<&str> = line.split_whitespace().collect();
+            play_moves(&mut chess_game, &moves).expect("The default openings are legal");
+            chess_game
+        })
+        .collect()
+}
+
+/// Games between two engines, each opening played twice with the colors
+/// swapped
+pub(crate) struct Match {
+    bots: [Box<dyn Bot>; 2],
+    openings: Vec<ChessEngine>,
+    games: usize,
+    limits: SearchLimits,
+    adjudication: Adjudication,
+    sprt: Option<Sprt>,
+}
+
+impl Match {
+    pub(crate) fn new(first: Box<dyn Bot>, second: Box<dyn Bot>, limits: SearchLimits) -> Self {
+        Self {
+            bots: [first, second],
+            openings: default_openings(),
+            games: 2,
+            limits,
+            adjudication: Adjudication::default(),
+            sprt: None,
+        }
+    }
+
+    pub(crate) fn with_games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Ignored when empty
+    pub(crate) fn with_openings(mut self, openings: Vec<ChessEngine>) -> Self {
+        if !openings.is_empty() {
+            self.openings = openings;
+        }
+        self
+    }
+
+    pub(crate) fn with_adjudication(mut self, adjudication: Adjudication) -> Self {
+        self.adjudication = adjudication;
+        self
+    }
+
+    /// Stops the match as soon as the test concludes
+    pub(crate) fn with_sprt(mut self, sprt: Sprt) -> Self {
+        self.sprt = Some(sprt);
+        self
+    }
+
+    /// Plays the games, calling `on_game` after each one with the results so
+    /// far of the first engine
+    pub(crate) fn run(&mut self, on_game: &mut dyn FnMut(&Game, &Tally)) -> Tally {
+        let mut tally: Tally = Tally::default();
+
+        for index in 0..self.games {
+            let opening: &ChessEngine = &self.openings[index / 2 % self.openings.len()];
+            let first_is_white: bool = index % 2 == 0;
+            let [first, second] = &mut self.bots;
+            let (white, black): (&mut Box<dyn Bot>, &mut Box<dyn Bot>) = if first_is_white {
+                (first, second)
+            } else {
+                (second, first)
+            };
+
+            let game: Game = play_game(index + 1, white.as_mut(), black.as_mut(), opening, &self.limits, &self.adjudication);
+
+            let first_color: Color = if first_is_white { Color::White } else { Color::Black };
+            tally.add(game.outcome.points(first_color));
+            on_game(&game, &tally);
+
+            if self.sprt.is_some_and(|sprt| sprt.status(&tally) != SprtStatus::Continue) {
+                break;
+            }
+        }
+
+        tally
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::bot::alpha_beta_bot::AlphaBetaBot;
+    use crate::bot::score::Score;
+    use crate::bot::search::SearchLimits;
+    use crate::bot::{self, Bot};
+    use crate::game::ChessEngine;
+    use crate::game::fen_parser::FenParser;
+
+    use super::stats::Tally;
+    use super::{play_game, read_openings, Adjudication, Game, Match, Outcome};
+
+    #[test]
+    fn test_checkmate_ends_game() -> Result<()> {
+        let opening: ChessEngine = FenParser::parse("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1")?;
+        let mut white: AlphaBetaBot = AlphaBetaBot::new();
+        let mut black: AlphaBetaBot = AlphaBetaBot::new();
+
+        let game: Game = play_game(1, &mut white, &mut black, &opening, &SearchLimits::depth(2), &Adjudication::default());
+
+        assert_eq!(Outcome::WhiteWins, game.outcome);
+        assert_eq!("checkmate", game.reason.as_str());
+        assert!(!game.adjudicated);
+        assert_eq!(1, game.chess_game.history().len());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("8/8/4k3/8/8/3K4/8/8 w - - 0 1", Adjudication::default(), Outcome::Draw, "insufficient material", 0)]
+    #[case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        Adjudication::default().with_max_plies(6),
+        Outcome::Draw,
+        "move limit",
+        6
+    )]
+    #[case(
+        "3qk3/8/8/8/8/8/8/4K3 w - - 0 1",
+        Adjudication::default().with_resign(Score::centipawns(500), 1),
+        Outcome::BlackWins,
+        "alpha-beta resigns",
+        1
+    )]
+    fn test_adjudications(
+        #[case] fen: &str,
+        #[case] adjudication: Adjudication,
+        #[case] outcome: Outcome,
+        #[case] reason: &str,
+        #[case] plies: usize,
+    ) -> Result<()> {
+        // Searched to a fixed depth, the games are the same every time
+        let mut white: AlphaBetaBot = AlphaBetaBot::new();
+        let mut black: AlphaBetaBot = AlphaBetaBot::new();
+        let opening: ChessEngine = FenParser::parse(fen)?;
+
+        let game: Game = play_game(1, &mut white, &mut black, &opening, &SearchLimits::depth(1), &adjudication);
+
+        assert_eq!((outcome, reason, plies), (game.outcome, game.reason.as_str(), game.chess_game.history().len()));
+        assert!(game.adjudicated);
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_swaps_colors() -> Result<()> {
+        let openings: Vec<ChessEngine> = read_openings("# comment\n4k3/8/8/8/8/8/8/R3K3 w - -\n")?;
+        let first: Box<dyn Bot> = Box::new(AlphaBetaBot::new());
+        let second: Box<dyn Bot> = bot::from_name("random")?;
+        let mut games: Vec<Game> = Vec::new();
+
+        let tally: Tally = Match::new(first, second, SearchLimits::depth(1))
+            .with_games(2)
+            .with_openings(openings)
+            .with_adjudication(Adjudication::default().with_max_plies(4))
+            .run(&mut |game, _| games.push(game.clone()));
+
+        assert_eq!(2, tally.games());
+        assert_eq!(2, games.len());
+        assert_eq!(("alpha-beta", "random"), (games[0].white.as_str(), games[0].black.as_str()));
+        assert_eq!(("random", "alpha-beta"), (games[1].white.as_str(), games[1].black.as_str()));
+        assert_eq!(Some("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), games[0].pgn().tag("FEN"));
+        assert_eq!(Some("2"), games[1].pgn().tag("Round"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_openings() {
+        assert_eq!(2, read_openings("8/8/4k3/8/8/3K4/8/8 w - - 0 1\n\n8/8/4k3/8/8/3K4/8/8 b - -\n").unwrap().len());
+        assert!(read_openings("8/8 w").is_err());
+    }
+}
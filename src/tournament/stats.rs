@@ -0,0 +1,208 @@
+use std::fmt::{self, Display};
+
+// Two-sided 95% confidence
+const Z_95: f64 = 1.96;
+
+/// Results of a match from the point of view of the first engine
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Tally {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl Tally {
+    /// Records a game, `points` being 1, 0.5 or 0
+    pub(crate) fn add(&mut self, points: f64) {
+        if points > 0.5 {
+            self.wins += 1;
+        } else if points < 0.5 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+    }
+
+    pub(crate) const fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Average points per game, between 0 and 1
+    pub(crate) fn score(&self) -> Option<f64> {
+        if self.games() == 0 {
+            return None;
+        }
+
+        Some((f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games()))
+    }
+
+    /// Variance of the points of a single game
+    fn variance(&self) -> Option<f64> {
+        let score: f64 = self.score()?;
+        let games: f64 = f64::from(self.games());
+
+        Some(
+            (f64::from(self.wins) * (1.0 - score).powi(2)
+                + f64::from(self.draws) * (0.5 - score).powi(2)
+                + f64::from(self.losses) * score.powi(2))
+                / games,
+        )
+    }
+
+    /// Elo difference, infinite when one side scored every point
+    pub(crate) fn elo(&self) -> Option<f64> {
+        self.score().map(elo)
+    }
+
+    /// Half the width of the 95% confidence interval of `elo`
+    pub(crate) fn elo_error(&self) -> Option<f64> {
+        let score: f64 = self.score()?;
+        let deviation: f64 = (self.variance()? / f64::from(self.games())).sqrt();
+        let low: f64 = elo((score - Z_95 * deviation).max(0.0));
+        let high: f64 = elo((score + Z_95 * deviation).min(1.0));
+
+        Some((high - low) / 2.0)
+    }
+}
+
+impl Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)?;
+
+        if let (Some(elo), Some(error)) = (self.elo(), self.elo_error()) {
+            write!(f, " Elo {elo:+.1} +/- {error:.1}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl Tally {
+    pub(crate) const fn new(wins: u32, draws: u32, losses: u32) -> Self {
+        Self {
+            wins,
+            draws,
+            losses,
+        }
+    }
+}
+
+/// Elo difference giving an expected `score`
+fn elo(score: f64) -> f64 {
+    // Adding zero turns -0 into 0
+    -400.0 * (1.0 / score - 1.0).log10() + 0.0
+}
+
+/// Expected score for an Elo difference of `elo`
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SprtStatus {
+    Continue,
+    /// The first engine is not `elo1` stronger, accepting `elo0`
+    AcceptH0,
+    /// The first engine is `elo1` stronger, rejecting `elo0`
+    AcceptH1,
+}
+
+/// Sequential probability ratio test between the hypotheses "the first
+/// engine is `elo0` stronger" and "the first engine is `elo1` stronger",
+/// with 5% false positives and false negatives
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Sprt {
+    pub(crate) const fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// Log-likelihood ratio of the results, with the normal approximation
+    pub(crate) fn llr(&self, tally: &Tally) -> f64 {
+        let (Some(score), Some(variance)) = (tally.score(), tally.variance()) else {
+            return 0.0;
+        };
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let score0: f64 = expected_score(self.elo0);
+        let score1: f64 = expected_score(self.elo1);
+
+        f64::from(tally.games()) * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// The LLR below which `elo0` is accepted, and above which `elo1` is
+    pub(crate) fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    pub(crate) fn status(&self, tally: &Tally) -> SprtStatus {
+        let llr: f64 = self.llr(tally);
+        let (lower, upper) = self.bounds();
+
+        if llr <= lower {
+            SprtStatus::AcceptH0
+        } else if llr >= upper {
+            SprtStatus::AcceptH1
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{Sprt, SprtStatus, Tally};
+
+    #[test]
+    fn test_tally() {
+        let mut tally: Tally = Tally::default();
+        for points in [1.0, 0.5, 0.0, 1.0] {
+            tally.add(points);
+        }
+
+        assert_eq!(Tally::new(2, 1, 1), tally);
+        assert_eq!(Some(0.625), tally.score());
+        assert_eq!(None, Tally::default().score());
+    }
+
+    #[test]
+    fn test_elo() {
+        let even: Tally = Tally::new(10, 20, 10);
+        assert_eq!(Some(0.0), even.elo());
+
+        let stronger: Tally = Tally::new(60, 20, 20);
+        let elo: f64 = stronger.elo().unwrap();
+        let error: f64 = stronger.elo_error().unwrap();
+        // A 70% score is about 147 Elo
+        assert!((elo - 147.2).abs() < 0.1, "{elo}");
+        assert!(error > 50.0 && error < 100.0, "{error}");
+        assert!(stronger.to_string().starts_with("+60 =20 -20 Elo +147.2 +/- "));
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt: Sprt = Sprt::new(0.0, 10.0);
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001);
+        assert!((upper - 2.944).abs() < 0.001);
+
+        assert_eq!(SprtStatus::Continue, sprt.status(&Tally::new(5, 5, 4)));
+        assert_eq!(SprtStatus::AcceptH1, sprt.status(&Tally::new(600, 200, 200)));
+        assert_eq!(SprtStatus::AcceptH0, sprt.status(&Tally::new(200, 200, 600)));
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::bot::Bot;
+use crate::bot::search::{SearchLimits, SearchResult};
+use crate::game::ChessEngine;
+
+/// The bot thinking about the position shown, without a time limit, in a
+/// thread of its own. It is restarted whenever the position changes
+pub(crate) struct Analysis {
+    chess_game: ChessEngine,
+    stop: Arc<AtomicBool>,
+    results: Receiver<SearchResult>,
+    // Gives the bot back once stopped, for the next position
+    search: Option<JoinHandle<Box<dyn Bot>>>,
+    last: Option<SearchResult>,
+}
+
+impl Analysis {
+    /// Any bot can analyze, an external UCI engine too
+    pub(crate) fn start(chess_game: &ChessEngine, mut bot: Box<dyn Bot>) -> Self {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let (sender, results): (Sender<SearchResult>, Receiver<SearchResult>) = mpsc::channel();
+        let limits: SearchLimits = SearchLimits::default().with_stop(Arc::clone(&stop));
+        let analyzed: ChessEngine = chess_game.clone();
+
+        let search: JoinHandle<Box<dyn Bot>> = thread::spawn(move || {
+            bot.search(&analyzed, limits, &mut |result| {
+                // The receiver is gone once the analysis is stopped
+                let _ = sender.send(result.clone());
+            });
+            bot
+        });
+
+        Self {
+            chess_game: chess_game.clone(),
+            stop,
+            results,
+            search: Some(search),
+            last: None,
+        }
+    }
+
+    /// The position analyzed
+    pub(crate) const fn chess_game(&self) -> &ChessEngine {
+        &self.chess_game
+    }
+
+    /// Restarts the analysis when `chess_game` is not the position analyzed
+    pub(crate) fn follow(&mut self, chess_game: &ChessEngine) {
+        if self.chess_game.key() != chess_game.key() || self.chess_game.history() != chess_game.history() {
+            self.stop.store(true, Ordering::Relaxed);
+            let search: JoinHandle<Box<dyn Bot>> = self.search.take().expect("The analysis runs until stopped");
+            *self = Self::start(chess_game, search.join().expect("The analysis thread should not panic"));
+        }
+    }
+
+    /// Collects the results found since the last call, returns whether
+    /// there was any
+    pub(crate) fn update(&mut self) -> bool {
+        let mut updated: bool = false;
+        while let Ok(result) = self.results.try_recv() {
+            self.last = Some(result);
+            updated = true;
+        }
+
+        updated
+    }
+
+    /// Stops the search and gives the bot back
+    pub(crate) fn stop(mut self) -> Box<dyn Bot> {
+        self.stop.store(true, Ordering::Relaxed);
+        let search: JoinHandle<Box<dyn Bot>> = self.search.take().expect("The analysis runs until stopped");
+
+        search.join().expect("The analysis thread should not panic")
+    }
+
+    /// The deepest result collected so far
+    pub(crate) const fn result(&self) -> Option<&SearchResult> {
+        self.last.as_ref()
+    }
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::bot;
+    use crate::bot::alpha_beta_bot::AlphaBetaBot;
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+
+    use super::Analysis;
+
+    fn wait_for_result(analysis: &mut Analysis) -> bool {
+        let start: Instant = Instant::now();
+
+        while start.elapsed() < Duration::from_secs(10) {
+            if analysis.update() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        false
+    }
+
+    #[test]
+    fn test_analysis_follows_the_position() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut analysis: Analysis = Analysis::start(&chess_game, Box::new(AlphaBetaBot::new()));
+        assert!(wait_for_result(&mut analysis));
+
+        chess_game.try_move(Position::from_notation("e2"), Position::from_notation("e4"));
+        analysis.follow(&chess_game);
+
+        assert_eq!(chess_game.key(), analysis.chess_game().key());
+        assert!(wait_for_result(&mut analysis));
+        let (from, to) = analysis.result().and_then(|result| result.best_move()).expect("A best move");
+        assert!(chess_game.legal_move(from, to).is_some());
+    }
+
+    #[test]
+    fn test_stop_gives_the_bot_back() -> anyhow::Result<()> {
+        let mut analysis: Analysis = Analysis::start(&ChessEngine::new(), bot::from_name("greedy")?);
+        assert!(wait_for_result(&mut analysis));
+
+        assert_eq!("greedy", analysis.stop().name());
+        Ok(())
+    }
+
+    #[test]
+    fn test_analysis_with_any_bot() -> anyhow::Result<()> {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut analysis: Analysis = Analysis::start(&chess_game, bot::from_name("greedy")?);
+        assert!(wait_for_result(&mut analysis));
+
+        // The same bot goes on with the next position
+        chess_game.try_move(Position::from_notation("d2"), Position::from_notation("d4"));
+        analysis.follow(&chess_game);
+
+        assert!(wait_for_result(&mut analysis));
+        let (from, to) = analysis.result().and_then(|result| result.best_move()).expect("A best move");
+        assert!(chess_game.legal_move(from, to).is_some());
+        Ok(())
+    }
+}
@@ -1,18 +1,24 @@
 use crossterm::event::MouseEvent;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum CursorEvent {
     Event(MouseEvent),
     None,
     Stop,
-}
-
-impl CursorEvent {
-    pub(super) fn position(&self) -> Option<(u16, u16)> {
-        match self {
-            CursorEvent::Event(mouse_event) => Some((mouse_event.row, mouse_event.column)),
-            CursorEvent::None => None,
-            CursorEvent::Stop => None,
-        }
-    }
+    ToggleEvaluation,
+    NextLevel,
+    ToggleAnalysis,
+    ToggleTactics,
+    Hint,
+    Save,
+    Review,
+    // Left and right as well, on the board
+    Previous,
+    Next,
+    Up,
+    Down,
+    Select,
+    Cancel,
+    Undo,
+    Quit,
 }
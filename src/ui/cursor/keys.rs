@@ -0,0 +1,219 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::KeyCode;
+
+use super::cursor_event::CursorEvent;
+
+/// Read when the terminal starts, from the working directory like the
+/// saved game
+pub(crate) const KEYS_PATH: &str = "chessterm.keys";
+
+// The actions as they are named in the keys file
+const ACTIONS: [(&str, CursorEvent); 15] = [
+    ("up", CursorEvent::Up),
+    ("down", CursorEvent::Down),
+    ("left", CursorEvent::Previous),
+    ("right", CursorEvent::Next),
+    ("select", CursorEvent::Select),
+    ("cancel", CursorEvent::Cancel),
+    ("undo", CursorEvent::Undo),
+    ("quit", CursorEvent::Quit),
+    ("evaluation", CursorEvent::ToggleEvaluation),
+    ("level", CursorEvent::NextLevel),
+    ("analysis", CursorEvent::ToggleAnalysis),
+    ("tactics", CursorEvent::ToggleTactics),
+    ("hint", CursorEvent::Hint),
+    ("save", CursorEvent::Save),
+    ("review", CursorEvent::Review),
+];
+
+/// The event of every key bound to an action. The keys file changes them
+/// with lines like `level = o n`, `#` starting a comment
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Keys {
+    bindings: Vec<(KeyCode, CursorEvent)>,
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (KeyCode::Up, CursorEvent::Up),
+                (KeyCode::Char('k'), CursorEvent::Up),
+                (KeyCode::Down, CursorEvent::Down),
+                (KeyCode::Char('j'), CursorEvent::Down),
+                (KeyCode::Left, CursorEvent::Previous),
+                (KeyCode::Char('h'), CursorEvent::Previous),
+                (KeyCode::Right, CursorEvent::Next),
+                (KeyCode::Char('l'), CursorEvent::Next),
+                (KeyCode::Enter, CursorEvent::Select),
+                (KeyCode::Char(' '), CursorEvent::Select),
+                (KeyCode::Esc, CursorEvent::Cancel),
+                (KeyCode::Char('u'), CursorEvent::Undo),
+                (KeyCode::Char('q'), CursorEvent::Quit),
+                (KeyCode::Char('e'), CursorEvent::ToggleEvaluation),
+                (KeyCode::Char('o'), CursorEvent::NextLevel),
+                (KeyCode::Char('a'), CursorEvent::ToggleAnalysis),
+                (KeyCode::Char('t'), CursorEvent::ToggleTactics),
+                (KeyCode::Char('?'), CursorEvent::Hint),
+                (KeyCode::Char('s'), CursorEvent::Save),
+                (KeyCode::Char('r'), CursorEvent::Review),
+            ],
+        }
+    }
+}
+
+impl Keys {
+    /// The default keys changed by the file at `path`, if there is one
+    pub(crate) fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("Could not read the keys in {path}")),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("Could not read {path}")),
+        }
+    }
+
+    /// The default keys, each line of `text` replacing the keys of its
+    /// action. A key given to another action leaves the one it had
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let mut keys: Self = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line: &str = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((action, names)) = line.split_once('=') else {
+                bail!("Line {}: expected \"action = key...\"", number + 1);
+            };
+            let Some((_, event)) = ACTIONS.iter().find(|(name, _)| *name == action.trim()) else {
+                bail!("Line {}: unknown action \"{}\", expected one of {}", number + 1, action.trim(), ACTIONS.map(|(name, _)| name).join(", "));
+            };
+            let codes: Vec<KeyCode> = names
+                .split_whitespace()
+                .map(parse_key)
+                .collect::<Result<_>>()
+                .with_context(|| format!("Line {}", number + 1))?;
+            if codes.is_empty() {
+                bail!("Line {}: no key for \"{}\"", number + 1, action.trim());
+            }
+
+            keys.bindings.retain(|(code, bound)| bound != event && !codes.contains(code));
+            keys.bindings.extend(codes.into_iter().map(|code| (code, *event)));
+        }
+
+        Ok(keys)
+    }
+
+    /// The event of `code`, None when it is bound to nothing
+    pub(crate) fn event(&self, code: KeyCode) -> CursorEvent {
+        self
+            .bindings
+            .iter()
+            .find(|(bound, _)| *bound == code)
+            .map_or(CursorEvent::None, |(_, event)| *event)
+    }
+
+    /// The first key bound to `event`, to tell it to the player
+    pub(crate) fn name(&self, event: &CursorEvent) -> String {
+        self
+            .bindings
+            .iter()
+            .find(|(_, bound)| bound == event)
+            .map_or_else(|| "no key".to_string(), |(code, _)| key_name(*code))
+    }
+}
+
+/// A character, or a special key like "Enter" in any case
+fn parse_key(name: &str) -> Result<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    Ok(match name.to_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        _ => bail!("Unknown key \"{name}\""),
+    })
+}
+
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        code => format!("{code:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::ui::cursor::cursor_event::CursorEvent;
+
+    use super::Keys;
+
+    #[rstest]
+    #[case(KeyCode::Up, CursorEvent::Up)]
+    #[case(KeyCode::Char('h'), CursorEvent::Previous)]
+    #[case(KeyCode::Char(' '), CursorEvent::Select)]
+    #[case(KeyCode::Esc, CursorEvent::Cancel)]
+    #[case(KeyCode::Char('o'), CursorEvent::NextLevel)]
+    #[case(KeyCode::Char('x'), CursorEvent::None)]
+    fn test_default_keys(
+        #[case] code: KeyCode,
+        #[case] expected: CursorEvent,
+    ) {
+        assert_eq!(expected, Keys::default().event(code));
+    }
+
+    #[test]
+    fn test_parse_keys() -> anyhow::Result<()> {
+        let keys: Keys = Keys::parse("# Vim keys only\nleft = h\nlevel = L n  # stronger\n\nquit = Esc\n")?;
+
+        assert_eq!(CursorEvent::None, keys.event(KeyCode::Left));
+        assert_eq!(CursorEvent::Previous, keys.event(KeyCode::Char('h')));
+        assert_eq!(CursorEvent::NextLevel, keys.event(KeyCode::Char('n')));
+        assert_eq!(CursorEvent::None, keys.event(KeyCode::Char('o')));
+        // Esc left the cancel action
+        assert_eq!(CursorEvent::Quit, keys.event(KeyCode::Esc));
+        assert_eq!(CursorEvent::None, keys.event(KeyCode::Char('q')));
+        assert_eq!("L", keys.name(&CursorEvent::NextLevel));
+        assert_eq!("no key", keys.name(&CursorEvent::Cancel));
+        assert_eq!("Enter", keys.name(&CursorEvent::Select));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("jump = j", "Line 1: unknown action \"jump\"")]
+    #[case("\nundo = F1", "Line 2")]
+    #[case("undo =", "Line 1: no key for \"undo\"")]
+    #[case("undo u", "Line 1: expected \"action = key...\"")]
+    fn test_parse_errors(
+        #[case] text: &str,
+        #[case] expected: &str,
+    ) {
+        let error: String = Keys::parse(text).unwrap_err().to_string();
+
+        assert!(error.starts_with(expected), "{error}");
+    }
+
+    #[test]
+    fn test_missing_file() -> anyhow::Result<()> {
+        assert_eq!(Keys::default(), Keys::load("/nonexistent/chessterm.keys")?);
+        Ok(())
+    }
+}
@@ -1,28 +1,44 @@
 use anyhow::{Error, Result};
-use crossterm::event::{read, Event, MouseEventKind};
+use crossterm::event::{poll, read, Event, MouseEventKind};
 use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use cursor_event::CursorEvent;
-use std::iter::{self, RepeatWith};
+use keys::{Keys, KEYS_PATH};
+use std::time::Duration;
 
+use crate::game::board::color::Color;
 use crate::game::board::position::Position;
 use crate::game::board::{COLUMNS, ROWS};
 use crate::game::ChessEngine;
 
-use super::drawer::SQUARE_SIZE;
+use super::drawer::{draw_message, SQUARE_SIZE};
 
 pub(crate) mod cursor_event;
+pub(crate) mod keys;
 
 pub(crate) struct Cursor {
     event: CursorEvent,
-    event_iterator: RepeatWith<fn() -> CursorEvent>,
+    keys: Keys,
+    show_evaluation: bool,
+    // The square the next move is played from
+    selected: Option<Position>,
+    // The square the keyboard moves on the board, shown once it is used
+    focus: Option<Position>,
 }
 
 impl Cursor {
-    pub(crate) fn new() -> Self {
+    /// With the keys of the keys file, when there is one
+    pub(crate) fn new() -> Result<Self> {
+        Ok(Self::with_keys(Keys::load(KEYS_PATH)?))
+    }
+
+    fn with_keys(keys: Keys) -> Self {
         Self {
             event: CursorEvent::None,
-            event_iterator: Self::event_iter(),
+            keys,
+            show_evaluation: false,
+            selected: None,
+            focus: None,
         }
     }
 
@@ -30,27 +46,107 @@ impl Cursor {
         &self.event
     }
 
+    /// Forgets the last event, once a move that is not the player's was
+    /// played, so that the screen doesn't act on it again
+    pub(crate) fn clear_event(&mut self) {
+        self.event = CursorEvent::None;
+    }
+
+    pub(crate) const fn show_evaluation(&self) -> bool {
+        self.show_evaluation
+    }
+
+    /// The first key of `event`, for the messages
+    pub(crate) fn key(&self, event: &CursorEvent) -> String {
+        self.keys.name(event)
+    }
+
     pub(crate) fn next_event(&mut self, chess_game: &mut ChessEngine) {
-        if let Some(event) = self.event_iterator.next() {
-            let current_position = Self::to_board_position(&self.event);
-            let new_position = Self::to_board_position(&event);
-
-            if chess_game.try_move(current_position, new_position) {
-                self.event = CursorEvent::None;
-            } else {
-                self.event = event;
+        let event: CursorEvent = self.read_event();
+        self.handle(event, chess_game);
+    }
+
+    /// The next event as it is, for the screens that don't play moves
+    pub(crate) fn wait_event(&mut self) -> CursorEvent {
+        self.read_event()
+    }
+
+    /// Whether an event is waiting, after `timeout` at most: `next_event`
+    /// won't block then
+    pub(crate) fn has_event(timeout: Duration) -> Result<bool> {
+        poll(timeout).map_err(Error::from)
+    }
+
+    pub(crate) fn selected(&self) -> Option<Position> {
+        self.selected
+    }
+
+    pub(crate) fn focus(&self) -> Option<Position> {
+        self.focus
+    }
+
+    /// Plays the move of a click or of the keyboard, moves the focus, or
+    /// keeps the event for the screen to act on
+    fn handle(&mut self, event: CursorEvent, chess_game: &mut ChessEngine) {
+        self.event = event;
+        match event {
+            CursorEvent::Event(mouse_event) => {
+                self.focus = None;
+                self.pick(chess_game, Self::to_board_position(mouse_event.row, mouse_event.column));
             }
-        } else {
-            self.event = CursorEvent::None;
+            CursorEvent::Up | CursorEvent::Down | CursorEvent::Previous | CursorEvent::Next => {
+                self.focus = Some(match self.focus {
+                    Some(focus) => Self::step(focus, event),
+                    // The first press shows the focus
+                    None => self.selected.unwrap_or_else(|| Self::first_focus(chess_game)),
+                });
+            }
+            CursorEvent::Select => match self.focus {
+                Some(focus) => self.pick(chess_game, Some(focus)),
+                None => self.focus = Some(Self::first_focus(chess_game)),
+            },
+            CursorEvent::Cancel | CursorEvent::Undo => self.selected = None,
+            CursorEvent::ToggleEvaluation => self.show_evaluation = !self.show_evaluation,
+            CursorEvent::Quit => {
+                draw_message(&format!("Press {} again to leave, any other key to stay", self.key(&CursorEvent::Quit)));
+                self.event = match self.read_event() {
+                    CursorEvent::Quit => CursorEvent::Stop,
+                    _ => CursorEvent::None,
+                };
+                draw_message("");
+            }
+            _ => {}
         }
     }
 
-    pub(crate) fn selected(&self) -> Option<Position> {
-        Self::to_board_position(&self.event)
+    /// Moves from the selected square to `position` when it can, selects
+    /// `position` otherwise
+    fn pick(&mut self, chess_game: &mut ChessEngine, position: Option<Position>) {
+        self.selected = if chess_game.try_move(self.selected, position) { None } else { position };
+    }
+
+    /// In front of the king of the player to move
+    fn first_focus(chess_game: &ChessEngine) -> Position {
+        match chess_game.current_player() {
+            Color::Black => (1usize, 4usize).into(),
+            _ => (ROWS - 2, 4usize).into(),
+        }
     }
 
-    fn to_board_position(cursor_event: &CursorEvent) -> Option<Position> {
-        let (row, column) = cursor_event.position()?;
+    /// One square towards the direction of `event`, staying on the board
+    fn step(focus: Position, event: CursorEvent) -> Position {
+        let (row, column): (usize, usize) = (focus.row(), focus.column());
+
+        match event {
+            CursorEvent::Up => (row.saturating_sub(1), column),
+            CursorEvent::Down => ((row + 1).min(ROWS - 1), column),
+            CursorEvent::Previous => (row, column.saturating_sub(1)),
+            CursorEvent::Next => (row, (column + 1).min(COLUMNS - 1)),
+            _ => (row, column),
+        }.into()
+    }
+
+    fn to_board_position(row: u16, column: u16) -> Option<Position> {
         let chess_row: usize = row as usize / SQUARE_SIZE;
         let chess_column: usize = column as usize / SQUARE_SIZE / 2;
         if chess_row > ROWS || chess_column > COLUMNS {
@@ -60,20 +156,20 @@ impl Cursor {
         Some((chess_row, chess_column).into())
     }
 
-    fn event_iter() -> RepeatWith<fn() -> CursorEvent> {
-        iter::repeat_with(|| {
-            loop {
-                let new_event: Result<Event> = read().map_err(Error::from);
+    /// The next click or bound key, the keys bound to nothing giving
+    /// `CursorEvent::None`
+    fn read_event(&self) -> CursorEvent {
+        loop {
+            let new_event: Result<Event> = read().map_err(Error::from);
 
-                if let Ok(Event::Mouse(event)) = new_event {
-                    if let MouseEventKind::Down(_) = event.kind {
-                        return CursorEvent::Event(event)
-                    }
-                } else if let Ok(Event::Key(_)) = new_event {
-                    return CursorEvent::Stop;
+            if let Ok(Event::Mouse(event)) = new_event {
+                if let MouseEventKind::Down(_) = event.kind {
+                    return CursorEvent::Event(event)
                 }
+            } else if let Ok(Event::Key(event)) = new_event {
+                return self.keys.event(event.code);
             }
-        })
+        }
     }
 
     pub(crate) fn start() -> Result<()> {
@@ -92,3 +188,73 @@ impl Cursor {
         disable_raw_mode().map_err(Error::from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::game::ChessEngine;
+    use crate::game::board::position::Position;
+
+    use super::cursor_event::CursorEvent;
+    use super::keys::Keys;
+    use super::Cursor;
+
+    #[test]
+    fn test_keyboard_move() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut cursor: Cursor = Cursor::with_keys(Keys::default());
+
+        // Shown on e2, picked, then dropped on e4
+        for event in [CursorEvent::Up, CursorEvent::Select, CursorEvent::Up, CursorEvent::Up] {
+            cursor.handle(event, &mut chess_game);
+        }
+        assert_eq!(Some(Position::from((6usize, 4usize))), cursor.selected());
+        assert_eq!(Some(Position::from((4usize, 4usize))), cursor.focus());
+        cursor.handle(CursorEvent::Select, &mut chess_game);
+
+        assert_eq!(vec![(Position::from((6usize, 4usize)), Position::from((4usize, 4usize)))], chess_game.history());
+        assert_eq!(None, cursor.selected());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut cursor: Cursor = Cursor::with_keys(Keys::default());
+
+        for event in [CursorEvent::Select, CursorEvent::Select, CursorEvent::Cancel, CursorEvent::Up, CursorEvent::Select] {
+            cursor.handle(event, &mut chess_game);
+        }
+
+        assert!(chess_game.history().is_empty());
+        assert_eq!(Some(Position::from((5usize, 4usize))), cursor.selected());
+    }
+
+    #[test]
+    fn test_clear_event() {
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        let mut cursor: Cursor = Cursor::with_keys(Keys::default());
+
+        cursor.handle(CursorEvent::Undo, &mut chess_game);
+        assert_eq!(&CursorEvent::Undo, cursor.event());
+        cursor.clear_event();
+
+        assert_eq!(&CursorEvent::None, cursor.event());
+    }
+
+    #[rstest]
+    #[case((0, 0), CursorEvent::Up, (0, 0))]
+    #[case((0, 0), CursorEvent::Previous, (0, 0))]
+    #[case((7, 7), CursorEvent::Down, (7, 7))]
+    #[case((7, 7), CursorEvent::Next, (7, 7))]
+    #[case((3, 3), CursorEvent::Down, (4, 3))]
+    #[case((3, 3), CursorEvent::Previous, (3, 2))]
+    fn test_step(
+        #[case] focus: (usize, usize),
+        #[case] event: CursorEvent,
+        #[case] expected: (usize, usize),
+    ) {
+        assert_eq!(Position::from(expected), Cursor::step(focus.into(), event));
+    }
+}
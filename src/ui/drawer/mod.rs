@@ -2,13 +2,19 @@ use std::collections::HashSet;
 
 use header::{HeaderColumn, HeaderRow};
 
+use crate::bot::evaluation::trace::EvalTrace;
+use crate::bot::evaluation::{evaluate, Term};
+use crate::bot::score::Score;
+use crate::bot::search::SearchResult;
 use crate::game::ChessEngine;
 use crate::game::board::color::Color;
 use crate::game::board::position::Position;
 use crate::game::board::square::Square;
 use crate::game::board::{COLUMNS, ROWS};
+use crate::game::san::line_to_san;
 
 use super::cursor::Cursor;
+use super::cursor::cursor_event::CursorEvent;
 
 mod header;
 mod pieces;
@@ -17,6 +23,8 @@ mod square;
 const ATTACKED_COLOR: u8 = 42u8;
 const CHECKED_COLOR: u8 = 196u8;
 const CURSOR_COLOR: u8 = 69u8;
+const FOCUS_COLOR: u8 = 99u8;
+const HINT_COLOR: u8 = 214u8;
 const PIECE_BLACK: u8 = 235u8;
 const PIECE_WHITE: u8 = 240u8;
 const SQUARE_BLACK: u8 = 0u8;
@@ -24,6 +32,21 @@ const SQUARE_WHITE: u8 = 255u8;
 const HEADER_BACKGROUND: u8 = 232u8;
 const HEADER_FOREGROUND: u8 = 255u8;
 pub(super) const SQUARE_SIZE: usize = 20usize;
+// The evaluation bar of the analysis is drawn on the right of the row
+// headers, then the information panel
+const EVAL_BAR_COLUMN: usize = (COLUMNS + 1) * SQUARE_SIZE * 2 + 2;
+const EVAL_BAR_WIDTH: usize = 4usize;
+const EVAL_BAR_HEIGHT: usize = ROWS * SQUARE_SIZE;
+const INFO_COLUMN: usize = EVAL_BAR_COLUMN + EVAL_BAR_WIDTH + 2;
+const INFO_WIDTH: usize = 70usize;
+// Between the evaluation and its trace
+const STATUS_ROW: usize = 1;
+// The evaluation trace has one line per term, plus a header and 3 summary lines
+const TRACE_LINES: usize = Term::values().len() + 4;
+const MESSAGE_ROW: usize = TRACE_LINES + 3;
+// The analysis or the review of the game
+const PANEL_ROW: usize = MESSAGE_ROW + 2;
+const PANEL_LINES: usize = 8;
 
 const CLEAN: &str = "\x1b[2J";
 const RESET: &str = "\x1b[0m";
@@ -87,9 +110,15 @@ pub(crate) fn draw_headers() {
     print!("{}", output);
 }
 
-pub(crate) fn draw_game(chess_game: &ChessEngine, cursor: &Cursor) {
+/// The board, with the squares of `highlight` highlighted: a hint, or the
+/// move reviewed
+pub(crate) fn draw_game(chess_game: &ChessEngine, cursor: &Cursor, highlight: Option<(Position, Position)>) {
     let possible_moves: Option<HashSet<Position>> = chess_game.possible_positions(cursor.selected());
-    let checked_king: Option<Position> = chess_game.checked_king();
+    // The checked king and the pieces giving check
+    let checked: Vec<Position> = chess_game
+        .checked_king()
+        .map(|king| [vec![king], chess_game.checkers()].concat())
+        .unwrap_or_default();
     let mut position: Position;
 
     for i in 0..ROWS {
@@ -100,21 +129,122 @@ pub(crate) fn draw_game(chess_game: &ChessEngine, cursor: &Cursor) {
                 panic!("The square ({i}, {j}) should exist");
             };
 
-            draw_square(possible_moves.as_ref(), checked_king, cursor, square, position);
+            let hinted: bool = highlight.is_some_and(|(from, to)| position == from || position == to);
+            draw_square(possible_moves.as_ref(), &checked, hinted, cursor, square, position);
         }
     }
 
     draw_headers();
+    draw_evaluation(chess_game, cursor);
 }
 
-fn colors(possible_moves: Option<&HashSet<Position>>, checked_king: Option<Position>, cursor: &Cursor, square: &Square, position: Position) -> (u8, u8) {
+fn draw_evaluation(chess_game: &ChessEngine, cursor: &Cursor) {
+    let details: String = cursor.key(&CursorEvent::ToggleEvaluation);
+    draw_text(0, INFO_COLUMN, &format!("Evaluation: {} (press {details} for details)", evaluate(chess_game)));
+
+    let lines: Vec<String> = if cursor.show_evaluation() {
+        EvalTrace::new(chess_game.board()).lines()
+    } else {
+        Vec::new()
+    };
+
+    for i in 0..TRACE_LINES {
+        draw_text(i + 2, INFO_COLUMN, lines.get(i).map_or("", String::as_str));
+    }
+}
+
+/// The evaluation bar and the best line of the analysis, nothing but
+/// blanks without an analysis
+pub(crate) fn draw_analysis(analysis: Option<(&ChessEngine, Option<&SearchResult>)>, cursor: &Cursor) {
+    let stop: String = cursor.key(&CursorEvent::ToggleAnalysis);
+    let mut lines: Vec<String> = Vec::new();
+    // The bar is half white until the first result
+    let mut white_share: Option<f64> = analysis.map(|_| 0.5);
+
+    if let Some((chess_game, result)) = analysis {
+        match result {
+            Some(result) => {
+                let score: Score = result.score().relative(chess_game.current_player());
+                white_share = Some(score.win_probability());
+                lines.push(format!("Analysis: {score} at depth {} (press {stop} to stop)", result.depth()));
+                lines.push(line_to_san(chess_game, result.pv()));
+            }
+            None => lines.push(format!("Analysis: thinking... (press {stop} to stop)")),
+        }
+    }
+
+    draw_panel(white_share, &lines);
+}
+
+/// The evaluation bar, white's share at the bottom, and `lines` under the
+/// message, wrapped. Blanks the bar without a share
+pub(crate) fn draw_panel(white_share: Option<f64>, lines: &[String]) {
+    let white_rows: usize = white_share.map_or(0, |share| (share * EVAL_BAR_HEIGHT as f64).round() as usize);
+    let mut output: String = String::new();
+    for row in 0..EVAL_BAR_HEIGHT {
+        output += &goto(row, EVAL_BAR_COLUMN);
+        if white_share.is_none() {
+            output += RESET;
+        } else if row < EVAL_BAR_HEIGHT - white_rows {
+            output += &terminal_color(SQUARE_BLACK);
+        } else {
+            output += &terminal_color(SQUARE_WHITE);
+        }
+        output += &" ".repeat(EVAL_BAR_WIDTH);
+        output += RESET;
+    }
+    print!("{output}");
+
+    let lines: Vec<String> = lines.iter().flat_map(|line| wrap(line, INFO_WIDTH)).collect();
+    for i in 0..PANEL_LINES {
+        draw_text(PANEL_ROW + i, INFO_COLUMN, lines.get(i).map_or("", String::as_str));
+    }
+}
+
+/// Splits `text` into lines of `width` characters at most, between words
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// A line of text under the information panel, replacing the previous one
+pub(crate) fn draw_message(text: &str) {
+    draw_text(MESSAGE_ROW, INFO_COLUMN, text);
+}
+
+/// A line under the evaluation, blank when `text` is, cut to the panel
+pub(crate) fn draw_status(text: &str) {
+    let text: String = text.chars().take(INFO_WIDTH).collect();
+    draw_text(STATUS_ROW, INFO_COLUMN, &text);
+}
+
+fn draw_text(row: usize, column: usize, text: &str) {
+    print!("{}{RESET}{text:<INFO_WIDTH$}", goto(row, column));
+}
+
+fn colors(possible_moves: Option<&HashSet<Position>>, checked: &[Position], hinted: bool, cursor: &Cursor, square: &Square, position: Position) -> (u8, u8) {
     let possible_moves = possible_moves.as_ref();
     let background_color: u8;
 
     if cursor.selected() == Some(position) {
         background_color = CURSOR_COLOR;
-    } else if checked_king == Some(position) {
+    } else if cursor.focus() == Some(position) {
+        background_color = FOCUS_COLOR;
+    } else if checked.contains(&position) {
         background_color = CHECKED_COLOR;
+    } else if hinted {
+        background_color = HINT_COLOR;
     } else if possible_moves.as_ref().is_some_and(|moves| moves.contains(&position)) {
         background_color = ATTACKED_COLOR;
     } else {
@@ -125,11 +255,11 @@ fn colors(possible_moves: Option<&HashSet<Position>>, checked_king: Option<Posit
     (background_color, piece_color)
 }
 
-fn draw_square(possible_moves: Option<&HashSet<Position>>, checked_king: Option<Position>, cursor: &Cursor, square: &Square, position: Position) {
+fn draw_square(possible_moves: Option<&HashSet<Position>>, checked: &[Position], hinted: bool, cursor: &Cursor, square: &Square, position: Position) {
     let mut output: String = String::new();
     let (row, column): (usize, usize) = (position.row()*SQUARE_SIZE, position.column()*SQUARE_SIZE*2);
     let drawing = square.drawing();
-    let (background_color, piece_color) = colors(possible_moves, checked_king, cursor, square, position);
+    let (background_color, piece_color) = colors(possible_moves, checked, hinted, cursor, square, position);
     let background_color: String = terminal_color(background_color);
     let piece_color: String = terminal_color(piece_color);
 
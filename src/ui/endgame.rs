@@ -0,0 +1,95 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::bot::Bot;
+use crate::bot::alpha_beta_bot::AlphaBetaBot;
+use crate::bot::rng::Rng;
+use crate::bot::search::SearchLimits;
+use crate::endgame::{Endgame, Goal, Progress};
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::tablebase::Tablebase;
+
+use super::Opponent;
+use super::cursor::Cursor;
+use super::cursor::cursor_event::CursorEvent;
+use super::drawer::{draw_game, draw_message, draw_panel};
+use super::Terminal;
+
+// The endgames are small, the bot defends well enough in a second
+const OPPONENT_MOVETIME: Duration = Duration::from_secs(1);
+
+/// Practices `endgame` against the bot, one position after the other until
+/// the player leaves. The player has the side to move. With a tablebase,
+/// the bot defends perfectly and the distance to mate is shown
+pub(crate) fn run(endgame: Endgame, mut rng: Rng, tablebase: Option<Arc<Tablebase>>) -> Result<()> {
+    let mut cursor: Cursor = Cursor::new()?;
+    let mut achieved: usize = 0;
+    let mut tried: usize = 0;
+
+    let _terminal: Terminal = Terminal::start()?;
+    loop {
+        let start: ChessEngine = endgame.position(&mut rng);
+        let Some(progress) = practice(endgame, &start, &mut cursor, tablebase.as_ref())? else {
+            break;
+        };
+        tried += 1;
+
+        let outcome: String = match progress {
+            Progress::Achieved => {
+                achieved += 1;
+                "Goal achieved!".to_string()
+            }
+            Progress::Failed(reason) => format!("Goal failed: {reason}"),
+            Progress::Playing => unreachable!("The practice goes on while playing"),
+        };
+        draw_panel(None, &[format!("{endgame}: {}", endgame.goal()), outcome, format!("Achieved {achieved} of {tried}")]);
+        draw_message(&format!("Press {} for another position, any other key to leave", cursor.key(&CursorEvent::Next)));
+        if cursor.wait_event() != CursorEvent::Next {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays from `start` until the goal is achieved or failed, None when the
+/// player leaves
+fn practice(endgame: Endgame, start: &ChessEngine, cursor: &mut Cursor, tablebase: Option<&Arc<Tablebase>>) -> Result<Option<Progress>> {
+    let player: Color = start.current_player();
+    let goal: Goal = endgame.goal();
+    let mut bot: AlphaBetaBot = AlphaBetaBot::new();
+    if let Some(tablebase) = tablebase {
+        bot.set_tablebase(Arc::clone(tablebase));
+    }
+    let mut opponent: Opponent = Opponent::new(Box::new(bot), player.other(), SearchLimits::movetime(OPPONENT_MOVETIME));
+    let mut chess_game: ChessEngine = start.clone();
+
+    loop {
+        let plies: usize = chess_game.history().len() - start.history().len();
+        let mut lines: Vec<String> = vec![format!("{endgame}: {goal}"), format!("Plies played: {plies}")];
+        lines.extend(tablebase.and_then(|tablebase| tablebase.status(&chess_game)).map(|status| format!("Tablebase: {status}")));
+        draw_game(&chess_game, cursor, None);
+        draw_panel(None, &lines);
+
+        let progress: Progress = goal.progress(start, &chess_game, player);
+        if progress != Progress::Playing {
+            return Ok(Some(progress));
+        }
+
+        if chess_game.current_player() == player {
+            draw_message(&format!("Your move, {} to leave", cursor.key(&CursorEvent::Quit)));
+            cursor.next_event(&mut chess_game);
+            if CursorEvent::Stop.eq(cursor.event()) {
+                return Ok(None);
+            }
+        } else {
+            draw_message("The bot is thinking...");
+            let _ = io::stdout().flush();
+            opponent.play(&mut chess_game)?;
+        }
+    }
+}
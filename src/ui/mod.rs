@@ -1,2 +1,452 @@
+use std::fs;
+use std::panic;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use analysis::Analysis;
+use cursor::Cursor;
+use cursor::cursor_event::CursorEvent;
+use drawer::{clean_screen, draw_analysis, draw_game, draw_message, draw_panel, draw_status};
+
+use crate::bot::{self, Bot};
+use crate::bot::difficulty::{Difficulty, LEVELS};
+use crate::bot::search::SearchLimits;
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::eco::classify;
+use crate::game::pgn::Pgn;
+use crate::game::san::to_san;
+use crate::tablebase::Tablebase;
+use crate::tactics::{find_motifs, motifs_after, Motif};
+
+mod analysis;
 pub(super) mod drawer;
 pub(super) mod cursor;
+pub(crate) mod endgame;
+pub(crate) mod puzzle;
+pub(crate) mod repertoire;
+mod review;
+
+// Thinking time of the opponents chosen from the terminal
+const LEVEL_MOVETIME: Duration = Duration::from_secs(5);
+// How often the analysis is redrawn while waiting for the player
+const ANALYSIS_REFRESH: Duration = Duration::from_millis(200);
+// A hint is a short search, the player shouldn't wait for it
+const HINT_DEPTH: i16 = 4;
+const HINT_MOVETIME: Duration = Duration::from_secs(1);
+// Where the game is saved, in the working directory
+const SAVE_PATH: &str = "chessterm.pgn";
+/// The bot of the analysis, the hints and the review unless another is
+/// named
+pub(crate) const DEFAULT_ENGINE: &str = "alpha-beta";
+
+/// A bot playing one side of the game shown in the terminal
+pub(crate) struct Opponent {
+    bot: Box<dyn Bot>,
+    color: Color,
+    limits: SearchLimits,
+    // None when the bot was not chosen by level
+    level: Option<Difficulty>,
+}
+
+impl Opponent {
+    pub(crate) fn new(bot: Box<dyn Bot>, color: Color, limits: SearchLimits) -> Self {
+        Self {
+            level: Difficulty::from_name(bot.name()),
+            bot,
+            color,
+            limits,
+        }
+    }
+
+    fn with_level(level: Difficulty, color: Color) -> Self {
+        Self {
+            bot: level.bot(),
+            color,
+            limits: SearchLimits::movetime(LEVEL_MOVETIME),
+            level: Some(level),
+        }
+    }
+
+    /// Switches to the next level, the weakest one first
+    fn next_level(&mut self) {
+        let level: Difficulty = self.level.map_or(LEVELS[0], |level| level.next());
+        self.bot = level.bot();
+        self.level = Some(level);
+    }
+
+    fn play(&mut self, chess_game: &mut ChessEngine) -> Result<()> {
+        match self.bot.choose_move(chess_game, self.limits.clone()).best_move() {
+            Some((from, to)) => {
+                chess_game.try_move(Some(from), Some(to));
+                Ok(())
+            }
+            None => bail!("{} gave up: {}", self.bot.name(), self.bot.failure().unwrap_or("no move found")),
+        }
+    }
+}
+
+/// The bot of the analysis, the hints and the review, started the first
+/// time it is needed and kept for the game: an external engine is a
+/// process to launch
+struct Engine<'a> {
+    name: &'a str,
+    tablebase: Option<Arc<Tablebase>>,
+    // None until it is needed, and while the analysis has it
+    bot: Option<Box<dyn Bot>>,
+}
+
+impl<'a> Engine<'a> {
+    const fn new(name: &'a str, tablebase: Option<Arc<Tablebase>>) -> Self {
+        Self { name, tablebase, bot: None }
+    }
+
+    /// Stops the analysis, if there is one, and keeps its bot
+    fn stop_analysis(&mut self, analysis: &mut Option<Analysis>) {
+        if let Some(analysis) = analysis.take() {
+            self.bot = Some(analysis.stop());
+        }
+    }
+
+    /// The bot, taken from the analysis when it runs, started on the first
+    /// call. Given back with `put`
+    fn take(&mut self, analysis: &mut Option<Analysis>) -> Result<Box<dyn Bot>> {
+        self.stop_analysis(analysis);
+        match self.bot.take() {
+            Some(bot) => Ok(bot),
+            None => engine_bot(self.name, self.tablebase.as_ref()),
+        }
+    }
+
+    fn put(&mut self, bot: Box<dyn Bot>) {
+        self.bot = Some(bot);
+    }
+}
+
+/// Runs the game in the terminal until it ends or the player leaves. The
+/// tablebase, when given, tells the result of the endgames it knows. The
+/// bot named `engine` analyzes, gives the hints and reviews the game
+pub(crate) fn run(mut opponent: Option<Opponent>, tablebase: Option<Arc<Tablebase>>, engine: &str) -> Result<()> {
+    let mut chess_game: ChessEngine = ChessEngine::new();
+    let mut cursor: Cursor = Cursor::new()?;
+    let mut engine: Engine = Engine::new(engine, tablebase.clone());
+    let mut analysis: Option<Analysis> = None;
+    let mut hint: Option<(Position, Position)> = None;
+    let mut hints: usize = 0;
+    // The tactics take the place of the analysis under the board
+    let mut tactics: bool = false;
+
+    let _terminal: Terminal = Terminal::start()?;
+    draw_game(&chess_game, &cursor, hint);
+
+    loop {
+        let key: u64 = chess_game.key();
+        match opponent.as_mut() {
+            Some(bot) if bot.color == chess_game.current_player() => {
+                // Without its opponent, the player keeps playing both sides
+                if let Err(error) = bot.play(&mut chess_game) {
+                    draw_message(&error.to_string());
+                    opponent = None;
+                }
+                // The player's last key was already acted on
+                cursor.clear_event();
+            }
+            _ => {
+                while let Some(analysis) = analysis.as_mut() {
+                    if Cursor::has_event(ANALYSIS_REFRESH)? {
+                        break;
+                    }
+                    if analysis.update() {
+                        draw_analysis(Some((analysis.chess_game(), analysis.result())), &cursor);
+                    }
+                }
+                cursor.next_event(&mut chess_game);
+            }
+        }
+
+        // The first press brings an opponent for the side that is not
+        // playing, the next ones make it stronger
+        if CursorEvent::NextLevel.eq(cursor.event()) {
+            let opponent: &mut Opponent = match opponent.as_mut() {
+                Some(opponent) => {
+                    opponent.next_level();
+                    opponent
+                }
+                None => opponent.insert(Opponent::with_level(LEVELS[0], chess_game.current_player().other())),
+            };
+            if let Some(level) = opponent.level {
+                draw_message(&format!("Opponent: {level}, press {} to change", cursor.key(&CursorEvent::NextLevel)));
+            }
+        }
+        if CursorEvent::Undo.eq(cursor.event()) {
+            take_back(&mut chess_game, opponent.as_ref());
+        }
+        if key != chess_game.key() {
+            hint = None;
+        }
+        if CursorEvent::Hint.eq(cursor.event()) && !chess_game.is_end() {
+            let analyzing: bool = analysis.is_some();
+            match engine.take(&mut analysis) {
+                Ok(mut bot) => {
+                    hint = suggest(&chess_game, bot.as_mut());
+                    hints += 1;
+                    if let Some(san) = hint.and_then(|suggested| to_san(&chess_game, suggested)) {
+                        draw_message(&format!("Hint: {san} ({hints} used)"));
+                    }
+                    // The analysis goes on with the same bot
+                    if analyzing {
+                        analysis = Some(Analysis::start(&chess_game, bot));
+                    } else {
+                        engine.put(bot);
+                    }
+                }
+                Err(error) => draw_message(&error.to_string()),
+            }
+        }
+        if CursorEvent::Save.eq(cursor.event()) {
+            let message: String = match fs::write(SAVE_PATH, saved_game(&chess_game, opponent.as_ref(), hints).to_string()) {
+                Ok(()) => format!("Game saved to {SAVE_PATH}"),
+                Err(error) => format!("Could not save the game: {error}"),
+            };
+            draw_message(&message);
+        }
+        if CursorEvent::Review.eq(cursor.event()) {
+            show_review(&chess_game, &mut cursor, &mut engine, &mut analysis);
+        }
+        if CursorEvent::ToggleAnalysis.eq(cursor.event()) {
+            if analysis.is_some() {
+                engine.stop_analysis(&mut analysis);
+            } else {
+                analysis = engine
+                    .take(&mut analysis)
+                    .inspect_err(|error| draw_message(&error.to_string()))
+                    .ok()
+                    .map(|bot| Analysis::start(&chess_game, bot));
+            }
+            tactics = false;
+        }
+        if CursorEvent::ToggleTactics.eq(cursor.event()) {
+            tactics = !tactics;
+            engine.stop_analysis(&mut analysis);
+        }
+        // The analysis starts over after every move
+        if let Some(analysis) = analysis.as_mut() {
+            analysis.follow(&chess_game);
+            analysis.update();
+        }
+        draw_game(&chess_game, &cursor, hint);
+        // The tablebase takes over from the opening in the endgame
+        let status: Option<String> = tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase.status(&chess_game))
+            .map(|status| format!("Tablebase: {status}"))
+            .or_else(|| classify(&chess_game).map(|opening| opening.to_string()));
+        draw_status(&status.unwrap_or_default());
+        if tactics {
+            draw_panel(None, &explain_tactics(&chess_game, &cursor.key(&CursorEvent::ToggleTactics)));
+        } else {
+            draw_analysis(analysis.as_ref().map(|analysis| (analysis.chess_game(), analysis.result())), &cursor);
+        }
+
+        if CursorEvent::Stop.eq(cursor.event()) || chess_game.is_end() {
+            break;
+        }
+    }
+
+    if chess_game.is_end() {
+        draw_message(&format!("Game over, press {} to review it, any other key to leave", cursor.key(&CursorEvent::Review)));
+        if CursorEvent::Review == cursor.wait_event() {
+            show_review(&chess_game, &mut cursor, &mut engine, &mut analysis);
+        }
+    }
+
+    Ok(())
+}
+
+/// The terminal taken over by a screen, given back when dropped: on every
+/// way out of the screen, errors included
+pub(crate) struct Terminal;
+
+impl Terminal {
+    /// Restores the terminal before the message of a panic too
+    pub(crate) fn start() -> Result<Self> {
+        panic::set_hook(Box::new(|p| {
+            let _ = Cursor::stop();
+            panic!("{p}");
+        }));
+        clean_screen();
+
+        Cursor::start()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = Cursor::stop();
+    }
+}
+
+/// Takes back the last move, and the opponent's reply before it so that the
+/// player has the move again
+fn take_back(chess_game: &mut ChessEngine, opponent: Option<&Opponent>) {
+    if chess_game.history().is_empty() {
+        return;
+    }
+    chess_game.undo_move();
+    if opponent.is_some_and(|opponent| opponent.color == chess_game.current_player()) && !chess_game.history().is_empty() {
+        chess_game.undo_move();
+    }
+}
+
+/// The bot named `engine`, with the tablebase when there is one
+fn engine_bot(engine: &str, tablebase: Option<&Arc<Tablebase>>) -> Result<Box<dyn Bot>> {
+    let mut bot: Box<dyn Bot> = bot::from_name(engine)?;
+    if let Some(tablebase) = tablebase {
+        bot.set_tablebase(Arc::clone(tablebase));
+    }
+
+    Ok(bot)
+}
+
+/// Reviews the game with the bot of `engine`, which stops the analysis, or
+/// tells why it can't
+fn show_review(chess_game: &ChessEngine, cursor: &mut Cursor, engine: &mut Engine, analysis: &mut Option<Analysis>) {
+    match engine.take(analysis) {
+        Ok(mut bot) => {
+            review::show(chess_game, cursor, bot.as_mut());
+            engine.put(bot);
+        }
+        Err(error) => draw_message(&error.to_string()),
+    }
+}
+
+/// The move a short search of `bot` suggests to the player
+fn suggest(chess_game: &ChessEngine, bot: &mut dyn Bot) -> Option<(Position, Position)> {
+    bot
+        .choose_move(chess_game, SearchLimits::movetime(HINT_MOVETIME).with_depth(HINT_DEPTH))
+        .best_move()
+}
+
+/// The motifs the last move made, or the ones on the board before the
+/// first move, under a title telling the `hide` key
+fn explain_tactics(chess_game: &ChessEngine, hide: &str) -> Vec<String> {
+    let motifs: Vec<Motif> = match chess_game.history().last() {
+        Some(&played) => {
+            let mut before: ChessEngine = chess_game.clone();
+            before.undo_move();
+            motifs_after(&before, played).unwrap_or_default()
+        }
+        None => find_motifs(chess_game),
+    };
+
+    let mut lines: Vec<String> = vec![format!("Tactics (press {hide} to hide):")];
+    if motifs.is_empty() {
+        lines.push("nothing found".to_string());
+    }
+    lines.extend(motifs.iter().map(|motif| format!("{}: {motif}", motif.kind())));
+
+    lines
+}
+
+/// The game as saved, with the number of hints the player asked for
+fn saved_game(chess_game: &ChessEngine, opponent: Option<&Opponent>, hints: usize) -> Pgn {
+    let mut pgn: Pgn = Pgn::new(chess_game).with_tag("Hints", &hints.to_string());
+    if let Some(opponent) = opponent {
+        let side: &str = if opponent.color == Color::White { "White" } else { "Black" };
+        pgn = pgn.with_tag(side, opponent.bot.name());
+    }
+
+    pgn
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::bot::{self, Bot};
+    use crate::bot::alpha_beta_bot::AlphaBetaBot;
+    use crate::bot::search::SearchLimits;
+    use crate::game::ChessEngine;
+    use crate::game::board::color::Color;
+    use crate::game::board::position::Position;
+    use crate::game::pgn::Pgn;
+    use crate::protocol::play_moves;
+
+    use super::analysis::Analysis;
+    use super::{saved_game, suggest, take_back, Engine, Opponent};
+
+    #[test]
+    fn test_saved_game_counts_hints() -> anyhow::Result<()> {
+        let opponent: Opponent = Opponent::new(bot::from_name("random")?, Color::Black, SearchLimits::default());
+
+        let pgn: Pgn = saved_game(&ChessEngine::new(), Some(&opponent), 3);
+
+        assert_eq!(Some("3"), pgn.tag("Hints"));
+        assert_eq!(Some("random"), pgn.tag("Black"));
+        assert_eq!(Some("?"), pgn.tag("White"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_is_started_once() -> anyhow::Result<()> {
+        // The bot is known by the address of its box
+        let address = |bot: &dyn Bot| (bot as *const dyn Bot).cast::<()>();
+        let mut engine: Engine = Engine::new("greedy", None);
+        let bot: Box<dyn Bot> = engine.take(&mut None)?;
+        let started = address(bot.as_ref());
+        let mut analysis: Option<Analysis> = Some(Analysis::start(&ChessEngine::new(), bot));
+
+        // Back from the analysis, then from the engine
+        let bot: Box<dyn Bot> = engine.take(&mut analysis)?;
+        assert!(analysis.is_none());
+        assert_eq!(started, address(bot.as_ref()));
+        engine.put(bot);
+        assert_eq!(started, address(engine.take(&mut None)?.as_ref()));
+
+        assert!(Engine::new("no such bot", None).take(&mut None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_is_legal() {
+        let chess_game: ChessEngine = ChessEngine::new();
+
+        let (from, to): (Position, Position) = suggest(&chess_game, &mut AlphaBetaBot::new()).expect("A hint");
+
+        assert!(chess_game.legal_move(from, to).is_some());
+    }
+
+    #[test]
+    fn test_take_back() -> anyhow::Result<()> {
+        let opponent: Opponent = Opponent::new(bot::from_name("random")?, Color::Black, SearchLimits::default());
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        play_moves(&mut chess_game, &["e2e4", "e7e5", "g1f3"])?;
+
+        take_back(&mut chess_game, None);
+        assert_eq!(2, chess_game.history().len());
+        // The bot's reply goes with the player's move
+        take_back(&mut chess_game, Some(&opponent));
+        assert_eq!(0, chess_game.history().len());
+        take_back(&mut chess_game, Some(&opponent));
+        assert_eq!(0, chess_game.history().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_back_to_the_bot() -> anyhow::Result<()> {
+        let opponent: Opponent = Opponent::new(bot::from_name("random")?, Color::White, SearchLimits::default());
+        let mut chess_game: ChessEngine = ChessEngine::new();
+        play_moves(&mut chess_game, &["e2e4"])?;
+
+        // The bot's first move has no move of the player before it: the bot
+        // has the move again, and plays it without a new take back
+        take_back(&mut chess_game, Some(&opponent));
+        assert_eq!(0, chess_game.history().len());
+        assert_eq!(opponent.color, chess_game.current_player());
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::san::{line_to_san, to_san};
+use crate::puzzle::stats::PuzzleStats;
+use crate::puzzle::{Attempt, Puzzle, Verdict};
+
+use super::cursor::Cursor;
+use super::cursor::cursor_event::CursorEvent;
+use super::drawer::{draw_game, draw_message, draw_panel};
+use super::Terminal;
+
+// Leaves the player the time to see their move before the reply
+const REPLY_DELAY: Duration = Duration::from_millis(500);
+
+/// Plays `puzzles` in turn, until the last one or until the player leaves.
+/// Every outcome is added to the stats saved at `stats_path`
+pub(crate) fn run(puzzles: &[Puzzle], stats_path: &str) -> Result<()> {
+    let mut stats: PuzzleStats = PuzzleStats::load(stats_path)?;
+    let mut cursor: Cursor = Cursor::new()?;
+
+    let _terminal: Terminal = Terminal::start()?;
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        let Some((solved, outcome)) = solve(puzzle, &mut cursor, &info(puzzle, index, puzzles.len())) else {
+            break;
+        };
+        stats.record(solved);
+        stats.save(stats_path)?;
+
+        draw_panel(None, &[info(puzzle, index, puzzles.len()), outcome, stats.to_string()]);
+        draw_message(&format!("Press {} for the next puzzle, any other key to leave", cursor.key(&CursorEvent::Next)));
+        if cursor.wait_event() != CursorEvent::Next {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lets the player solve `puzzle`, returns whether they did without a hint
+/// and how it went, None when they left
+fn solve(puzzle: &Puzzle, cursor: &mut Cursor, info: &str) -> Option<(bool, String)> {
+    let mut attempt: Attempt = Attempt::new(puzzle);
+    let player: &str = if puzzle.chess_game().current_player() == Color::White { "White" } else { "Black" };
+    let mut message: String = format!("{player} to play, press {} for a hint", cursor.key(&CursorEvent::Hint));
+    let mut hint: Option<(Position, Position)> = None;
+    let mut hinted: bool = false;
+
+    loop {
+        draw_game(attempt.chess_game(), cursor, hint);
+        draw_panel(None, &[info.to_string()]);
+        draw_message(&message);
+
+        let mut chess_game: ChessEngine = attempt.chess_game().clone();
+        cursor.next_event(&mut chess_game);
+        if CursorEvent::Stop.eq(cursor.event()) {
+            return None;
+        }
+        if CursorEvent::Hint.eq(cursor.event()) {
+            hint = attempt.expected();
+            hinted = true;
+            if let Some(san) = hint.and_then(|hint| to_san(attempt.chess_game(), hint)) {
+                message = format!("Hint: {san}, the puzzle counts as failed");
+            }
+        }
+        if chess_game.history().len() == attempt.chess_game().history().len() {
+            continue;
+        }
+        let Some(&played) = chess_game.history().last() else {
+            continue;
+        };
+
+        let solution: String = line_to_san(attempt.chess_game(), attempt.remaining());
+        match attempt.play(played) {
+            Verdict::Correct => {
+                draw_game(attempt.chess_game(), cursor, None);
+                let _ = io::stdout().flush();
+                thread::sleep(REPLY_DELAY);
+                attempt.reply();
+                hint = None;
+                message = "Correct, keep going".to_string();
+            }
+            Verdict::Solved => {
+                draw_game(attempt.chess_game(), cursor, None);
+                let outcome: &str = if hinted { "Solved, with a hint" } else { "Solved!" };
+                return Some((!hinted, outcome.to_string()));
+            }
+            Verdict::Wrong => {
+                draw_game(attempt.chess_game(), cursor, None);
+                return Some((false, format!("Wrong, the solution was {solution}")));
+            }
+            Verdict::Illegal => {}
+        }
+    }
+}
+
+/// The puzzle's id, position in the list, rating and themes
+fn info(puzzle: &Puzzle, index: usize, count: usize) -> String {
+    let rating: String = puzzle.rating().map(|rating| format!(", rated {rating}")).unwrap_or_default();
+    let themes: String = if puzzle.themes().is_empty() { String::new() } else { format!(" ({})", puzzle.themes().join(", ")) };
+
+    format!("Puzzle {} ({}/{count}){rating}{themes}", puzzle.id(), index + 1)
+}
@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::bot::rng::Rng;
+use crate::game::ChessEngine;
+use crate::game::san::{line_to_san, to_san};
+use crate::repertoire::schedule::Schedule;
+use crate::repertoire::{Drill, Repertoire, Verdict};
+
+use super::cursor::Cursor;
+use super::cursor::cursor_event::CursorEvent;
+use super::drawer::{draw_game, draw_message, draw_panel};
+use super::Terminal;
+
+// Leaves the player the time to see their move before the reply
+const REPLY_DELAY: Duration = Duration::from_millis(500);
+
+/// Drills the lines of `repertoire` until the player leaves, the weak ones
+/// more often. The schedule saved at `schedule_path` is updated after each
+/// line
+pub(crate) fn run(repertoire: &Repertoire, schedule_path: &str, mut rng: Rng) -> Result<()> {
+    let mut schedule: Schedule = Schedule::load(schedule_path)?;
+    let mut cursor: Cursor = Cursor::new()?;
+    let mut known: usize = 0;
+    let mut drilled: usize = 0;
+
+    let _terminal: Terminal = Terminal::start()?;
+    loop {
+        let mut drill: Drill = repertoire.drill(&schedule, &mut rng);
+        let Some((success, outcome)) = train(&mut drill, &mut schedule, &mut rng, &mut cursor) else {
+            break;
+        };
+        schedule.save(schedule_path)?;
+        drilled += 1;
+        known += usize::from(success);
+
+        draw_panel(None, &[outcome, format!("Lines known: {known} of {drilled}, {} in the repertoire", repertoire.len())]);
+        draw_message(&format!("Press {} for another line, any other key to leave", cursor.key(&CursorEvent::Next)));
+        if cursor.wait_event() != CursorEvent::Next {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays the drill to the end of its line or to the first move out of the
+/// repertoire. Returns whether the player knew the line and what happened,
+/// None when they left
+fn train(drill: &mut Drill, schedule: &mut Schedule, rng: &mut Rng, cursor: &mut Cursor) -> Option<(bool, String)> {
+    loop {
+        draw_game(drill.chess_game(), cursor, None);
+        draw_panel(None, &[]);
+        if drill.is_complete() {
+            drill.complete(schedule);
+            return Some((true, format!("Line complete: {}", played_line(drill.chess_game()))));
+        }
+
+        if !drill.players_turn() {
+            let _ = io::stdout().flush();
+            thread::sleep(REPLY_DELAY);
+            drill.reply(schedule, rng);
+            continue;
+        }
+
+        draw_message(&format!("Your move, {} to leave", cursor.key(&CursorEvent::Quit)));
+        let mut chess_game: ChessEngine = drill.chess_game().clone();
+        cursor.next_event(&mut chess_game);
+        if CursorEvent::Stop.eq(cursor.event()) {
+            return None;
+        }
+        if chess_game.history().len() == drill.chess_game().history().len() {
+            continue;
+        }
+        let Some(&played) = chess_game.history().last() else {
+            continue;
+        };
+
+        let before: ChessEngine = drill.chess_game().clone();
+        match drill.play(played, schedule) {
+            Verdict::Prepared => {}
+            Verdict::Complete => {
+                draw_game(drill.chess_game(), cursor, None);
+                return Some((true, format!("Line complete: {}", played_line(drill.chess_game()))));
+            }
+            Verdict::Deviation(prepared) => {
+                // Back to the position, the prepared move shown
+                draw_game(&before, cursor, prepared.first().copied());
+                let expected: Vec<String> = prepared.iter().filter_map(|&next| to_san(&before, next)).collect();
+                let deviation: String = to_san(&before, played).unwrap_or_default();
+                return Some((false, format!("{deviation} is out of the repertoire, expected {}", expected.join(" or "))));
+            }
+        }
+    }
+}
+
+fn played_line(chess_game: &ChessEngine) -> String {
+    line_to_san(&chess_game.starting_position(), &chess_game.history())
+}
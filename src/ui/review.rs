@@ -0,0 +1,76 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::bot::Bot;
+use crate::bot::search::SearchLimits;
+use crate::game::ChessEngine;
+use crate::game::board::color::Color;
+use crate::game::board::position::Position;
+use crate::game::san::line_to_san;
+use crate::review::{MoveReview, Review};
+
+use super::cursor::Cursor;
+use super::cursor::cursor_event::CursorEvent;
+use super::drawer::{draw_game, draw_message, draw_panel};
+
+// Each position gets a short search, a long game shouldn't take minutes
+const REVIEW_DEPTH: i16 = 3;
+const REVIEW_MOVETIME: Duration = Duration::from_millis(500);
+
+/// Reviews `chess_game` with `bot` and steps through its moves with the
+/// arrows, any other key goes back to the game
+pub(crate) fn show(chess_game: &ChessEngine, cursor: &mut Cursor, bot: &mut dyn Bot) {
+    let limits: SearchLimits = SearchLimits::movetime(REVIEW_MOVETIME).with_depth(REVIEW_DEPTH);
+    let review: Review = Review::new(chess_game, bot, &limits, &mut |done, total| {
+        draw_message(&format!("Reviewing the game: {done}/{total}"));
+        let _ = io::stdout().flush();
+    });
+    if review.moves().is_empty() {
+        draw_message("No move to review");
+        return;
+    }
+
+    let history: Vec<(Position, Position)> = chess_game.history();
+    let mut ply: usize = review.moves().len() - 1;
+    loop {
+        let mut before: ChessEngine = chess_game.starting_position();
+        for &(from, to) in &history[..ply] {
+            before.try_move(Some(from), Some(to));
+        }
+        let reviewed: &MoveReview = &review.moves()[ply];
+        let mut after: ChessEngine = before.clone();
+        after.try_move(Some(reviewed.played().0), Some(reviewed.played().1));
+
+        draw_game(&after, cursor, Some(reviewed.played()));
+        draw_message("Review: left and right to step through the moves, any other key to leave");
+        draw_panel(Some(reviewed.score_after().win_probability()), &lines(&review, reviewed, &before));
+
+        match cursor.wait_event() {
+            CursorEvent::Previous => ply = ply.saturating_sub(1),
+            CursorEvent::Next => ply = (ply + 1).min(review.moves().len() - 1),
+            _ => break,
+        }
+    }
+
+    draw_panel(None, &[]);
+    draw_message("");
+}
+
+/// The move reviewed, played from `before`, its judgement and the better
+/// line, then the summary
+fn lines(review: &Review, reviewed: &MoveReview, before: &ChessEngine) -> Vec<String> {
+    let suffix: &str = reviewed.judgement().map_or("", |judgement| judgement.suffix());
+    let played: String = line_to_san(before, &[reviewed.played()]);
+    let mut lines: Vec<String> = vec![format!("{played}{suffix} {}", reviewed.score_after())];
+
+    if let Some(judgement) = reviewed.judgement() {
+        lines.push(judgement.to_string());
+    }
+    if let Some(variation) = reviewed.variation() {
+        lines.push(format!("Better: {variation}"));
+    }
+    lines.push(review.summary(Color::White));
+    lines.push(review.summary(Color::Black));
+
+    lines
+}